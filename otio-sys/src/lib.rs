@@ -12,9 +12,21 @@
 //! ```
 //!
 //! The build script will compile the C library and generate Rust bindings automatically.
+//!
+//! The [`safe`] module wraps the raw bindings in owned, drop-managed types
+//! for reading timelines; prefer it over calling the bindings directly.
+
+#[cfg(feature = "otio-native")]
+#[allow(non_upper_case_globals, non_camel_case_types, non_snake_case, dead_code)]
+mod bindings {
+    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+}
+
+#[cfg(feature = "otio-native")]
+pub use bindings::*;
 
 #[cfg(feature = "otio-native")]
-include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+pub mod safe;
 
 #[cfg(not(feature = "otio-native"))]
 pub mod stub {
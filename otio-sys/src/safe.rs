@@ -0,0 +1,327 @@
+//! Safe wrappers over the raw bindings.
+//!
+//! `Timeline` owns the object graph it was read from and releases it on
+//! drop; `Track` and `Clip` borrow from it and cannot outlive it. Times and
+//! strings are copied out into plain Rust values, so nothing returned here
+//! holds a raw pointer past the borrow it came from.
+
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_void};
+use std::path::Path;
+
+use crate as sys;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RationalTime {
+    pub value: f64,
+    pub rate: f64,
+}
+
+impl RationalTime {
+    fn from_raw(raw: sys::RationalTime) -> Self {
+        // SAFETY: RationalTime is a plain value type; the accessors only read it.
+        unsafe {
+            Self {
+                value: sys::RationalTime_value(raw),
+                rate: sys::RationalTime_rate(raw),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeRange {
+    pub start_time: RationalTime,
+    pub duration: RationalTime,
+}
+
+impl TimeRange {
+    fn from_raw(raw: sys::TimeRange) -> Self {
+        // SAFETY: TimeRange is a plain value type; the accessors only read it.
+        unsafe {
+            Self {
+                start_time: RationalTime::from_raw(sys::TimeRange_start_time(raw)),
+                duration: RationalTime::from_raw(sys::TimeRange_duration(raw)),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackKind {
+    Video,
+    Audio,
+    Other(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct OtioError(pub String);
+
+impl std::fmt::Display for OtioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for OtioError {}
+
+/// An `OTIOErrorStatus`, destroyed on drop.
+struct ErrorStatus(*mut sys::OTIOErrorStatus);
+
+impl ErrorStatus {
+    fn new() -> Self {
+        // SAFETY: creates a fresh, independently owned status object.
+        Self(unsafe { sys::OTIOErrorStatus_create() })
+    }
+
+    fn check(&self, context: &str) -> Result<(), OtioError> {
+        // SAFETY: self.0 is valid until drop.
+        let outcome = unsafe { sys::OTIOErrorStatus_get_outcome(self.0) };
+        if outcome == sys::OTIO_ErrorStatus_Outcome_OTIO_ErrorStatus_Outcome_OK {
+            return Ok(());
+        }
+        // SAFETY: the details come back as a copy we own.
+        let details = unsafe { take_string(sys::OTIOErrorStatus_get_details(self.0)) };
+        Err(OtioError(format!("{context}: {}", details.unwrap_or_default())))
+    }
+}
+
+impl Drop for ErrorStatus {
+    fn drop(&mut self) {
+        // SAFETY: created by OTIOErrorStatus_create and destroyed exactly once.
+        unsafe { sys::OTIOErrorStatus_destroy(self.0) }
+    }
+}
+
+/// A timeline read through OpenTimelineIO's own deserializer.
+pub struct Timeline {
+    raw: *mut sys::Timeline,
+}
+
+impl Timeline {
+    pub fn from_json_str(json: &str) -> Result<Self, OtioError> {
+        let input = CString::new(json).map_err(|_| OtioError("OTIO JSON contains a NUL byte".into()))?;
+        let status = ErrorStatus::new();
+        // SAFETY: input is a valid C string and status a live error status.
+        let object = unsafe { sys::SerializableObject_from_json_string(input.as_ptr(), status.0) };
+        Self::from_object(object, &status)
+    }
+
+    pub fn from_json_file(path: &Path) -> Result<Self, OtioError> {
+        let file_name = CString::new(path.to_string_lossy().as_bytes())
+            .map_err(|_| OtioError("Path contains a NUL byte".into()))?;
+        let status = ErrorStatus::new();
+        // SAFETY: file_name is a valid C string and status a live error status.
+        let object = unsafe { sys::SerializableObject_from_json_file(file_name.as_ptr(), status.0) };
+        Self::from_object(object, &status)
+    }
+
+    fn from_object(object: *mut sys::SerializableObject, status: &ErrorStatus) -> Result<Self, OtioError> {
+        if let Err(e) = status.check("Failed to read OTIO") {
+            if !object.is_null() {
+                // SAFETY: we hold the only reference to the object.
+                unsafe { sys::SerializableObject_possibly_delete(object) };
+            }
+            return Err(e);
+        }
+        if object.is_null() {
+            return Err(OtioError("OTIO deserializer returned nothing".into()));
+        }
+
+        // SAFETY: object is a live serializable object.
+        let schema = unsafe { take_string(sys::SerializableObject_schema_name(object)) }.unwrap_or_default();
+        if schema != "Timeline" {
+            // SAFETY: we hold the only reference to the object.
+            unsafe { sys::SerializableObject_possibly_delete(object) };
+            return Err(OtioError(format!("Expected a Timeline, found {schema}")));
+        }
+        Ok(Self { raw: object.cast() })
+    }
+
+    pub fn name(&self) -> String {
+        // SAFETY: self.raw is live for the lifetime of self.
+        unsafe { take_string(sys::Timeline_name(self.raw)) }.unwrap_or_default()
+    }
+
+    pub fn global_start_time(&self) -> Option<RationalTime> {
+        // SAFETY: self.raw is live for the lifetime of self.
+        let start = unsafe { sys::Timeline_global_start_time(self.raw) };
+        start.valid.then(|| RationalTime::from_raw(start.value))
+    }
+
+    /// The tracks of the timeline's top-level stack, in order.
+    pub fn tracks(&self) -> Vec<Track<'_>> {
+        // SAFETY: the stack is owned by the timeline and lives as long as it.
+        let stack = unsafe { sys::Timeline_tracks(self.raw) };
+        if stack.is_null() {
+            return Vec::new();
+        }
+        // SAFETY: stack is live; children are retained by it.
+        let children = unsafe { Children::new(sys::Stack_children(stack)) };
+        children.iter()
+            .filter(|&child| schema_of(child.cast()) == "Track")
+            .map(|child| Track { raw: child.cast(), _owner: PhantomData })
+            .collect()
+    }
+}
+
+impl Drop for Timeline {
+    fn drop(&mut self) {
+        // SAFETY: the timeline is the root we were handed; nothing else
+        // references it once the wrapper is gone.
+        unsafe { sys::SerializableObject_possibly_delete(self.raw.cast()) };
+    }
+}
+
+// The object graph is not shared with any other thread and the C library
+// keeps no thread-local state for it.
+unsafe impl Send for Timeline {}
+
+pub struct Track<'a> {
+    raw: *mut sys::Track,
+    _owner: PhantomData<&'a Timeline>,
+}
+
+impl<'a> Track<'a> {
+    pub fn name(&self) -> String {
+        // SAFETY: the track is retained by the timeline borrowed for 'a.
+        unsafe { take_string(sys::Track_name(self.raw)) }.unwrap_or_default()
+    }
+
+    pub fn kind(&self) -> TrackKind {
+        // SAFETY: as above.
+        let kind = unsafe { take_string(sys::Track_kind(self.raw)) }.unwrap_or_default();
+        match kind.as_str() {
+            "Video" => TrackKind::Video,
+            "Audio" => TrackKind::Audio,
+            _ => TrackKind::Other(kind),
+        }
+    }
+
+    /// The clips on the track, skipping gaps, transitions and nested stacks.
+    pub fn clips(&self) -> Vec<Clip<'a>> {
+        // SAFETY: children are retained by the track for 'a.
+        let children = unsafe { Children::new(sys::Track_children(self.raw)) };
+        children.iter()
+            .filter(|&child| schema_of(child.cast()) == "Clip")
+            .map(|child| Clip { raw: child.cast(), _owner: PhantomData })
+            .collect()
+    }
+}
+
+pub struct Clip<'a> {
+    raw: *mut sys::Clip,
+    _owner: PhantomData<&'a Timeline>,
+}
+
+impl Clip<'_> {
+    pub fn name(&self) -> String {
+        // SAFETY: the clip is retained by its track for the borrow.
+        unsafe { take_string(sys::Clip_name(self.raw)) }.unwrap_or_default()
+    }
+
+    pub fn source_range(&self) -> Option<TimeRange> {
+        // SAFETY: as above.
+        let range = unsafe { sys::Clip_source_range(self.raw) };
+        range.valid.then(|| TimeRange::from_raw(range.value))
+    }
+
+    /// The part of the media the clip plays: its source range, or the
+    /// media's available range when it has none.
+    pub fn trimmed_range(&self) -> Result<TimeRange, OtioError> {
+        let status = ErrorStatus::new();
+        // SAFETY: clip and status are live.
+        let range = unsafe { sys::Clip_trimmed_range(self.raw, status.0) };
+        status.check("Failed to compute trimmed range")?;
+        Ok(TimeRange::from_raw(range))
+    }
+
+    /// Where the clip sits on its track.
+    pub fn range_in_parent(&self) -> Result<TimeRange, OtioError> {
+        let status = ErrorStatus::new();
+        // SAFETY: clip and status are live.
+        let range = unsafe { sys::Clip_range_in_parent(self.raw, status.0) };
+        status.check("Failed to compute range in parent")?;
+        Ok(TimeRange::from_raw(range))
+    }
+
+    /// The target URL of an external media reference.
+    pub fn media_url(&self) -> Option<String> {
+        // SAFETY: the media reference is owned by the clip.
+        let reference = unsafe { sys::Clip_media_reference(self.raw) };
+        if reference.is_null() || schema_of(reference.cast()) != "ExternalReference" {
+            return None;
+        }
+        // SAFETY: schema checked above.
+        unsafe { take_string(sys::ExternalReference_target_url(reference.cast())) }
+            .filter(|url| !url.is_empty())
+    }
+}
+
+/// A `ComposableRetainerVector` copied out of a composition, destroyed on
+/// drop. The composables it points at stay retained by their parent.
+struct Children(*mut sys::ComposableRetainerVector);
+
+impl Children {
+    /// # Safety
+    /// `raw` must be a vector returned by a `*_children` call, or null.
+    unsafe fn new(raw: *mut sys::ComposableRetainerVector) -> Self {
+        Self(raw)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = *mut sys::Composable> + '_ {
+        let len = if self.0.is_null() {
+            0
+        } else {
+            // SAFETY: self.0 is a live vector.
+            unsafe { sys::ComposableRetainerVector_size(self.0) }
+        };
+        (0..len).filter_map(move |i| {
+            // SAFETY: i < size; the retainer returned by `at` is a copy we release.
+            unsafe {
+                let retainer = sys::ComposableRetainerVector_at(self.0, i);
+                if retainer.is_null() {
+                    return None;
+                }
+                let value = sys::RetainerComposable_value(retainer);
+                sys::RetainerComposable_managed_destroy(retainer);
+                (!value.is_null()).then_some(value)
+            }
+        })
+    }
+}
+
+impl Drop for Children {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            // SAFETY: the vector was handed to us and is destroyed exactly once.
+            unsafe { sys::ComposableRetainerVector_destroy(self.0) }
+        }
+    }
+}
+
+fn schema_of(object: *mut sys::SerializableObject) -> String {
+    // SAFETY: callers pass live objects.
+    unsafe { take_string(sys::SerializableObject_schema_name(object)) }.unwrap_or_default()
+}
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+/// Copy a string returned by the C bindings into Rust and free it. Every
+/// string accessor in the bindings `malloc`s a fresh copy and leaves
+/// releasing it to the caller.
+///
+/// # Safety
+/// `ptr` must be null or a string returned by the bindings that nothing
+/// else frees.
+unsafe fn take_string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    let string = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+    free(ptr as *mut c_void);
+    Some(string)
+}
@@ -8,6 +8,18 @@ extern "C" {
 
 /* Core OTIO C types and functions */
 /* These will be available once vendor/OpenTimelineIO-C-Bindings is cloned */
+#include <copentime/rationalTime.h>
+#include <copentime/timeRange.h>
+#include <copentime/optionalOpenTime.h>
+#include <copentimelineio/errorStatus.h>
+#include <copentimelineio/serializableObject.h>
+#include <copentimelineio/timeline.h>
+#include <copentimelineio/stack.h>
+#include <copentimelineio/track.h>
+#include <copentimelineio/clip.h>
+#include <copentimelineio/composable.h>
+#include <copentimelineio/composableRetainerVector.h>
+#include <copentimelineio/externalReference.h>
 
 #ifdef __cplusplus
 }
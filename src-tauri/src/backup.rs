@@ -1,6 +1,14 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::fs;
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// One backup at a time, so two never write the same temporary files.
+static BACKUP_LOCK: parking_lot::Mutex<()> = parking_lot::Mutex::new(());
 
 fn backup_root() -> PathBuf {
     dirs::home_dir()
@@ -24,6 +32,29 @@ pub struct ProjectEntry {
     pub last_backup: String,
 }
 
+/// What a backup folder holds, put in place with the database once every
+/// object is there, so it never lists an object that isn't.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub created_at: String,
+    /// Goes up by one whenever a backup captures a changed database.
+    pub db_generation: u64,
+    pub db_hash: String,
+    /// Size of every object in the backup by hash, each checked against its
+    /// hash when it was copied.
+    pub objects: BTreeMap<String, u64>,
+}
+
+fn read_manifest(dir: &Path) -> Option<BackupManifest> {
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_manifest(path: &Path, manifest: &BackupManifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest).map_err(|e| format!("{e}"))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write backup manifest: {e}"))
+}
+
 pub fn get_registry() -> Vec<ProjectEntry> {
     let path = registry_path();
     if !path.exists() {
@@ -61,15 +92,47 @@ pub fn backup_project(project_name: &str, project_path: &str) -> Result<ProjectE
     let dest_dir = backup_root().join(&safe_name);
     fs::create_dir_all(&dest_dir).map_err(|e| format!("Failed to create backup dir: {e}"))?;
 
-    let dest_db = dest_dir.join("editgit.db");
-    fs::copy(&db_path, &dest_db).map_err(|e| format!("Failed to copy database: {e}"))?;
+    let _guard = BACKUP_LOCK.lock();
+    let previous = read_manifest(&dest_dir).unwrap_or_default();
+
+    // The database goes in last, once every object it refers to is there;
+    // until then the previous backup stays whole.
+    let next_db = dest_dir.join("editgit.db.next");
+    crate::watcher::snapshot::snapshot_database(&db_path, &next_db)
+        .map_err(|e| format!("Failed to copy database: {e}"))?;
+    let db_hash = crate::hasher::hash_file(&next_db).map_err(|e| format!("Failed to hash database: {e}"))?;
+    let db_generation = if db_hash == previous.db_hash { previous.db_generation } else { previous.db_generation + 1 };
 
-    let objects_dir = turnaround_dir.join("objects");
+    let src_objects = turnaround_dir.join("objects");
     let dest_objects = dest_dir.join("objects");
-    if objects_dir.exists() {
-        copy_dir_recursive(&objects_dir, &dest_objects)
-            .map_err(|e| format!("Failed to backup objects: {e}"))?;
+    let wanted = list_objects(&src_objects).map_err(|e| format!("Failed to list objects: {e}"))?;
+    let synced = sync_objects(&src_objects, &dest_objects, &wanted, &previous.objects)
+        .map_err(|e| format!("Failed to backup objects: {e}"))?;
+    if !synced.failed.is_empty() {
+        let _ = fs::remove_file(&next_db);
+        return Err(format!("Backup incomplete, could not copy {}", describe_failed(&synced.failed)));
+    }
+
+    let manifest = BackupManifest {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        db_generation,
+        db_hash,
+        objects: synced.objects,
+    };
+    let next_manifest = dest_dir.join(format!("{MANIFEST_FILE}.next"));
+    if let Err(e) = write_manifest(&next_manifest, &manifest) {
+        let _ = fs::remove_file(&next_db);
+        return Err(e);
     }
+    // Both are written in full before either goes in place. Interrupted
+    // between the two renames, the backup holds the new database beside the
+    // old manifest, and recovery checks each object by its hash instead.
+    fs::rename(&next_db, dest_dir.join("editgit.db")).map_err(|e| format!("Failed to copy database: {e}"))?;
+    fs::rename(&next_manifest, dest_dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("Failed to write backup manifest: {e}"))?;
+    // Objects the project has since dropped, now that the manifest no
+    // longer lists them.
+    prune_objects(&dest_objects, &manifest.objects).map_err(|e| format!("Failed to prune backup objects: {e}"))?;
 
     let now = chrono::Utc::now().to_rfc3339();
     let entry = ProjectEntry {
@@ -98,36 +161,185 @@ pub fn recover_project(original_path: &str, target_path: &str) -> Result<(), Str
         return Err("Backup directory no longer exists".to_string());
     }
 
+    let src_db = backup_dir.join("editgit.db");
+    let src_objects = backup_dir.join("objects");
+    // A manifest only vouches for the database it was written with. Backups
+    // from before manifests existed, or interrupted between putting the
+    // database and the manifest in place, are checked object by object
+    // against the hashes they are named by as they are copied.
+    let manifest = match read_manifest(backup_dir) {
+        Some(manifest) => {
+            let db_hash = crate::hasher::hash_file(&src_db)
+                .map_err(|e| format!("Failed to read backup database: {e}"))?;
+            Some(manifest).filter(|m| m.db_hash == db_hash)
+        }
+        None => None,
+    };
+    let objects = match manifest {
+        Some(manifest) => {
+            let missing: Vec<(String, String)> = manifest.objects.iter()
+                .filter_map(|(hash, size)| {
+                    let Some(path) = object_path(&src_objects, hash) else {
+                        return Some((hash.clone(), "not a valid object hash".to_string()));
+                    };
+                    match fs::metadata(path) {
+                        Ok(meta) if meta.len() == *size => None,
+                        Ok(_) => Some((hash.clone(), "size differs from the manifest".to_string())),
+                        Err(_) => Some((hash.clone(), "missing from the backup".to_string())),
+                    }
+                })
+                .collect();
+            if !missing.is_empty() {
+                return Err(format!("Backup does not match its manifest for {}", describe_failed(&missing)));
+            }
+            manifest.objects
+        }
+        None => list_objects(&src_objects).map_err(|e| format!("Failed to list backup objects: {e}"))?,
+    };
+
     let target = Path::new(target_path);
     let turnaround_dir = target.join(".turnaround");
     fs::create_dir_all(&turnaround_dir).map_err(|e| format!("{e}"))?;
 
-    let src_db = backup_dir.join("editgit.db");
+    let synced = sync_objects(&src_objects, &turnaround_dir.join("objects"), &objects, &BTreeMap::new())
+        .map_err(|e| format!("Failed to restore objects: {e}"))?;
+    if !synced.failed.is_empty() {
+        return Err(format!("Restore incomplete, could not copy {}", describe_failed(&synced.failed)));
+    }
+
     if src_db.exists() {
         fs::copy(&src_db, turnaround_dir.join("editgit.db")).map_err(|e| format!("{e}"))?;
     }
 
-    let src_objects = backup_dir.join("objects");
-    if src_objects.exists() {
-        let dest_objects = turnaround_dir.join("objects");
-        copy_dir_recursive(&src_objects, &dest_objects)
-            .map_err(|e| format!("Failed to restore objects: {e}"))?;
+    Ok(())
+}
+
+struct SyncResult {
+    /// Size of every object `dst` now holds, by hash.
+    objects: BTreeMap<String, u64>,
+    /// Objects that could not be copied, with why.
+    failed: Vec<(String, String)>,
+}
+
+fn describe_failed(failed: &[(String, String)]) -> String {
+    let list: Vec<String> = failed.iter().map(|(hash, why)| format!("{hash} ({why})")).collect();
+    format!("{} objects: {}", failed.len(), list.join(", "))
+}
+
+/// Copy the `objects` (size by hash) of `src` that `dst` is missing,
+/// checking each copy against its hash. Objects in `verified` are trusted if
+/// their size still matches; others already in `dst` are rehashed.
+fn sync_objects(
+    src: &Path,
+    dst: &Path,
+    objects: &BTreeMap<String, u64>,
+    verified: &BTreeMap<String, u64>,
+) -> Result<SyncResult, std::io::Error> {
+    let mut result = SyncResult { objects: BTreeMap::new(), failed: Vec::new() };
+    for (hash, size) in objects {
+        let (Some(src_path), Some(dst_path)) = (object_path(src, hash), object_path(dst, hash)) else {
+            result.failed.push((hash.clone(), "not a valid object hash".to_string()));
+            continue;
+        };
+        let dst_size = fs::metadata(&dst_path).map(|m| m.len()).ok();
+
+        let present = dst_size == Some(*size)
+            && (verified.get(hash) == Some(size) || crate::hasher::hash_file(&dst_path)? == *hash);
+        if !present {
+            if let Err(e) = copy_verified(&src_path, &dst_path, hash) {
+                result.failed.push((hash.clone(), e.to_string()));
+                continue;
+            }
+        }
+        result.objects.insert(hash.clone(), *size);
     }
+    Ok(result)
+}
 
+/// Remove the objects in `dir` that aren't in `keep`.
+fn prune_objects(dir: &Path, keep: &BTreeMap<String, u64>) -> Result<(), std::io::Error> {
+    for hash in list_objects(dir)?.keys() {
+        if keep.contains_key(hash) {
+            continue;
+        }
+        if let Some(path) = object_path(dir, hash) {
+            fs::remove_file(path)?;
+        }
+    }
     Ok(())
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
-    fs::create_dir_all(dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            fs::copy(&src_path, &dst_path)?;
+/// Where the object `hash` is stored under `dir`; `None` if `hash` is not a
+/// hex digest, as in a damaged or hand-edited manifest.
+fn object_path(dir: &Path, hash: &str) -> Option<PathBuf> {
+    if hash.len() <= 2 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(dir.join(hash.get(..2)?).join(hash.get(2..)?))
+}
+
+/// Objects stored under `dir` as `<first two hex digits>/<rest>`, with
+/// their sizes, by hash.
+fn list_objects(dir: &Path) -> Result<BTreeMap<String, u64>, std::io::Error> {
+    let mut objects = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(objects);
+    }
+    for prefix in fs::read_dir(dir)? {
+        let prefix = prefix?;
+        let prefix_name = prefix.file_name().to_string_lossy().to_string();
+        if prefix_name.len() != 2 || !prefix.path().is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(prefix.path())? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Derived files are `<object>.<suffix>`.
+            if name.contains('.') || !entry.file_type()?.is_file() {
+                continue;
+            }
+            let hash = format!("{prefix_name}{name}");
+            // Anything else that ended up in the folder isn't an object.
+            if object_path(dir, &hash).is_none() {
+                continue;
+            }
+            objects.insert(hash, entry.metadata()?.len());
         }
     }
-    Ok(())
+    Ok(objects)
+}
+
+/// Copy `src` to `dst` through a temporary file, hashing on the way, and
+/// only put it in place if the content matches `hash`.
+fn copy_verified(src: &Path, dst: &Path, hash: &str) -> Result<(), std::io::Error> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut partial = dst.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let result = (|| {
+        let mut reader = fs::File::open(src)?;
+        let mut writer = fs::File::create(&partial)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 1024 * 1024];
+        loop {
+            let n = reader.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+            writer.write_all(&buffer[..n])?;
+        }
+        writer.sync_all()?;
+        if hex::encode(hasher.finalize()) != hash {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "content does not match its hash"));
+        }
+        fs::rename(&partial, dst)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result
 }
@@ -0,0 +1,120 @@
+use std::path::Path;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::AppState;
+use crate::compare::{lut, still};
+use crate::db::schema::{self, FileSnapshot};
+use crate::error::AppError;
+use crate::vcs::object_store::ObjectStore;
+
+/// Compare the `.cube` LUT at `file_path` between two versions: grid size
+/// and domain changes, per-channel output deltas and, with `include_chart`,
+/// a ColorChecker pushed through both.
+#[tauri::command]
+pub fn compare_luts(
+    state: State<AppState>,
+    commit_a: String,
+    commit_b: String,
+    file_path: String,
+    include_chart: Option<bool>,
+) -> Result<lut::LutComparison, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+
+    let obj_store = ObjectStore::new(&Path::new(&project_path).join(".turnaround"));
+
+    let (old, new) = {
+        let db = state.db.lock();
+        (find_snapshot(&db.conn, &commit_a, &file_path)?, find_snapshot(&db.conn, &commit_b, &file_path)?)
+    };
+    let load = |snapshot: &FileSnapshot| -> Result<lut::CubeLut, AppError> {
+        let content = std::fs::read_to_string(obj_store.retrieve_path(&snapshot.content_hash))?;
+        lut::parse_cube(&content).map_err(|e| AppError::Compare(format!("{file_path}: {e}")))
+    };
+    let old = load(&old)?;
+    let new = load(&new)?;
+
+    Ok(lut::compare_luts(&old, &new, include_chart.unwrap_or(false)))
+}
+
+/// Longer edge of the cached thumbnails, in pixels.
+const THUMBNAIL_SIZE: u32 = 320;
+/// Heatmaps are cached no larger than this on their longer edge.
+const HEATMAP_MAX_SIDE: u32 = 1024;
+
+#[derive(Serialize, Deserialize)]
+pub struct ImageDiffReport {
+    #[serde(flatten)]
+    pub comparison: still::ImageComparison,
+    pub heatmap_path: String,
+    pub old_thumbnail_path: String,
+    pub new_thumbnail_path: String,
+}
+
+/// Visual diff of the still at `file_path` between two versions. Thumbnails
+/// are cached beside each object and the heatmap and metrics under the pair,
+/// so asking again for the same pair of versions does not decode anything.
+#[tauri::command]
+pub fn compare_images(
+    state: State<AppState>,
+    commit_a: String,
+    commit_b: String,
+    file_path: String,
+) -> Result<ImageDiffReport, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+
+    let obj_store = ObjectStore::new(&Path::new(&project_path).join(".turnaround"));
+
+    let (old, new) = {
+        let db = state.db.lock();
+        (find_snapshot(&db.conn, &commit_a, &file_path)?, find_snapshot(&db.conn, &commit_b, &file_path)?)
+    };
+    let old_thumbnail = obj_store.derived_path(&old.content_hash, "thumb.png");
+    let new_thumbnail = obj_store.derived_path(&new.content_hash, "thumb.png");
+    let heatmap = obj_store.pair_derived_path(&old.content_hash, &new.content_hash, "diff.png")?;
+    let metrics = obj_store.pair_derived_path(&old.content_hash, &new.content_hash, "diff.json")?;
+
+    if [&old_thumbnail, &new_thumbnail, &heatmap].iter().all(|p| p.exists()) {
+        let cached = std::fs::read_to_string(&metrics).ok()
+            .and_then(|json| serde_json::from_str::<ImageDiffReport>(&json).ok());
+        if let Some(report) = cached {
+            return Ok(report);
+        }
+    }
+
+    let load = |snapshot: &FileSnapshot| -> Result<DynamicImage, AppError> {
+        let bytes = std::fs::read(obj_store.retrieve_path(&snapshot.content_hash))?;
+        still::decode(&bytes).map_err(|e| AppError::Compare(format!("{file_path}: {e}")))
+    };
+    let old_image = load(&old)?;
+    let new_image = load(&new)?;
+
+    save_image(&still::thumbnail(&old_image, THUMBNAIL_SIZE), &old_thumbnail)?;
+    save_image(&still::thumbnail(&new_image, THUMBNAIL_SIZE), &new_thumbnail)?;
+    let diff = still::compare_images(&old_image, &new_image);
+    save_image(&still::thumbnail(&DynamicImage::ImageRgb8(diff.heatmap), HEATMAP_MAX_SIDE), &heatmap)?;
+
+    let report = ImageDiffReport {
+        comparison: diff.comparison,
+        heatmap_path: heatmap.to_string_lossy().to_string(),
+        old_thumbnail_path: old_thumbnail.to_string_lossy().to_string(),
+        new_thumbnail_path: new_thumbnail.to_string_lossy().to_string(),
+    };
+    let json = serde_json::to_string(&report)
+        .map_err(|e| AppError::Compare(format!("Failed to cache image diff: {e}")))?;
+    std::fs::write(&metrics, json)?;
+    Ok(report)
+}
+
+fn save_image(img: &DynamicImage, path: &Path) -> Result<(), AppError> {
+    img.save(path).map_err(|e| AppError::Compare(format!("Failed to write {}: {e}", path.display())))
+}
+
+fn find_snapshot(conn: &rusqlite::Connection, commit_id: &str, file_path: &str) -> Result<FileSnapshot, AppError> {
+    schema::get_snapshots_for_commit(conn, commit_id)?
+        .into_iter()
+        .find(|s| s.file_path == file_path)
+        .ok_or_else(|| AppError::Compare(format!("{file_path} is not part of version {commit_id}")))
+}
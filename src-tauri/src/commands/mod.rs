@@ -3,3 +3,4 @@ pub mod registry;
 pub mod vcs;
 pub mod watcher;
 pub mod timeline;
+pub mod compare;
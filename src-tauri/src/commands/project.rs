@@ -4,6 +4,7 @@ use crate::AppState;
 use crate::db::{Database, schema};
 use crate::error::AppError;
 use crate::registry::ProjectEntry;
+use crate::vcs::ignore::{self, IgnoreRules};
 use crate::vcs::object_store::ObjectStore;
 use uuid::Uuid;
 use chrono::Utc;
@@ -34,7 +35,7 @@ pub fn init_project(
     name: String,
 ) -> Result<ProjectInfo, AppError> {
     let project_path = Path::new(&path);
-    std::fs::create_dir_all(&project_path)?;
+    std::fs::create_dir_all(project_path)?;
 
     let turnaround_dir = project_path.join(".turnaround");
     std::fs::create_dir_all(&turnaround_dir)?;
@@ -74,6 +75,11 @@ pub fn init_project(
         });
     }
 
+    let ignore_path = project_path.join(ignore::IGNORE_FILE);
+    if !ignore_path.exists() {
+        std::fs::write(&ignore_path, ignore::DEFAULT_IGNORE)?;
+    }
+
     let project_id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
 
@@ -242,7 +248,8 @@ pub fn get_project_tree(state: State<AppState>) -> Result<FileNode, AppError> {
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| "Project".to_string());
 
-    Ok(build_tree(root, &name, "")?)
+    let rules = IgnoreRules::load(root);
+    Ok(build_tree(root, &name, "", &rules)?)
 }
 
 #[tauri::command]
@@ -317,7 +324,7 @@ pub fn recover_project_from_backup(
     })
 }
 
-fn build_tree(path: &Path, name: &str, rel_path: &str) -> Result<FileNode, std::io::Error> {
+fn build_tree(path: &Path, name: &str, rel_path: &str, rules: &IgnoreRules) -> Result<FileNode, std::io::Error> {
     if path.is_dir() {
         let mut children = Vec::new();
 
@@ -330,17 +337,17 @@ fn build_tree(path: &Path, name: &str, rel_path: &str) -> Result<FileNode, std::
                 continue;
             }
 
-            if entry_name == "node_modules" || entry_name == "target" || entry_name == "__pycache__" {
-                continue;
-            }
-
             let child_rel = if rel_path.is_empty() {
                 entry_name.clone()
             } else {
                 format!("{}/{}", rel_path, entry_name)
             };
 
-            children.push(build_tree(&entry_path, &entry_name, &child_rel)?);
+            if rules.is_ignored(&child_rel, entry_path.is_dir()) {
+                continue;
+            }
+
+            children.push(build_tree(&entry_path, &entry_name, &child_rel, rules)?);
         }
 
         children.sort_by(|a, b| {
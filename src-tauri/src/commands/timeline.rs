@@ -1,43 +1,542 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::State;
 use crate::AppState;
-use crate::db::schema;
+use crate::db::schema::{self, Commit, FileSnapshot};
 use crate::error::AppError;
-use crate::timeline::{self, diff, parser};
+use crate::timeline::{self, diff, lineage, parser, report, subtitle, usage, validate, writer};
+use crate::timeline::time::FrameRate;
+use crate::vcs;
 use crate::vcs::object_store::ObjectStore;
+use crate::vcs::tracking::TrackingConfig;
+use serde::Serialize;
 
+#[derive(Serialize)]
+pub struct TimelineExport {
+    pub source_path: String,
+    pub dest_path: String,
+    pub timeline_name: String,
+    pub track_count: usize,
+    pub clip_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct ChangeListReport {
+    pub list: report::ChangeList,
+    pub content: String,
+    pub dest_path: Option<String>,
+}
+
+/// A timeline file that could not be read. Plain `.xml` files are often not
+/// timelines at all, so one bad file does not fail the whole diff.
+#[derive(Serialize)]
+pub struct SkippedTimelineFile {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Serialize)]
+pub struct CommitTimelineDiff {
+    #[serde(flatten)]
+    pub diff: diff::TimelineSetDiff,
+    pub skipped: Vec<SkippedTimelineFile>,
+}
+
+/// Diff every timeline in `commit_a` against `commit_b`, paired by path and,
+/// inside multi-timeline files, by sequence name.
 #[tauri::command]
 pub fn get_timeline_diff(
     state: State<AppState>,
     commit_a: String,
     commit_b: String,
-) -> Result<diff::TimelineDiff, AppError> {
+) -> Result<CommitTimelineDiff, AppError> {
     let project_path = state.active_project_path.lock().clone()
         .ok_or(AppError::NoActiveProject)?;
 
-    let db = state.db.lock();
     let turnaround_dir = Path::new(&project_path).join(".turnaround");
     let obj_store = ObjectStore::new(&turnaround_dir);
 
-    let snapshots_a = schema::get_snapshots_for_commit(&db.conn, &commit_a)?;
-    let snapshots_b = schema::get_snapshots_for_commit(&db.conn, &commit_b)?;
+    let (old_snapshots, new_snapshots) = {
+        let db = state.db.lock();
+        (vcs::commit::get_detail(&db.conn, &commit_a)?.1, vcs::commit::get_detail(&db.conn, &commit_b)?.1)
+    };
+    let mut skipped = Vec::new();
+    let old = parse_commit_timelines(&obj_store, &old_snapshots, &mut skipped);
+    let new = parse_commit_timelines(&obj_store, &new_snapshots, &mut skipped);
+    if old.is_empty() && new.is_empty() && skipped.is_empty() {
+        return Err(AppError::Timeline("No timeline files found in either version".into()));
+    }
 
-    let timeline_file_a = snapshots_a.iter()
-        .find(|s| is_timeline_type(&s.file_type))
-        .ok_or_else(|| AppError::Timeline("No timeline file found in version A".into()))?;
-    let timeline_file_b = snapshots_b.iter()
-        .find(|s| is_timeline_type(&s.file_type))
-        .ok_or_else(|| AppError::Timeline("No timeline file found in version B".into()))?;
+    Ok(CommitTimelineDiff {
+        diff: diff::diff_timeline_sets(&old, &new),
+        skipped,
+    })
+}
 
-    let path_a = obj_store.retrieve_path(&timeline_file_a.content_hash);
-    let path_b = obj_store.retrieve_path(&timeline_file_b.content_hash);
+/// Build a change list between two versions for sound and VFX vendors,
+/// rendered as CSV, Markdown or a self-contained HTML page. When `dest_path`
+/// is given the rendered report is also written there.
+///
+/// The report covers one timeline, paired between the versions as in
+/// `get_timeline_diff`. `file_path` and `timeline_name` pick it; they
+/// may be left out only when that leaves a single paired timeline.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn generate_change_list(
+    state: State<AppState>,
+    commit_a: String,
+    commit_b: String,
+    format: report::ReportFormat,
+    track_filter: Option<report::TrackFilter>,
+    file_path: Option<String>,
+    timeline_name: Option<String>,
+    dest_path: Option<String>,
+) -> Result<ChangeListReport, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
 
-    let tl_a = parser::parse_timeline_from_path(&path_a)
-        .map_err(AppError::Timeline)?;
-    let tl_b = parser::parse_timeline_from_path(&path_b)
+    let turnaround_dir = Path::new(&project_path).join(".turnaround");
+    let obj_store = ObjectStore::new(&turnaround_dir);
+
+    let (commit_old, old_snapshots, commit_new, new_snapshots) = {
+        let db = state.db.lock();
+        let commit_old = schema::get_commit(&db.conn, &commit_a)?
+            .ok_or_else(|| AppError::Timeline(format!("Version {commit_a} not found")))?;
+        let commit_new = schema::get_commit(&db.conn, &commit_b)?
+            .ok_or_else(|| AppError::Timeline(format!("Version {commit_b} not found")))?;
+        let old_snapshots = schema::get_snapshots_for_commit(&db.conn, &commit_a)?;
+        let new_snapshots = schema::get_snapshots_for_commit(&db.conn, &commit_b)?;
+        (commit_old, old_snapshots, commit_new, new_snapshots)
+    };
+    let mut skipped = Vec::new();
+    let old = parse_commit_timelines(&obj_store, &old_snapshots, &mut skipped);
+    let new = parse_commit_timelines(&obj_store, &new_snapshots, &mut skipped);
+
+    let set = diff::diff_timeline_sets(&old, &new);
+    let mut candidates: Vec<diff::PairedTimelineDiff> = set.timelines.into_iter()
+        .filter(|t| file_path.as_ref().is_none_or(|p| &t.path == p))
+        .filter(|t| timeline_name.as_ref().is_none_or(|n| &t.name == n || &t.diff.old_name == n))
+        .collect();
+    let paired = match candidates.len() {
+        1 => candidates.remove(0),
+        0 => {
+            let wanted = match (&file_path, &timeline_name) {
+                (Some(path), Some(name)) => format!("\"{name}\" in {path}"),
+                (Some(path), None) => path.clone(),
+                (None, Some(name)) => format!("\"{name}\""),
+                (None, None) => "A timeline".to_string(),
+            };
+            let mut message = format!("{wanted} is not in both versions");
+            if let Some(s) = skipped.first() {
+                message.push_str(&format!(" ({} could not be read: {})", s.path, s.error));
+            }
+            return Err(AppError::Timeline(message));
+        }
+        _ => {
+            let names: Vec<String> = candidates.iter().map(|t| format!("{} — {}", t.path, t.name)).collect();
+            return Err(AppError::Timeline(format!(
+                "Both versions hold several timelines; choose one of: {}",
+                names.join(", "),
+            )));
+        }
+    };
+
+    let list = report::build_change_list(
+        &paired.diff,
+        &version_label(&commit_old),
+        &version_label(&commit_new),
+        track_filter.unwrap_or_default(),
+    );
+    let content = report::render(&list, format);
+
+    if let Some(dest) = &dest_path {
+        std::fs::write(dest, &content)?;
+    }
+
+    Ok(ChangeListReport { list, content, dest_path })
+}
+
+/// Convert a committed timeline (OTIO, FCPXML, xmeml or EDL) to an `.otio` file.
+/// When `file_path` is omitted the first timeline file in the commit is used,
+/// and when `timeline_name` is omitted the first timeline in that file.
+#[tauri::command]
+pub fn export_timeline_otio(
+    state: State<AppState>,
+    commit_id: String,
+    file_path: Option<String>,
+    timeline_name: Option<String>,
+    dest_path: String,
+) -> Result<TimelineExport, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+
+    let turnaround_dir = Path::new(&project_path).join(".turnaround");
+    let obj_store = ObjectStore::new(&turnaround_dir);
+
+    let (_commit, mut snapshots) = vcs::commit::get_detail(&state.db.lock().conn, &commit_id)?;
+    snapshots.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    let snapshot = match &file_path {
+        Some(path) => snapshots.iter()
+            .find(|s| &s.file_path == path)
+            .ok_or_else(|| AppError::Timeline(format!("{path} is not part of this version")))?,
+        None => snapshots.iter()
+            .find(|s| is_timeline_path(&s.file_path))
+            .ok_or_else(|| AppError::Timeline("No timeline file found in this version".into()))?,
+    };
+
+    let tl = select_timeline(load_snapshot_timelines(&obj_store, snapshot)?, timeline_name.as_deref())
+        .ok_or_else(|| AppError::Timeline(format!(
+            "\"{}\" is not in {}", timeline_name.as_deref().unwrap_or_default(), snapshot.file_path,
+        )))?;
+    writer::write_otio_file(&tl, Path::new(&dest_path))
         .map_err(AppError::Timeline)?;
 
-    Ok(diff::diff_timelines(&tl_a, &tl_b))
+    Ok(TimelineExport {
+        source_path: snapshot.file_path.clone(),
+        dest_path,
+        timeline_name: tl.name.clone(),
+        track_count: tl.tracks.len(),
+        clip_count: tl.tracks.iter().map(|t| t.clips.len()).sum(),
+    })
+}
+
+#[derive(Serialize)]
+pub struct TimelineValidation {
+    pub path: String,
+    pub name: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<validate::LintIssue>,
+}
+
+#[derive(Serialize)]
+pub struct CommitValidation {
+    pub commit_id: String,
+    pub timelines: Vec<TimelineValidation>,
+    pub skipped: Vec<SkippedTimelineFile>,
+}
+
+/// Lint every timeline in a commit before it goes out: overlaps, short
+/// gaps, flash frames, mixed frame rates, and media missing from the
+/// version or from disk.
+#[tauri::command]
+pub fn validate_timelines(
+    state: State<AppState>,
+    commit_id: String,
+    options: Option<validate::LintOptions>,
+) -> Result<CommitValidation, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+    let project_root = Path::new(&project_path);
+
+    let obj_store = ObjectStore::new(&project_root.join(".turnaround"));
+    let options = options.unwrap_or_default();
+
+    let (_commit, snapshots) = vcs::commit::get_detail(&state.db.lock().conn, &commit_id)?;
+    let committed: Vec<PathBuf> = snapshots.iter().map(|s| PathBuf::from(&s.file_path)).collect();
+
+    let mut skipped = Vec::new();
+    let mut timelines = Vec::new();
+    for (path, tl) in parse_commit_timelines(&obj_store, &snapshots, &mut skipped) {
+        let timeline_dir = Path::new(&path).parent().unwrap_or(Path::new("")).to_path_buf();
+        let mut issues = validate::validate_timeline(&tl, &options);
+        issues.extend(validate::check_media(
+            &tl,
+            |media_ref| validate::match_media_file(media_ref, &committed, project_root, &timeline_dir).is_some(),
+            |media_ref| validate::media_on_disk(media_ref, project_root, &timeline_dir),
+        ));
+        issues.sort_by_key(|i| i.severity);
+
+        timelines.push(TimelineValidation {
+            errors: issues.iter().filter(|i| i.severity == validate::Severity::Error).count(),
+            warnings: issues.iter().filter(|i| i.severity == validate::Severity::Warning).count(),
+            path,
+            name: tl.name,
+            issues,
+        });
+    }
+
+    Ok(CommitValidation { commit_id, timelines, skipped })
+}
+
+/// Cross-reference the media used by every timeline in a commit with the
+/// media committed in it and the media currently in the project folder.
+#[tauri::command]
+pub fn get_media_usage(
+    state: State<AppState>,
+    commit_id: String,
+) -> Result<usage::MediaUsageReport, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+    let project_root = Path::new(&project_path);
+
+    let obj_store = ObjectStore::new(&project_root.join(".turnaround"));
+
+    let (snapshots, tracking) = {
+        let db = state.db.lock();
+        let (_commit, snapshots) = vcs::commit::get_detail(&db.conn, &commit_id)?;
+        (snapshots, TrackingConfig::load(&db.conn)?)
+    };
+    let mut files: Vec<usage::MediaFile> = snapshots.iter()
+        .filter(|s| is_media_type(&s.file_type))
+        .map(|s| usage::MediaFile {
+            path: s.file_path.clone(),
+            file_type: s.file_type.clone(),
+            size: s.file_size,
+            in_version: true,
+            on_disk: project_root.join(&s.file_path).exists(),
+        })
+        .collect();
+
+    for path in vcs::commit::scan_tracked_files(project_root, &tracking)? {
+        if files.iter().any(|f| f.path == path) {
+            continue;
+        }
+        let (file_type, _) = tracking.classify_file(&project_root.join(&path));
+        if !is_media_type(&file_type) {
+            continue;
+        }
+        let size = std::fs::metadata(project_root.join(&path)).map(|m| m.len() as i64).unwrap_or(0);
+        files.push(usage::MediaFile { path, file_type, size, in_version: false, on_disk: true });
+    }
+
+    let file_paths: Vec<PathBuf> = files.iter().map(|f| PathBuf::from(&f.path)).collect();
+    let mut skipped = Vec::new();
+    let timelines = parse_commit_timelines(&obj_store, &snapshots, &mut skipped);
+
+    Ok(usage::media_usage(&timelines, &files, |timeline_path, media_ref| {
+        let timeline_dir = Path::new(timeline_path).parent().unwrap_or(Path::new(""));
+        validate::match_media_file(media_ref, &file_paths, project_root, timeline_dir)
+    }))
+}
+
+#[derive(Serialize)]
+pub struct ClipLineageEntry {
+    pub commit_id: String,
+    pub message: String,
+    pub created_at: String,
+    pub is_milestone: bool,
+    pub event: lineage::LineageEvent,
+    pub changes: Vec<diff::ClipChange>,
+    pub clip_name: String,
+    pub record_timecode: Option<String>,
+    pub source_range: Option<timeline::TimeRange>,
+}
+
+#[derive(Serialize)]
+pub struct ClipLineage {
+    pub file_path: String,
+    pub timeline_name: String,
+    pub media_ref: String,
+    /// Newest first.
+    pub entries: Vec<ClipLineageEntry>,
+    /// History was cut off at the depth limit before the clip's first
+    /// version, so the oldest entry is not where it was added.
+    pub truncated: bool,
+}
+
+/// Walk back from `commit_id` and report every version in which the clip
+/// using `media_ref` nearest `record_timecode` was added, edited or removed.
+/// The timeline is picked by `file_path` and `timeline_name`, defaulting to
+/// the first timeline in the commit.
+#[tauri::command]
+pub fn get_clip_lineage(
+    state: State<AppState>,
+    commit_id: String,
+    media_ref: String,
+    record_timecode: String,
+    file_path: Option<String>,
+    timeline_name: Option<String>,
+    max_depth: Option<usize>,
+) -> Result<ClipLineage, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+
+    let obj_store = ObjectStore::new(&Path::new(&project_path).join(".turnaround"));
+
+    // Read what's needed from the database up front; parsing happens unlocked.
+    let (chain, file_path, snapshots) = {
+        let db = state.db.lock();
+        let chain = vcs::history::walk_history(&db.conn, &commit_id, max_depth.unwrap_or(LINEAGE_MAX_DEPTH))?;
+        if chain.is_empty() {
+            return Err(vcs::VcsError::CommitNotFound(commit_id).into());
+        }
+
+        let file_path = match file_path {
+            Some(path) => path,
+            None => {
+                let mut snapshots = schema::get_snapshots_for_commit(&db.conn, &commit_id)?;
+                snapshots.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+                snapshots.into_iter()
+                    .find(|s| is_timeline_path(&s.file_path))
+                    .map(|s| s.file_path)
+                    .ok_or_else(|| AppError::Timeline("No timeline file found in this version".into()))?
+            }
+        };
+        let snapshots = chain.iter()
+            .map(|commit| schema::get_snapshot(&db.conn, &commit.id, &file_path))
+            .collect::<Result<Vec<_>, _>>()?;
+        (chain, file_path, snapshots)
+    };
+    // The oldest loaded version still has a parent we didn't read.
+    let truncated = chain.last().is_some_and(|c| c.parent_id.is_some());
+
+    let load = |i: usize, name: Option<&str>| -> Result<Option<timeline::Timeline>, AppError> {
+        let Some(snapshot) = &snapshots[i] else { return Ok(None) };
+        Ok(select_timeline(load_snapshot_timelines(&obj_store, snapshot)?, name))
+    };
+
+    let mut newer = load(0, timeline_name.as_deref())?
+        .ok_or_else(|| AppError::Timeline(format!("{file_path} is not part of this version")))?;
+    let timeline_name = newer.name.clone();
+    let near = lineage::timecode_position(&newer, &record_timecode).map_err(AppError::Timeline)?;
+
+    let entry = |commit: &Commit, event, changes, tl: &timeline::Timeline, pos: lineage::ClipPosition| {
+        let track = &tl.tracks[pos.track];
+        let clip = &track.clips[pos.clip];
+        let record = &track.record_ranges()[pos.clip];
+        ClipLineageEntry {
+            commit_id: commit.id.clone(),
+            message: commit.message.clone(),
+            created_at: commit.created_at.clone(),
+            is_milestone: commit.is_milestone,
+            event,
+            changes,
+            clip_name: clip.name.clone(),
+            record_timecode: diff::record_timecode(&record.start, tl),
+            source_range: clip.source_range.clone(),
+        }
+    };
+
+    let mut entries = Vec::new();
+    let mut start = 0;
+    let mut pos = lineage::locate_clip(&newer, &media_ref, near);
+
+    // Not in this version: find the version before it was removed.
+    if pos.is_none() {
+        for i in 1..chain.len() {
+            let Some(older) = load(i, Some(&timeline_name))? else { break };
+            if let Some(found) = lineage::locate_clip(&older, &media_ref, near) {
+                entries.push(entry(&chain[i - 1], lineage::LineageEvent::Removed, Vec::new(), &older, found));
+                (newer, pos, start) = (older, Some(found), i);
+                break;
+            }
+        }
+    }
+    let Some(mut pos) = pos else {
+        return Err(AppError::Timeline(format!("No clip using {media_ref} found in this version or its history")));
+    };
+
+    for i in start..chain.len() {
+        let has_parent = i + 1 < chain.len();
+        let older = if has_parent { load(i + 1, Some(&timeline_name))? } else { None };
+        let Some(older) = older else {
+            // Past the depth limit we can't tell whether the clip was new here.
+            if has_parent || !truncated {
+                entries.push(entry(&chain[i], lineage::LineageEvent::Added, Vec::new(), &newer, pos));
+            }
+            break;
+        };
+        match lineage::step_back(&older, &newer, pos) {
+            lineage::LineageStep::Unchanged(p) => pos = p,
+            lineage::LineageStep::Changed { older: p, changes } => {
+                entries.push(entry(&chain[i], lineage::LineageEvent::Modified, changes, &newer, pos));
+                pos = p;
+            }
+            lineage::LineageStep::Introduced { changes } => {
+                entries.push(entry(&chain[i], lineage::LineageEvent::Added, changes, &newer, pos));
+                break;
+            }
+        }
+        newer = older;
+    }
+
+    Ok(ClipLineage { file_path, timeline_name, media_ref, entries, truncated })
+}
+
+const LINEAGE_MAX_DEPTH: usize = 500;
+
+#[derive(Serialize)]
+pub struct SubtitleFileDiff {
+    pub path: String,
+    /// Added or Removed when the file exists in only one version.
+    pub status: diff::DiffStatus,
+    #[serde(flatten)]
+    pub diff: subtitle::SubtitleDiff,
+}
+
+#[derive(Serialize)]
+pub struct CommitSubtitleDiff {
+    pub files: Vec<SubtitleFileDiff>,
+    pub skipped: Vec<SkippedTimelineFile>,
+}
+
+/// Cue-level diff of every subtitle file that differs between `commit_a`
+/// and `commit_b`. Timecodes are at `frame_rate` when given, otherwise at
+/// the rate of the first timeline in `commit_b`, falling back to 24 fps.
+#[tauri::command]
+pub fn get_subtitle_diff(
+    state: State<AppState>,
+    commit_a: String,
+    commit_b: String,
+    frame_rate: Option<f64>,
+) -> Result<CommitSubtitleDiff, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+
+    let obj_store = ObjectStore::new(&Path::new(&project_path).join(".turnaround"));
+
+    let (old_snapshots, new_snapshots) = {
+        let db = state.db.lock();
+        (schema::get_snapshots_for_commit(&db.conn, &commit_a)?, schema::get_snapshots_for_commit(&db.conn, &commit_b)?)
+    };
+
+    let (rate, drop_frame) = match frame_rate.and_then(FrameRate::from_f64) {
+        Some(rate) => (rate, false),
+        None => parse_commit_timelines(&obj_store, &new_snapshots, &mut Vec::new())
+            .iter()
+            .find_map(|(_, tl)| tl.frame_rate().map(|r| (r, tl.drop_frame)))
+            .unwrap_or((FrameRate::new(24, 1), false)),
+    };
+
+    let mut paths: Vec<&str> = old_snapshots.iter().chain(&new_snapshots)
+        .map(|s| s.file_path.as_str())
+        .filter(|p| is_subtitle_path(p))
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    let load = |snapshot: Option<&FileSnapshot>| -> Result<Vec<subtitle::SubtitleCue>, AppError> {
+        let Some(snapshot) = snapshot else { return Ok(Vec::new()) };
+        let (content, ext) = read_snapshot(&obj_store, snapshot)?;
+        subtitle::parse_subtitles_str(&content, &ext).map_err(AppError::Timeline)
+    };
+
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        let old = old_snapshots.iter().find(|s| s.file_path == path);
+        let new = new_snapshots.iter().find(|s| s.file_path == path);
+        let status = match (old, new) {
+            (Some(a), Some(b)) if a.content_hash == b.content_hash => continue,
+            (Some(_), Some(_)) => diff::DiffStatus::Modified,
+            (None, _) => diff::DiffStatus::Added,
+            (_, None) => diff::DiffStatus::Removed,
+        };
+        match (load(old), load(new)) {
+            (Ok(old_cues), Ok(new_cues)) => files.push(SubtitleFileDiff {
+                path: path.to_string(),
+                status,
+                diff: subtitle::diff_subtitles(&old_cues, &new_cues, rate, drop_frame),
+            }),
+            (Err(e), _) | (_, Err(e)) => skipped.push(SkippedTimelineFile {
+                path: path.to_string(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(CommitSubtitleDiff { files, skipped })
 }
 
 #[tauri::command]
@@ -46,6 +545,82 @@ pub fn parse_timeline_file(path: String) -> Result<timeline::Timeline, AppError>
         .map_err(AppError::Timeline)
 }
 
-fn is_timeline_type(file_type: &str) -> bool {
-    matches!(file_type, "otio" | "fcpxml" | "xml" | "edl")
+/// Objects are stored by hash without an extension, so the format comes
+/// from the path the file was committed under.
+fn load_snapshot_timelines(obj_store: &ObjectStore, snapshot: &FileSnapshot) -> Result<Vec<timeline::Timeline>, AppError> {
+    let (content, ext) = read_snapshot(obj_store, snapshot)?;
+    parser::parse_timelines_str(&content, &ext).map_err(AppError::Timeline)
+}
+
+fn read_snapshot(obj_store: &ObjectStore, snapshot: &FileSnapshot) -> Result<(String, String), AppError> {
+    let ext = Path::new(&snapshot.file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_string();
+    let content = std::fs::read_to_string(obj_store.retrieve_path(&snapshot.content_hash))?;
+    Ok((content, ext))
+}
+
+/// Every timeline in the timeline files among a commit's `snapshots`,
+/// tagged with its path; files that can't be read go to `skipped`. Reads
+/// only the object store, so callers release the database lock first.
+fn parse_commit_timelines(
+    obj_store: &ObjectStore,
+    snapshots: &[FileSnapshot],
+    skipped: &mut Vec<SkippedTimelineFile>,
+) -> Vec<(String, timeline::Timeline)> {
+    let mut snapshots: Vec<&FileSnapshot> = snapshots.iter()
+        .filter(|s| is_timeline_path(&s.file_path))
+        .collect();
+    snapshots.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    let mut timelines = Vec::new();
+    for snapshot in snapshots {
+        match load_snapshot_timelines(obj_store, snapshot) {
+            Ok(parsed) => timelines.extend(parsed.into_iter().map(|tl| (snapshot.file_path.clone(), tl))),
+            Err(e) => {
+                if !skipped.iter().any(|s| s.path == snapshot.file_path) {
+                    skipped.push(SkippedTimelineFile { path: snapshot.file_path.clone(), error: e.to_string() });
+                }
+            }
+        }
+    }
+    timelines
+}
+
+/// The timeline called `name` in the file at `path`, or the file's only
+/// timeline when no name is given or the sequence was renamed. `None` when
+/// the file is not part of the commit.
+fn select_timeline(mut timelines: Vec<timeline::Timeline>, name: Option<&str>) -> Option<timeline::Timeline> {
+    let index = match name {
+        Some(name) => timelines.iter().position(|t| t.name == name)
+            .or(if timelines.len() == 1 { Some(0) } else { None }),
+        None => Some(0),
+    };
+    index.filter(|&i| i < timelines.len()).map(|i| timelines.swap_remove(i))
+}
+
+fn version_label(commit: &Commit) -> String {
+    format!("{} ({})", commit.message, commit.created_at)
+}
+
+fn is_media_type(file_type: &str) -> bool {
+    matches!(file_type, "video" | "audio" | "image")
+}
+
+fn is_timeline_path(file_path: &str) -> bool {
+    Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(parser::is_timeline_extension)
+        .unwrap_or(false)
+}
+
+fn is_subtitle_path(file_path: &str) -> bool {
+    Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(subtitle::is_subtitle_extension)
+        .unwrap_or(false)
 }
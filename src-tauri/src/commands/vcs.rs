@@ -1,11 +1,13 @@
 use std::path::Path;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use crate::AppState;
 use crate::db::schema::{self, Branch, Commit, FileSnapshot};
 use crate::error::AppError;
 use crate::vcs;
 use crate::vcs::object_store::ObjectStore;
-use crate::vcs::commit::{RestoreReport, ExportReport};
+use crate::vcs::commit::{ChangedFiles, RestoreReport, ExportReport};
+use crate::vcs::history::FileRevision;
+use crate::vcs::tracking::TrackingConfig;
 use serde::Serialize;
 
 #[derive(Serialize)]
@@ -16,6 +18,7 @@ pub struct CommitDetail {
 
 #[tauri::command]
 pub fn create_commit(
+    app_handle: AppHandle,
     state: State<AppState>,
     message: String,
     is_milestone: bool,
@@ -36,12 +39,14 @@ pub fn create_commit(
         Path::new(&project_path),
         &message,
         is_milestone,
+        false,
         &obj_store,
     )?;
 
     drop(db);
     if let Err(e) = crate::backup::backup_project(&project.name, &project_path) {
         log::warn!("Background backup failed: {e}");
+        let _ = app_handle.emit("editgit://backup-failed", &e);
     }
 
     Ok(commit)
@@ -67,6 +72,17 @@ pub fn get_commit_detail(
     Ok(CommitDetail { commit, files })
 }
 
+#[tauri::command]
+pub fn get_file_history(
+    state: State<AppState>,
+    commit_id: String,
+    file_path: String,
+    limit: u32,
+) -> Result<Vec<FileRevision>, AppError> {
+    let db = state.db.lock();
+    Ok(vcs::history::file_history(&db.conn, &commit_id, &file_path, limit as usize)?)
+}
+
 #[tauri::command]
 pub fn get_branches(
     state: State<AppState>,
@@ -179,7 +195,7 @@ pub fn switch_branch(
 }
 
 #[tauri::command]
-pub fn get_changed_files(state: State<AppState>) -> Result<Vec<String>, AppError> {
+pub fn get_changed_files(state: State<AppState>) -> Result<ChangedFiles, AppError> {
     let project_path = state.active_project_path.lock().clone()
         .ok_or(AppError::NoActiveProject)?;
     let db = state.db.lock();
@@ -191,3 +207,24 @@ pub fn get_changed_files(state: State<AppState>) -> Result<Vec<String>, AppError
         Path::new(&project_path),
     )?)
 }
+
+#[tauri::command]
+pub fn get_tracking_config(state: State<AppState>) -> Result<TrackingConfig, AppError> {
+    let db = state.db.lock();
+    Ok(TrackingConfig::load(&db.conn)?)
+}
+
+/// The preset projects start from, for resetting the editor.
+#[tauri::command]
+pub fn get_default_tracking_config() -> TrackingConfig {
+    TrackingConfig::default()
+}
+
+/// Replace the project's tracked file types. Commits use the new settings
+/// straight away; the watcher picks them up the next time it starts.
+#[tauri::command]
+pub fn set_tracking_config(state: State<AppState>, config: TrackingConfig) -> Result<(), AppError> {
+    let db = state.db.lock();
+    config.save(&db.conn)?;
+    Ok(())
+}
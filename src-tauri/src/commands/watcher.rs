@@ -1,9 +1,11 @@
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use crate::AppState;
 use crate::error::AppError;
-use crate::watcher::resolve;
+use crate::watcher::{autocommit::{self, AutoCommitConfig}, editors, resolve};
+use crate::watcher::status::{self, WatchConfig, WatcherStatus};
 use crate::db::schema;
+use crate::vcs::tracking::TrackingConfig;
 
 #[tauri::command]
 pub fn start_watching(
@@ -20,12 +22,18 @@ pub fn start_watching(
         watch_path.clone()
     };
 
-    let linked_path = {
+    let (linked_path, watch, tracking, auto_commit) = {
         let db = state.db.lock();
-        schema::get_config(&db.conn, "resolve_db_path")
+        let linked = schema::get_config(&db.conn, "resolve_db_path")
             .ok()
             .flatten()
-            .map(PathBuf::from)
+            .map(PathBuf::from);
+        (
+            linked,
+            status::load_config(&db.conn)?,
+            TrackingConfig::load(&db.conn)?,
+            autocommit::load_config(&db.conn)?,
+        )
     };
 
     if let Some(ref path) = linked_path {
@@ -45,6 +53,9 @@ pub fn start_watching(
         app_handle,
         watch_dir,
         resolve_db,
+        watch,
+        tracking,
+        auto_commit,
     ).map_err(AppError::Watcher)?;
 
     let mut watcher_lock = state.watcher_handle.lock();
@@ -54,14 +65,58 @@ pub fn start_watching(
 }
 
 #[tauri::command]
-pub fn stop_watching(state: State<AppState>) -> Result<(), AppError> {
+pub fn stop_watching(app_handle: AppHandle, state: State<AppState>) -> Result<(), AppError> {
     let mut watcher_lock = state.watcher_handle.lock();
     *watcher_lock = None;
     let mut rdb = state.resolve_db_path.lock();
     *rdb = None;
+    let _ = app_handle.emit("editgit://watcher-status", WatcherStatus::stopped());
     Ok(())
 }
 
+#[tauri::command]
+pub fn get_watcher_status(state: State<AppState>) -> WatcherStatus {
+    state.watcher_handle.lock()
+        .as_ref()
+        .map(|handle| handle.status())
+        .unwrap_or_else(WatcherStatus::stopped)
+}
+
+#[tauri::command]
+pub fn get_watch_config(state: State<AppState>) -> Result<WatchConfig, AppError> {
+    let db = state.db.lock();
+    Ok(status::load_config(&db.conn)?)
+}
+
+/// Save how the project folder is watched. Takes effect the next time
+/// watching starts.
+#[tauri::command]
+pub fn set_watch_config(state: State<AppState>, config: WatchConfig) -> Result<(), AppError> {
+    let db = state.db.lock();
+    status::save_config(&db.conn, &config)?;
+    Ok(())
+}
+
+/// Tracked projects an editor currently has open, going by its lock files.
+#[tauri::command]
+pub fn get_open_projects(state: State<AppState>) -> Result<Vec<editors::OpenProject>, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+    let root = PathBuf::from(&project_path);
+    let tracking = TrackingConfig::load(&state.db.lock().conn)?;
+    let tracked = crate::vcs::commit::scan_tracked_files(&root, &tracking)?;
+    Ok(editors::open_projects(&root, &tracked))
+}
+
+/// Files in the editors' autosave folders, newest first. These are kept out
+/// of commits and offered as a separate history.
+#[tauri::command]
+pub fn list_autosaves(state: State<AppState>) -> Result<Vec<editors::AutosaveFile>, AppError> {
+    let project_path = state.active_project_path.lock().clone()
+        .ok_or(AppError::NoActiveProject)?;
+    Ok(editors::list_autosaves(&PathBuf::from(&project_path)))
+}
+
 #[tauri::command]
 pub fn focus_window(app_handle: AppHandle) -> Result<(), AppError> {
     if let Some(win) = app_handle.get_webview_window("main") {
@@ -70,9 +125,42 @@ pub fn focus_window(app_handle: AppHandle) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Registry setting holding extra Resolve library and disk database
+/// folders as a JSON array of paths.
+const RESOLVE_LIBRARY_ROOTS_KEY: &str = "resolve_library_roots";
+
+fn resolve_library_roots(state: &State<AppState>) -> Result<Vec<String>, AppError> {
+    let reg = state.registry.lock();
+    Ok(reg.get_setting(RESOLVE_LIBRARY_ROOTS_KEY)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn list_resolve_projects(state: State<AppState>) -> Result<Vec<resolve::ResolveProject>, AppError> {
+    let roots = resolve_library_roots(&state)?;
+    Ok(resolve::list_resolve_projects(&roots))
+}
+
+#[tauri::command]
+pub fn get_resolve_library_roots(state: State<AppState>) -> Result<Vec<String>, AppError> {
+    resolve_library_roots(&state)
+}
+
+/// Replace the extra folders scanned for Resolve projects, in addition to
+/// the platform's default locations.
 #[tauri::command]
-pub fn list_resolve_projects() -> Vec<resolve::ResolveProject> {
-    resolve::list_resolve_projects()
+pub fn set_resolve_library_roots(
+    state: State<AppState>,
+    roots: Vec<String>,
+) -> Result<(), AppError> {
+    if let Some(missing) = roots.iter().find(|r| !std::path::Path::new(r).is_dir()) {
+        return Err(AppError::Watcher(format!("Resolve library folder not found: {missing}")));
+    }
+    let json = serde_json::to_string(&roots).expect("paths serialize");
+    let reg = state.registry.lock();
+    reg.set_setting(RESOLVE_LIBRARY_ROOTS_KEY, &json)?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -100,3 +188,25 @@ pub fn get_linked_resolve_project(state: State<AppState>) -> Result<Option<Strin
     let db = state.db.lock();
     Ok(schema::get_config(&db.conn, "resolve_db_path")?)
 }
+
+#[tauri::command]
+pub fn get_auto_commit_config(state: State<AppState>) -> Result<AutoCommitConfig, AppError> {
+    let db = state.db.lock();
+    Ok(autocommit::load_config(&db.conn)?)
+}
+
+/// Save the auto-commit policies and apply them to the running watcher.
+#[tauri::command]
+pub fn set_auto_commit_config(
+    state: State<AppState>,
+    config: AutoCommitConfig,
+) -> Result<(), AppError> {
+    {
+        let db = state.db.lock();
+        autocommit::save_config(&db.conn, &config)?;
+    }
+    if let Some(handle) = state.watcher_handle.lock().as_ref() {
+        handle.set_auto_commit(config);
+    }
+    Ok(())
+}
@@ -0,0 +1,354 @@
+//! `.cube` LUTs (Resolve/Adobe format) and a numeric comparison between two
+//! versions of one.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum LutKind {
+    OneD,
+    ThreeD,
+}
+
+#[derive(Debug, Clone)]
+pub struct CubeLut {
+    pub title: Option<String>,
+    pub kind: LutKind,
+    /// Entries per channel (1D) or per axis of the cube (3D).
+    pub size: usize,
+    pub domain_min: [f64; 3],
+    pub domain_max: [f64; 3],
+    /// Output values; for 3D tables red varies fastest, then green, then blue.
+    pub table: Vec<[f64; 3]>,
+}
+
+impl CubeLut {
+    /// Map an input colour through the table: linear interpolation for 1D,
+    /// trilinear for 3D. Inputs outside the domain are clamped to it.
+    pub fn apply(&self, rgb: [f64; 3]) -> [f64; 3] {
+        let last = (self.size - 1) as f64;
+        let mut pos = [0.0; 3];
+        for c in 0..3 {
+            let span = self.domain_max[c] - self.domain_min[c];
+            let t = if span > 0.0 { (rgb[c] - self.domain_min[c]) / span } else { 0.0 };
+            pos[c] = t.clamp(0.0, 1.0) * last;
+        }
+
+        match self.kind {
+            LutKind::OneD => {
+                let mut out = [0.0; 3];
+                for c in 0..3 {
+                    let (i, f) = split(pos[c], self.size);
+                    out[c] = lerp(self.table[i][c], self.table[i + 1][c], f);
+                }
+                out
+            }
+            LutKind::ThreeD => {
+                let (ri, rf) = split(pos[0], self.size);
+                let (gi, gf) = split(pos[1], self.size);
+                let (bi, bf) = split(pos[2], self.size);
+                let at = |r: usize, g: usize, b: usize| self.table[r + self.size * (g + self.size * b)];
+                let mut out = [0.0; 3];
+                for (c, value) in out.iter_mut().enumerate() {
+                    let c00 = lerp(at(ri, gi, bi)[c], at(ri + 1, gi, bi)[c], rf);
+                    let c10 = lerp(at(ri, gi + 1, bi)[c], at(ri + 1, gi + 1, bi)[c], rf);
+                    let c01 = lerp(at(ri, gi, bi + 1)[c], at(ri + 1, gi, bi + 1)[c], rf);
+                    let c11 = lerp(at(ri, gi + 1, bi + 1)[c], at(ri + 1, gi + 1, bi + 1)[c], rf);
+                    *value = lerp(lerp(c00, c10, gf), lerp(c01, c11, gf), bf);
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Lower lattice index and fraction for a position on a `size`-entry axis,
+/// keeping `index + 1` in range.
+fn split(pos: f64, size: usize) -> (usize, f64) {
+    let i = (pos.floor() as usize).min(size.saturating_sub(2));
+    (i, pos - i as f64)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Largest 3D grid accepted. Shipping LUTs stop at 65 or 129 points per
+/// axis; beyond this the table alone would be gigabytes.
+const MAX_3D_SIZE: usize = 256;
+
+pub fn parse_cube(content: &str) -> Result<CubeLut, String> {
+    let mut title = None;
+    let mut size_1d = None;
+    let mut size_3d = None;
+    let mut domain_min = [0.0; 3];
+    let mut domain_max = [1.0; 3];
+    let mut table = Vec::new();
+
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let bad = || format!("Invalid .cube line {}: {line}", n + 1);
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap_or("");
+        let rest: Vec<&str> = words.collect();
+
+        match keyword {
+            "TITLE" => {
+                title = Some(line["TITLE".len()..].trim().trim_matches('"').to_string());
+            }
+            "LUT_1D_SIZE" => size_1d = Some(parse_size(&rest).ok_or_else(bad)?),
+            "LUT_3D_SIZE" => size_3d = Some(parse_size(&rest).ok_or_else(bad)?),
+            "DOMAIN_MIN" => domain_min = parse_triplet(&rest).ok_or_else(bad)?,
+            "DOMAIN_MAX" => domain_max = parse_triplet(&rest).ok_or_else(bad)?,
+            // Resolve writes the domain as one range for all channels.
+            "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {
+                let [min, max] = parse_floats(&rest).ok_or_else(bad)?[..] else {
+                    return Err(bad());
+                };
+                domain_min = [min; 3];
+                domain_max = [max; 3];
+            }
+            _ if keyword.parse::<f64>().is_ok() => {
+                let values = parse_floats(&line.split_whitespace().collect::<Vec<_>>()).ok_or_else(bad)?;
+                let [r, g, b] = values[..] else { return Err(bad()) };
+                table.push([r, g, b]);
+            }
+            // Unknown keywords are reserved for vendor extensions.
+            _ => {}
+        }
+    }
+
+    let (kind, size) = match (size_1d, size_3d) {
+        (Some(_), Some(_)) => return Err("LUTs with both 1D and 3D tables are not supported".to_string()),
+        (Some(size), None) => (LutKind::OneD, size),
+        (None, Some(size)) => (LutKind::ThreeD, size),
+        (None, None) => return Err("Missing LUT_1D_SIZE or LUT_3D_SIZE".to_string()),
+    };
+    if size < 2 {
+        return Err(format!("LUT size must be at least 2, got {size}"));
+    }
+    if kind == LutKind::ThreeD && size > MAX_3D_SIZE {
+        return Err(format!("3D LUT size must be at most {MAX_3D_SIZE}, got {size}"));
+    }
+    let expected = match kind {
+        LutKind::OneD => Some(size),
+        LutKind::ThreeD => size.checked_mul(size).and_then(|n| n.checked_mul(size)),
+    }
+    .ok_or_else(|| format!("LUT size {size} is too large"))?;
+    if table.len() != expected {
+        return Err(format!("Expected {expected} table entries, found {}", table.len()));
+    }
+    if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+        return Err("DOMAIN_MAX must be greater than DOMAIN_MIN".to_string());
+    }
+
+    Ok(CubeLut { title, kind, size, domain_min, domain_max, table })
+}
+
+fn parse_size(words: &[&str]) -> Option<usize> {
+    match words {
+        [n] => n.parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_floats(words: &[&str]) -> Option<Vec<f64>> {
+    words.iter().map(|w| w.parse().ok()).collect()
+}
+
+fn parse_triplet(words: &[&str]) -> Option<[f64; 3]> {
+    let values = parse_floats(words)?;
+    let [a, b, c] = values[..] else { return None };
+    Some([a, b, c])
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LutInfo {
+    pub title: Option<String>,
+    pub kind: LutKind,
+    pub size: usize,
+    pub domain_min: [f64; 3],
+    pub domain_max: [f64; 3],
+}
+
+impl From<&CubeLut> for LutInfo {
+    fn from(lut: &CubeLut) -> Self {
+        Self {
+            title: lut.title.clone(),
+            kind: lut.kind,
+            size: lut.size,
+            domain_min: lut.domain_min,
+            domain_max: lut.domain_max,
+        }
+    }
+}
+
+/// One ColorChecker patch pushed through both LUTs.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartSample {
+    pub patch: String,
+    pub input: [f64; 3],
+    pub old_output: [f64; 3],
+    pub new_output: [f64; 3],
+    /// CIE76 ΔE between the two outputs, read as display sRGB.
+    pub delta_e: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LutComparison {
+    pub old: LutInfo,
+    pub new: LutInfo,
+    pub kind_changed: bool,
+    pub size_changed: bool,
+    pub domain_changed: bool,
+    /// Largest absolute output difference per channel.
+    pub max_delta: [f64; 3],
+    pub mean_delta: [f64; 3],
+    /// The input colour at which the largest single-channel difference occurs.
+    pub max_delta_input: [f64; 3],
+    pub samples_compared: usize,
+    pub chart: Vec<ChartSample>,
+}
+
+/// Lattice points per axis when sampling both LUTs for the deltas.
+const COMPARE_GRID: usize = 33;
+
+/// Compare two LUTs by sampling both on a common lattice spanning the union
+/// of their domains, so LUTs of different sizes compare on equal terms.
+/// With `include_chart`, the 24 ColorChecker patches are also passed through
+/// both.
+pub fn compare_luts(old: &CubeLut, new: &CubeLut, include_chart: bool) -> LutComparison {
+    let lo: [f64; 3] = std::array::from_fn(|c| old.domain_min[c].min(new.domain_min[c]));
+    let hi: [f64; 3] = std::array::from_fn(|c| old.domain_max[c].max(new.domain_max[c]));
+    let step = |c: usize, i: usize| lo[c] + (hi[c] - lo[c]) * i as f64 / (COMPARE_GRID - 1) as f64;
+
+    let mut max_delta = [0.0f64; 3];
+    let mut sum = [0.0f64; 3];
+    let mut worst = (0.0f64, [0.0; 3]);
+    let mut count = 0;
+    for b in 0..COMPARE_GRID {
+        for g in 0..COMPARE_GRID {
+            for r in 0..COMPARE_GRID {
+                let input = [step(0, r), step(1, g), step(2, b)];
+                let (a, z) = (old.apply(input), new.apply(input));
+                for c in 0..3 {
+                    let d = (z[c] - a[c]).abs();
+                    max_delta[c] = max_delta[c].max(d);
+                    sum[c] += d;
+                    if d > worst.0 {
+                        worst = (d, input);
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+
+    let chart = if include_chart {
+        COLOR_CHECKER.iter()
+            .map(|&(patch, rgb)| {
+                let input = rgb.map(|v| v as f64 / 255.0);
+                let (old_output, new_output) = (old.apply(input), new.apply(input));
+                ChartSample {
+                    patch: patch.to_string(),
+                    delta_e: delta_e76(old_output, new_output),
+                    input,
+                    old_output,
+                    new_output,
+                }
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    LutComparison {
+        kind_changed: old.kind != new.kind,
+        size_changed: old.size != new.size,
+        domain_changed: old.domain_min != new.domain_min || old.domain_max != new.domain_max,
+        old: LutInfo::from(old),
+        new: LutInfo::from(new),
+        max_delta,
+        mean_delta: sum.map(|s| s / count as f64),
+        max_delta_input: worst.1,
+        samples_compared: count,
+        chart,
+    }
+}
+
+/// The X-Rite ColorChecker Classic patches as published 8-bit sRGB values.
+const COLOR_CHECKER: [(&str, [u8; 3]); 24] = [
+    ("Dark Skin", [115, 82, 68]),
+    ("Light Skin", [194, 150, 130]),
+    ("Blue Sky", [98, 122, 157]),
+    ("Foliage", [87, 108, 67]),
+    ("Blue Flower", [133, 128, 177]),
+    ("Bluish Green", [103, 189, 170]),
+    ("Orange", [214, 126, 44]),
+    ("Purplish Blue", [80, 91, 166]),
+    ("Moderate Red", [193, 90, 99]),
+    ("Purple", [94, 60, 108]),
+    ("Yellow Green", [157, 188, 64]),
+    ("Orange Yellow", [224, 163, 46]),
+    ("Blue", [56, 61, 150]),
+    ("Green", [70, 148, 73]),
+    ("Red", [175, 54, 60]),
+    ("Yellow", [231, 199, 31]),
+    ("Magenta", [187, 86, 149]),
+    ("Cyan", [8, 133, 161]),
+    ("White 9.5", [243, 243, 242]),
+    ("Neutral 8", [200, 200, 200]),
+    ("Neutral 6.5", [160, 160, 160]),
+    ("Neutral 5", [122, 122, 121]),
+    ("Neutral 3.5", [85, 85, 85]),
+    ("Black 2", [52, 52, 52]),
+];
+
+fn delta_e76(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let (la, lb) = (srgb_to_lab(a), srgb_to_lab(b));
+    ((la[0] - lb[0]).powi(2) + (la[1] - lb[1]).powi(2) + (la[2] - lb[2]).powi(2)).sqrt()
+}
+
+fn srgb_to_lab(rgb: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb.map(|v| {
+        let v = v.clamp(0.0, 1.0);
+        if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+    });
+    // Linear sRGB to XYZ, normalised to the D65 white point.
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f64| if t > 216.0 / 24389.0 { t.cbrt() } else { (24389.0 / 27.0 * t + 16.0) / 116.0 };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_cube_is_rejected_without_overflowing() {
+        for size in ["257", "18446744073709551615"] {
+            let err = parse_cube(&format!("LUT_3D_SIZE {size}\n0 0 0\n")).unwrap_err();
+            assert!(err.contains("at most 256"), "{err}");
+        }
+    }
+
+    #[test]
+    fn identity_cube_parses() {
+        let mut content = String::from("TITLE \"identity\"\nLUT_3D_SIZE 2\n");
+        for b in 0..2 {
+            for g in 0..2 {
+                for r in 0..2 {
+                    content.push_str(&format!("{r} {g} {b}\n"));
+                }
+            }
+        }
+        let lut = parse_cube(&content).unwrap();
+        assert_eq!(lut.title.as_deref(), Some("identity"));
+        assert_eq!(lut.apply([0.25, 0.5, 0.75]), [0.25, 0.5, 0.75]);
+    }
+}
@@ -0,0 +1,4 @@
+//! Comparing versions of non-timeline assets: colour LUTs and stills.
+
+pub mod lut;
+pub mod still;
@@ -0,0 +1,162 @@
+//! Visual diff of two versions of a still: PSNR, a windowed SSIM on luma,
+//! the share of visibly changed pixels and a heatmap of where they are.
+
+use image::{imageops, DynamicImage, GrayImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Per-channel difference (of 255) below which a pixel counts as unchanged,
+/// so encoder noise in re-saved JPEGs does not light up the whole frame.
+const CHANGE_THRESHOLD: u8 = 8;
+
+/// SSIM window size and stride, in pixels.
+const SSIM_WINDOW: u32 = 8;
+const SSIM_STRIDE: u32 = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageComparison {
+    pub old_width: u32,
+    pub old_height: u32,
+    pub new_width: u32,
+    pub new_height: u32,
+    /// When the sizes differ the new version is resampled to the old one's
+    /// size before comparing.
+    pub dimensions_changed: bool,
+    /// Peak signal-to-noise ratio over RGB, in dB. `None` when the pixels
+    /// are identical.
+    pub psnr: Option<f64>,
+    /// Mean structural similarity of the luma planes, 1.0 for identical.
+    pub ssim: f64,
+    /// Mean absolute difference per channel, from 0 to 1.
+    pub mean_abs_error: f64,
+    /// Fraction of pixels with a visible change.
+    pub changed_fraction: f64,
+    /// `[x, y, width, height]` of the changed area, in the old version's
+    /// pixel grid.
+    pub changed_bounds: Option<[u32; 4]>,
+}
+
+pub struct ImageDiff {
+    pub comparison: ImageComparison,
+    pub heatmap: RgbImage,
+}
+
+pub fn decode(bytes: &[u8]) -> Result<DynamicImage, String> {
+    image::load_from_memory(bytes).map_err(|e| format!("Failed to decode image: {e}"))
+}
+
+pub fn compare_images(old: &DynamicImage, new: &DynamicImage) -> ImageDiff {
+    let a = old.to_rgb8();
+    let mut b = new.to_rgb8();
+    let dimensions_changed = a.dimensions() != b.dimensions();
+    if dimensions_changed {
+        b = imageops::resize(&b, a.width(), a.height(), imageops::FilterType::Triangle);
+    }
+
+    let (width, height) = a.dimensions();
+    let mut squared = 0.0f64;
+    let mut absolute = 0.0f64;
+    let mut changed = 0u64;
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    let mut heatmap = RgbImage::new(width, height);
+
+    for (x, y, pa) in a.enumerate_pixels() {
+        let pb = b.get_pixel(x, y);
+        let mut peak = 0u8;
+        for c in 0..3 {
+            let d = pa[c].abs_diff(pb[c]);
+            peak = peak.max(d);
+            squared += (d as f64).powi(2);
+            absolute += d as f64;
+        }
+        if peak >= CHANGE_THRESHOLD {
+            changed += 1;
+            bounds = Some(match bounds {
+                Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                None => (x, y, x, y),
+            });
+        }
+        heatmap.put_pixel(x, y, heat_pixel(luma(pb), peak));
+    }
+
+    let samples = (width as f64 * height as f64 * 3.0).max(1.0);
+    let mse = squared / samples;
+    let comparison = ImageComparison {
+        old_width: old.width(),
+        old_height: old.height(),
+        new_width: new.width(),
+        new_height: new.height(),
+        dimensions_changed,
+        psnr: (mse > 0.0).then(|| 10.0 * (255.0f64.powi(2) / mse).log10()),
+        ssim: ssim(&imageops::grayscale(&a), &imageops::grayscale(&b)),
+        mean_abs_error: absolute / samples / 255.0,
+        changed_fraction: changed as f64 / (width as f64 * height as f64).max(1.0),
+        changed_bounds: bounds.map(|(x0, y0, x1, y1)| [x0, y0, x1 - x0 + 1, y1 - y0 + 1]),
+    };
+    ImageDiff { comparison, heatmap }
+}
+
+/// Fit the image within `max_side` pixels on its longer edge.
+pub fn thumbnail(img: &DynamicImage, max_side: u32) -> DynamicImage {
+    if img.width() <= max_side && img.height() <= max_side {
+        img.clone()
+    } else {
+        img.thumbnail(max_side, max_side)
+    }
+}
+
+/// Changed pixels glow from yellow to red over a dimmed copy of the new
+/// version; unchanged areas stay grey for context.
+fn heat_pixel(background: u8, peak: u8) -> Rgb<u8> {
+    let grey = (background as f64 * 0.35) as u8;
+    if peak < CHANGE_THRESHOLD {
+        return Rgb([grey, grey, grey]);
+    }
+    let t = (peak as f64 / 96.0).min(1.0);
+    let color = [255.0, 230.0 * (1.0 - t), 0.0];
+    let alpha = 0.45 + 0.55 * t;
+    Rgb(color.map(|c| (grey as f64 * (1.0 - alpha) + c * alpha) as u8))
+}
+
+fn luma(p: &Rgb<u8>) -> u8 {
+    (0.2126 * p[0] as f64 + 0.7152 * p[1] as f64 + 0.0722 * p[2] as f64) as u8
+}
+
+/// Mean SSIM over sliding windows. Images smaller than a window are taken
+/// as a single window.
+fn ssim(a: &GrayImage, b: &GrayImage) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    let (width, height) = a.dimensions();
+    let (win_w, win_h) = (SSIM_WINDOW.min(width), SSIM_WINDOW.min(height));
+    if win_w == 0 || win_h == 0 {
+        return 1.0;
+    }
+
+    let mut total = 0.0;
+    let mut windows = 0u64;
+    let mut y = 0;
+    while y + win_h <= height {
+        let mut x = 0;
+        while x + win_w <= width {
+            let n = (win_w * win_h) as f64;
+            let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            for wy in y..y + win_h {
+                for wx in x..x + win_w {
+                    let (va, vb) = (a.get_pixel(wx, wy)[0] as f64, b.get_pixel(wx, wy)[0] as f64);
+                    sa += va;
+                    sb += vb;
+                    saa += va * va;
+                    sbb += vb * vb;
+                    sab += va * vb;
+                }
+            }
+            let (ma, mb) = (sa / n, sb / n);
+            let (va, vb, cov) = (saa / n - ma * ma, sbb / n - mb * mb, sab / n - ma * mb);
+            total += ((2.0 * ma * mb + C1) * (2.0 * cov + C2)) / ((ma * ma + mb * mb + C1) * (va + vb + C2));
+            windows += 1;
+            x += SSIM_STRIDE;
+        }
+        y += SSIM_STRIDE;
+    }
+    if windows == 0 { 1.0 } else { total / windows as f64 }
+}
@@ -59,6 +59,25 @@ const MIGRATIONS: &[&str] = &[
     );
     INSERT OR REPLACE INTO schema_version (version) VALUES (2);
     "#,
+
+    // V3: Flag commits made by an auto-commit policy
+    r#"
+    ALTER TABLE commits ADD COLUMN is_automatic INTEGER DEFAULT 0;
+    INSERT OR REPLACE INTO schema_version (version) VALUES (3);
+    "#,
+
+    // V4: Editor that produced a snapshotted project file
+    r#"
+    ALTER TABLE file_snapshots ADD COLUMN producer TEXT;
+    INSERT OR REPLACE INTO schema_version (version) VALUES (4);
+    "#,
+
+    // V5: Rename detection
+    r#"
+    ALTER TABLE file_snapshots ADD COLUMN partial_hash TEXT;
+    ALTER TABLE file_snapshots ADD COLUMN renamed_from TEXT;
+    INSERT OR REPLACE INTO schema_version (version) VALUES (5);
+    "#,
 ];
 
 pub fn run_all(conn: &Connection) -> Result<(), DbError> {
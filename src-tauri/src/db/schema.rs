@@ -28,6 +28,9 @@ pub struct Commit {
     pub message: String,
     pub is_milestone: bool,
     pub created_at: String,
+    /// Made by an auto-commit policy rather than by hand.
+    #[serde(default)]
+    pub is_automatic: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +41,13 @@ pub struct FileSnapshot {
     pub content_hash: String,
     pub file_size: i64,
     pub file_type: String,
+    /// Editor that wrote a project file, when its content says.
+    pub producer: Option<String>,
+    /// `hasher::hash_file_partial` of files stored by reference, for
+    /// recognising them after a move without hashing them in full.
+    pub partial_hash: Option<String>,
+    /// Path of this file in the parent commit, if it moved since.
+    pub renamed_from: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -142,11 +152,11 @@ pub fn update_branch_head(conn: &Connection, branch_id: &str, commit_id: &str) -
 
 pub fn insert_commit(conn: &Connection, commit: &Commit) -> Result<(), DbError> {
     conn.execute(
-        "INSERT INTO commits (id, project_id, branch_id, parent_id, message, is_milestone, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO commits (id, project_id, branch_id, parent_id, message, is_milestone, created_at, is_automatic) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
         params![
             commit.id, commit.project_id, commit.branch_id,
             commit.parent_id, commit.message, commit.is_milestone as i32,
-            commit.created_at
+            commit.created_at, commit.is_automatic as i32
         ],
     )?;
     Ok(())
@@ -154,7 +164,7 @@ pub fn insert_commit(conn: &Connection, commit: &Commit) -> Result<(), DbError>
 
 pub fn get_history(conn: &Connection, branch_id: &str, limit: u32) -> Result<Vec<Commit>, DbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, project_id, branch_id, parent_id, message, is_milestone, created_at FROM commits WHERE branch_id = ?1 ORDER BY created_at DESC LIMIT ?2"
+        "SELECT id, project_id, branch_id, parent_id, message, is_milestone, created_at, is_automatic FROM commits WHERE branch_id = ?1 ORDER BY created_at DESC LIMIT ?2"
     )?;
     let rows = stmt.query_map(params![branch_id, limit], |row| {
         Ok(Commit {
@@ -165,6 +175,7 @@ pub fn get_history(conn: &Connection, branch_id: &str, limit: u32) -> Result<Vec
             message: row.get(4)?,
             is_milestone: row.get::<_, i32>(5)? != 0,
             created_at: row.get(6)?,
+            is_automatic: row.get::<_, Option<i32>>(7)?.unwrap_or(0) != 0,
         })
     })?;
     Ok(rows.filter_map(|r| r.ok()).collect())
@@ -172,7 +183,7 @@ pub fn get_history(conn: &Connection, branch_id: &str, limit: u32) -> Result<Vec
 
 pub fn get_commit(conn: &Connection, commit_id: &str) -> Result<Option<Commit>, DbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, project_id, branch_id, parent_id, message, is_milestone, created_at FROM commits WHERE id = ?1"
+        "SELECT id, project_id, branch_id, parent_id, message, is_milestone, created_at, is_automatic FROM commits WHERE id = ?1"
     )?;
     let mut rows = stmt.query(params![commit_id])?;
     if let Some(row) = rows.next()? {
@@ -184,6 +195,7 @@ pub fn get_commit(conn: &Connection, commit_id: &str) -> Result<Option<Commit>,
             message: row.get(4)?,
             is_milestone: row.get::<_, i32>(5)? != 0,
             created_at: row.get(6)?,
+            is_automatic: row.get::<_, Option<i32>>(7)?.unwrap_or(0) != 0,
         }))
     } else {
         Ok(None)
@@ -204,29 +216,52 @@ pub fn get_content_hashes_for_commit(conn: &Connection, commit_id: &str) -> Resu
 
 pub fn insert_file_snapshot(conn: &Connection, snapshot: &FileSnapshot) -> Result<(), DbError> {
     conn.execute(
-        "INSERT INTO file_snapshots (id, commit_id, file_path, content_hash, file_size, file_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![snapshot.id, snapshot.commit_id, snapshot.file_path, snapshot.content_hash, snapshot.file_size, snapshot.file_type],
+        "INSERT INTO file_snapshots (id, commit_id, file_path, content_hash, file_size, file_type, producer, partial_hash, renamed_from) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            snapshot.id, snapshot.commit_id, snapshot.file_path, snapshot.content_hash, snapshot.file_size,
+            snapshot.file_type, snapshot.producer, snapshot.partial_hash, snapshot.renamed_from,
+        ],
     )?;
     Ok(())
 }
 
+const SNAPSHOT_COLUMNS: &str =
+    "id, commit_id, file_path, content_hash, file_size, file_type, producer, partial_hash, renamed_from";
+
+fn snapshot_from_row(row: &rusqlite::Row) -> rusqlite::Result<FileSnapshot> {
+    Ok(FileSnapshot {
+        id: row.get(0)?,
+        commit_id: row.get(1)?,
+        file_path: row.get(2)?,
+        content_hash: row.get(3)?,
+        file_size: row.get(4)?,
+        file_type: row.get(5)?,
+        producer: row.get(6)?,
+        partial_hash: row.get(7)?,
+        renamed_from: row.get(8)?,
+    })
+}
+
 pub fn get_snapshots_for_commit(conn: &Connection, commit_id: &str) -> Result<Vec<FileSnapshot>, DbError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, commit_id, file_path, content_hash, file_size, file_type FROM file_snapshots WHERE commit_id = ?1"
-    )?;
-    let rows = stmt.query_map(params![commit_id], |row| {
-        Ok(FileSnapshot {
-            id: row.get(0)?,
-            commit_id: row.get(1)?,
-            file_path: row.get(2)?,
-            content_hash: row.get(3)?,
-            file_size: row.get(4)?,
-            file_type: row.get(5)?,
-        })
-    })?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SNAPSHOT_COLUMNS} FROM file_snapshots WHERE commit_id = ?1"
+    ))?;
+    let rows = stmt.query_map(params![commit_id], snapshot_from_row)?;
     Ok(rows.filter_map(|r| r.ok()).collect())
 }
 
+pub fn get_snapshot(conn: &Connection, commit_id: &str, file_path: &str) -> Result<Option<FileSnapshot>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SNAPSHOT_COLUMNS} FROM file_snapshots WHERE commit_id = ?1 AND file_path = ?2"
+    ))?;
+    let mut rows = stmt.query(params![commit_id, file_path])?;
+    if let Some(row) = rows.next()? {
+        Ok(Some(snapshot_from_row(row)?))
+    } else {
+        Ok(None)
+    }
+}
+
 pub fn insert_object(conn: &Connection, obj: &StoredObject) -> Result<(), DbError> {
     conn.execute(
         "INSERT INTO objects (hash, size, stored_path, ref_count) VALUES (?1, ?2, ?3, ?4)",
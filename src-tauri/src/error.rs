@@ -39,6 +39,9 @@ pub enum AppError {
 
     #[error("{0}")]
     Watcher(String),
+
+    #[error("{0}")]
+    Compare(String),
 }
 
 impl From<rusqlite::Error> for AppError {
@@ -81,6 +84,7 @@ impl AppError {
             Self::Backup(_) => "BACKUP_ERROR",
             Self::Timeline(_) => "TIMELINE_ERROR",
             Self::Watcher(_) => "WATCHER_ERROR",
+            Self::Compare(_) => "COMPARE_ERROR",
         }
     }
 }
@@ -1,6 +1,6 @@
 use sha2::{Sha256, Digest};
 use std::fs::File;
-use std::io::{self, Read, BufReader};
+use std::io::{self, Read, BufReader, Seek, SeekFrom};
 use std::path::Path;
 
 const BUFFER_SIZE: usize = 1024 * 1024; // 1MB chunks
@@ -27,3 +27,30 @@ pub fn hash_bytes(data: &[u8]) -> String {
     hasher.update(data);
     hex::encode(hasher.finalize())
 }
+
+/// Hash of a file's size and its first and last `BUFFER_SIZE` bytes: cheap
+/// to compute for large media, and enough to recognise the same file under
+/// another name.
+pub fn hash_file_partial(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+
+    let mut buffer = Vec::with_capacity(BUFFER_SIZE);
+    (&mut file).take(BUFFER_SIZE as u64).read_to_end(&mut buffer)?;
+    hasher.update(&buffer);
+
+    if size > 2 * BUFFER_SIZE as u64 {
+        file.seek(SeekFrom::Start(size - BUFFER_SIZE as u64))?;
+        buffer.clear();
+        file.take(BUFFER_SIZE as u64).read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    } else {
+        buffer.clear();
+        file.read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
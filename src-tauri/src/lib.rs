@@ -1,5 +1,6 @@
 pub mod backup;
 pub mod commands;
+pub mod compare;
 pub mod db;
 pub mod error;
 pub mod hasher;
@@ -61,6 +62,7 @@ pub fn run() {
             commands::vcs::create_commit,
             commands::vcs::get_history,
             commands::vcs::get_commit_detail,
+            commands::vcs::get_file_history,
             commands::vcs::get_branches,
             commands::vcs::create_branch,
             commands::vcs::delete_commit,
@@ -69,15 +71,35 @@ pub fn run() {
             commands::vcs::export_commit,
             commands::vcs::switch_branch,
             commands::vcs::get_changed_files,
+            commands::vcs::get_tracking_config,
+            commands::vcs::get_default_tracking_config,
+            commands::vcs::set_tracking_config,
             commands::watcher::start_watching,
             commands::watcher::stop_watching,
+            commands::watcher::get_watcher_status,
+            commands::watcher::get_watch_config,
+            commands::watcher::set_watch_config,
+            commands::watcher::get_open_projects,
+            commands::watcher::list_autosaves,
             commands::watcher::focus_window,
             commands::watcher::list_resolve_projects,
+            commands::watcher::get_resolve_library_roots,
+            commands::watcher::set_resolve_library_roots,
             commands::watcher::link_resolve_project,
             commands::watcher::unlink_resolve_project,
             commands::watcher::get_linked_resolve_project,
+            commands::watcher::get_auto_commit_config,
+            commands::watcher::set_auto_commit_config,
             commands::timeline::get_timeline_diff,
             commands::timeline::parse_timeline_file,
+            commands::timeline::export_timeline_otio,
+            commands::timeline::generate_change_list,
+            commands::timeline::validate_timelines,
+            commands::timeline::get_media_usage,
+            commands::timeline::get_clip_lineage,
+            commands::timeline::get_subtitle_diff,
+            commands::compare::compare_luts,
+            commands::compare::compare_images,
         ])
         .run(tauri::generate_context!())
         .expect("error while running Turn Around");
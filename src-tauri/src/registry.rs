@@ -94,6 +94,11 @@ impl Registry {
                 commit_count INTEGER NOT NULL DEFAULT 0,
                 branch_count INTEGER NOT NULL DEFAULT 1
             );
+
+            CREATE TABLE IF NOT EXISTS app_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
             "#,
         )?;
         Ok(())
@@ -301,6 +306,25 @@ impl Registry {
         )?;
         Ok(())
     }
+
+    // ── App Settings ──
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>, RegistryError> {
+        let mut stmt = self.conn.prepare("SELECT value FROM app_settings WHERE key = ?1")?;
+        let mut rows = stmt.query(params![key])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<(), RegistryError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
 }
 
 pub fn default_registry_dir() -> PathBuf {
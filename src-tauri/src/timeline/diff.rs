@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
-use super::{Timeline, Track, Clip, TrackKind, TimeRange};
+use super::{Timeline, Track, Clip, TrackKind, TimeRange, RationalTime};
+use super::time::{self, ExactTime, FrameRate};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum DiffStatus {
@@ -9,21 +11,70 @@ pub enum DiffStatus {
     Unchanged,
 }
 
+/// What happened to a clip that exists in both versions. Frame deltas are
+/// measured at the clip's rate in the newer version; positive means later.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum ClipChange {
+    /// The source in point moved; positive frames shorten the head.
+    TrimmedHead { frames: i64 },
+    /// The source out point moved; positive frames extend the tail.
+    TrimmedTail { frames: i64 },
+    /// Same duration, different source frames.
+    Slipped { frames: i64 },
+    /// The clip now starts and ends at a different record time.
+    Moved { frames: i64 },
+    /// The clip changed places with other clips on the track.
+    Reordered,
+    /// Different media now occupies the clip's slot in the edit.
+    Replaced { old_name: String, old_media_ref: Option<String> },
+    /// The clip was taken off one track and put on another.
+    MovedTrack { from_track: usize, to_track: usize },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClipDiff {
     pub name: String,
     pub status: DiffStatus,
     pub media_ref: Option<String>,
+    pub reel: Option<String>,
     pub old_range: Option<TimeRange>,
     pub new_range: Option<TimeRange>,
+    pub old_record_range: Option<TimeRange>,
+    pub new_record_range: Option<TimeRange>,
+    /// Record in point as SMPTE timecode, offset by the timeline's start.
+    pub old_record_timecode: Option<String>,
+    pub new_record_timecode: Option<String>,
+    /// Record out point, the frame after the clip's last, as timecode.
+    pub old_record_out_timecode: Option<String>,
+    pub new_record_out_timecode: Option<String>,
+    pub changes: Vec<ClipChange>,
+    /// Where the clip sits in the version it is in: the old version for
+    /// removed clips, the new one for everything else.
     pub track_index: usize,
     pub clip_index: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TrackStatus {
+    Added,
+    Removed,
+    Renamed,
+    Reordered,
+    Matched,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackDiff {
     pub name: String,
     pub kind: TrackKind,
+    pub status: TrackStatus,
+    /// The track changed places relative to the other matched tracks. Set
+    /// for renamed tracks too, whose `status` is `Renamed`.
+    pub reordered: bool,
+    pub old_name: Option<String>,
+    pub old_index: Option<usize>,
+    pub new_index: Option<usize>,
     pub clips: Vec<ClipDiff>,
 }
 
@@ -31,6 +82,8 @@ pub struct TrackDiff {
 pub struct TimelineDiff {
     pub old_name: String,
     pub new_name: String,
+    pub frame_rate: Option<FrameRate>,
+    pub drop_frame: bool,
     pub tracks: Vec<TrackDiff>,
     pub summary: DiffSummary,
 }
@@ -44,139 +97,693 @@ pub struct DiffSummary {
 }
 
 pub fn diff_timelines(old: &Timeline, new: &Timeline) -> TimelineDiff {
-    let max_tracks = old.tracks.len().max(new.tracks.len());
     let mut tracks = Vec::new();
     let mut summary = DiffSummary { added: 0, removed: 0, modified: 0, unchanged: 0 };
 
-    for i in 0..max_tracks {
-        let old_track = old.tracks.get(i);
-        let new_track = new.tracks.get(i);
+    let pairs = match_tracks(&old.tracks, &new.tracks);
+    let reordered = reordered_tracks(&pairs);
 
-        match (old_track, new_track) {
-            (Some(ot), Some(nt)) => {
-                let track_diff = diff_tracks(ot, nt, i, &mut summary);
+    for (ni, nt) in new.tracks.iter().enumerate() {
+        match pairs.iter().find(|(_, n)| *n == ni) {
+            Some(&(oi, _)) => {
+                let ot = &old.tracks[oi];
+                let status = if ot.name != nt.name {
+                    TrackStatus::Renamed
+                } else if reordered.contains(&ni) {
+                    TrackStatus::Reordered
+                } else {
+                    TrackStatus::Matched
+                };
+                let mut track_diff = diff_tracks(ot, nt, oi, ni, &mut summary);
+                track_diff.status = status;
+                track_diff.reordered = reordered.contains(&ni);
+                track_diff.old_name = Some(ot.name.clone());
                 tracks.push(track_diff);
             }
-            (Some(ot), None) => {
-                let clips: Vec<ClipDiff> = ot.clips.iter().enumerate().map(|(ci, c)| {
-                    summary.removed += 1;
-                    ClipDiff {
-                        name: c.name.clone(),
-                        status: DiffStatus::Removed,
-                        media_ref: c.media_ref.clone(),
-                        old_range: c.source_range.clone(),
-                        new_range: None,
-                        track_index: i,
-                        clip_index: ci,
-                    }
-                }).collect();
-                tracks.push(TrackDiff { name: ot.name.clone(), kind: ot.kind.clone(), clips });
-            }
-            (None, Some(nt)) => {
-                let clips: Vec<ClipDiff> = nt.clips.iter().enumerate().map(|(ci, c)| {
-                    summary.added += 1;
-                    ClipDiff {
-                        name: c.name.clone(),
-                        status: DiffStatus::Added,
-                        media_ref: c.media_ref.clone(),
-                        old_range: None,
-                        new_range: c.source_range.clone(),
-                        track_index: i,
-                        clip_index: ci,
-                    }
-                }).collect();
-                tracks.push(TrackDiff { name: nt.name.clone(), kind: nt.kind.clone(), clips });
+            None => {
+                let clips = nt.clips.iter().zip(nt.record_ranges()).enumerate()
+                    .map(|(ci, (c, rec))| {
+                        summary.added += 1;
+                        added_clip(c, rec, ni, ci)
+                    })
+                    .collect();
+                tracks.push(TrackDiff {
+                    name: nt.name.clone(),
+                    kind: nt.kind.clone(),
+                    status: TrackStatus::Added,
+                    reordered: false,
+                    old_name: None,
+                    old_index: None,
+                    new_index: Some(ni),
+                    clips,
+                });
             }
-            (None, None) => {}
         }
     }
 
+    for (oi, ot) in old.tracks.iter().enumerate() {
+        if pairs.iter().any(|(o, _)| *o == oi) {
+            continue;
+        }
+        let clips = ot.clips.iter().zip(ot.record_ranges()).enumerate()
+            .map(|(ci, (c, rec))| {
+                summary.removed += 1;
+                removed_clip(c, rec, oi, ci)
+            })
+            .collect();
+        tracks.push(TrackDiff {
+            name: ot.name.clone(),
+            kind: ot.kind.clone(),
+            status: TrackStatus::Removed,
+            reordered: false,
+            old_name: Some(ot.name.clone()),
+            old_index: Some(oi),
+            new_index: None,
+            clips,
+        });
+    }
+
+    detect_track_moves(&mut tracks, &mut summary);
+    annotate_timecodes(&mut tracks, old, new);
+
     TimelineDiff {
         old_name: old.name.clone(),
         new_name: new.name.clone(),
+        frame_rate: new.frame_rate().or_else(|| old.frame_rate()),
+        drop_frame: new.drop_frame,
         tracks,
         summary,
     }
 }
 
-fn diff_tracks(old: &Track, new: &Track, track_idx: usize, summary: &mut DiffSummary) -> TrackDiff {
-    let mut clip_diffs = Vec::new();
+/// A timeline identified by the file it was committed in and its name there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineRef {
+    pub path: String,
+    pub name: String,
+}
 
-    let mut matched_new: Vec<bool> = vec![false; new.clips.len()];
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedTimelineDiff {
+    pub path: String,
+    pub name: String,
+    pub diff: TimelineDiff,
+}
 
-    for (oi, old_clip) in old.clips.iter().enumerate() {
-        let mut found = false;
-        for (ni, new_clip) in new.clips.iter().enumerate() {
-            if matched_new[ni] {
-                continue;
-            }
-            if clips_match(old_clip, new_clip) {
-                matched_new[ni] = true;
-                found = true;
-
-                if clips_identical(old_clip, new_clip) {
-                    summary.unchanged += 1;
-                    clip_diffs.push(ClipDiff {
-                        name: new_clip.name.clone(),
-                        status: DiffStatus::Unchanged,
-                        media_ref: new_clip.media_ref.clone(),
-                        old_range: old_clip.source_range.clone(),
-                        new_range: new_clip.source_range.clone(),
-                        track_index: track_idx,
-                        clip_index: ni,
-                    });
-                } else {
-                    summary.modified += 1;
-                    clip_diffs.push(ClipDiff {
-                        name: new_clip.name.clone(),
-                        status: DiffStatus::Modified,
-                        media_ref: new_clip.media_ref.clone(),
-                        old_range: old_clip.source_range.clone(),
-                        new_range: new_clip.source_range.clone(),
-                        track_index: track_idx,
-                        clip_index: ni,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineSetDiff {
+    pub timelines: Vec<PairedTimelineDiff>,
+    pub added: Vec<TimelineRef>,
+    pub removed: Vec<TimelineRef>,
+    pub summary: DiffSummary,
+}
+
+/// Diff every timeline of one version against the other. Timelines pair up
+/// by the path they were committed under; a file holding several timelines
+/// pairs them by name, in order for repeated names. A file with exactly one
+/// timeline on each side pairs it even if the sequence was renamed.
+pub fn diff_timeline_sets(old: &[(String, Timeline)], new: &[(String, Timeline)]) -> TimelineSetDiff {
+    let mut by_path: BTreeMap<&str, (Vec<&Timeline>, Vec<&Timeline>)> = BTreeMap::new();
+    for (path, tl) in old {
+        by_path.entry(path).or_default().0.push(tl);
+    }
+    for (path, tl) in new {
+        by_path.entry(path).or_default().1.push(tl);
+    }
+
+    let mut result = TimelineSetDiff {
+        timelines: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        summary: DiffSummary { added: 0, removed: 0, modified: 0, unchanged: 0 },
+    };
+
+    for (path, (old_tls, new_tls)) in by_path {
+        let timeline_ref = |tl: &Timeline| TimelineRef { path: path.to_string(), name: tl.name.clone() };
+        let mut old_used = vec![false; old_tls.len()];
+
+        for nt in &new_tls {
+            let paired = if old_tls.len() == 1 && new_tls.len() == 1 {
+                Some(0)
+            } else {
+                (0..old_tls.len()).find(|&i| !old_used[i] && old_tls[i].name == nt.name)
+            };
+            match paired {
+                Some(i) => {
+                    old_used[i] = true;
+                    let diff = diff_timelines(old_tls[i], nt);
+                    result.summary.added += diff.summary.added;
+                    result.summary.removed += diff.summary.removed;
+                    result.summary.modified += diff.summary.modified;
+                    result.summary.unchanged += diff.summary.unchanged;
+                    result.timelines.push(PairedTimelineDiff {
+                        path: path.to_string(),
+                        name: nt.name.clone(),
+                        diff,
                     });
                 }
+                None => result.added.push(timeline_ref(nt)),
+            }
+        }
+
+        for (i, ot) in old_tls.iter().enumerate() {
+            if !old_used[i] {
+                result.removed.push(timeline_ref(ot));
+            }
+        }
+    }
+
+    result
+}
+
+fn annotate_timecodes(tracks: &mut [TrackDiff], old: &Timeline, new: &Timeline) {
+    for clip in tracks.iter_mut().flat_map(|t| t.clips.iter_mut()) {
+        clip.old_record_timecode = clip.old_record_range.as_ref().and_then(|r| record_timecode(&r.start, old));
+        clip.new_record_timecode = clip.new_record_range.as_ref().and_then(|r| record_timecode(&r.start, new));
+        clip.old_record_out_timecode = clip.old_record_range.as_ref().and_then(|r| record_out_timecode(r, old));
+        clip.new_record_out_timecode = clip.new_record_range.as_ref().and_then(|r| record_out_timecode(r, new));
+    }
+}
+
+/// Timecode of a track position in `timeline`, counting from its start time.
+pub fn record_timecode(position: &RationalTime, timeline: &Timeline) -> Option<String> {
+    timecode_at(ExactTime::from(position), position.rate, timeline)
+}
+
+fn record_out_timecode(range: &TimeRange, timeline: &Timeline) -> Option<String> {
+    timecode_at(range.exact_end(), range.start.rate, timeline)
+}
+
+/// `position` as timecode at the timeline's rate, or `fallback_rate` when
+/// the timeline has none.
+fn timecode_at(position: ExactTime, fallback_rate: f64, timeline: &Timeline) -> Option<String> {
+    let rate = timeline.frame_rate().or_else(|| FrameRate::from_f64(fallback_rate))?;
+    let start = timeline.global_start_time.as_ref().map(ExactTime::from).unwrap_or(ExactTime::ZERO);
+    let frames = (start + position).to_frames(rate);
+    Some(time::format_timecode(frames, rate, timeline.drop_frame))
+}
+
+/// Pair old and new tracks as `(old_index, new_index)`. Tracks of the same
+/// kind pair by how much media they share, with an equal name as a strong
+/// tie-break; NLEs renumber default names like "V1" when a track is inserted,
+/// so content wins over name. Leftover tracks with the same name pair last.
+pub(super) fn match_tracks(old: &[Track], new: &[Track]) -> Vec<(usize, usize)> {
+    let mut candidates = Vec::new();
+    for (oi, ot) in old.iter().enumerate() {
+        for (ni, nt) in new.iter().enumerate() {
+            if ot.kind != nt.kind {
+                continue;
+            }
+            let mut score = track_similarity(ot, nt);
+            if ot.name == nt.name {
+                score += 0.3;
+            }
+            if oi == ni {
+                score += 0.05;
+            }
+            if score >= TRACK_MATCH_THRESHOLD {
+                candidates.push((score, oi, ni));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut pairs = take_pairs(candidates.into_iter().map(|(_, oi, ni)| (oi, ni)), old.len(), new.len());
+
+    for (ni, nt) in new.iter().enumerate() {
+        if pairs.iter().any(|&(_, n)| n == ni) {
+            continue;
+        }
+        let found = old.iter().enumerate().find(|(oi, ot)| {
+            ot.kind == nt.kind && ot.name == nt.name && !pairs.iter().any(|(o, _)| o == oi)
+        });
+        if let Some((oi, _)) = found {
+            pairs.push((oi, ni));
+        }
+    }
+
+    pairs.sort_by_key(|&(_, ni)| ni);
+    pairs
+}
+
+const TRACK_MATCH_THRESHOLD: f64 = 0.5;
+
+/// Jaccard similarity (0..=1) of the two tracks' clips by media.
+fn track_similarity(a: &Track, b: &Track) -> f64 {
+    let mut remaining: Vec<&Clip> = b.clips.iter().collect();
+    let mut shared = 0usize;
+    for clip in &a.clips {
+        if let Some(pos) = remaining.iter().position(|c| clips_match(clip, c)) {
+            remaining.swap_remove(pos);
+            shared += 1;
+        }
+    }
+    let union = a.clips.len() + b.clips.len() - shared;
+    if union == 0 { 0.0 } else { shared as f64 / union as f64 }
+}
+
+/// New indices of matched tracks whose order relative to the other matched
+/// tracks changed: everything outside the longest increasing run of old
+/// indices, walked in new order.
+fn reordered_tracks(pairs: &[(usize, usize)]) -> Vec<usize> {
+    let olds: Vec<usize> = pairs.iter().map(|&(oi, _)| oi).collect();
+    let n = olds.len();
+    let mut best = vec![1usize; n];
+    let mut prev = vec![usize::MAX; n];
+    for i in 0..n {
+        for j in 0..i {
+            if olds[j] < olds[i] && best[j] + 1 > best[i] {
+                best[i] = best[j] + 1;
+                prev[i] = j;
+            }
+        }
+    }
+
+    let mut in_order = vec![false; n];
+    if let Some(mut i) = (0..n).max_by_key(|&i| best[i]) {
+        loop {
+            in_order[i] = true;
+            if prev[i] == usize::MAX {
                 break;
             }
+            i = prev[i];
         }
+    }
 
-        if !found {
+    pairs.iter().zip(in_order)
+        .filter(|(_, keep)| !keep)
+        .map(|(&(_, ni), _)| ni)
+        .collect()
+}
+
+fn diff_tracks(old: &Track, new: &Track, old_idx: usize, track_idx: usize, summary: &mut DiffSummary) -> TrackDiff {
+    let old_rec = old.record_ranges();
+    let new_rec = new.record_ranges();
+
+    let mut matched_old: Vec<Option<usize>> = vec![None; old.clips.len()];
+    let mut matched_new: Vec<bool> = vec![false; new.clips.len()];
+    let mut clip_diffs = Vec::new();
+
+    // In-order matches first, then same-media clips that changed places.
+    let mut pairs: Vec<(usize, usize, bool)> = align_in_order(old, new)
+        .into_iter()
+        .map(|(oi, ni)| (oi, ni, true))
+        .collect();
+    for &(oi, ni, _) in &pairs {
+        matched_old[oi] = Some(ni);
+        matched_new[ni] = true;
+    }
+    for (oi, ni) in match_leftovers(old, new, &old_rec, &new_rec, &matched_old, &matched_new) {
+        matched_old[oi] = Some(ni);
+        matched_new[ni] = true;
+        pairs.push((oi, ni, false));
+    }
+
+    for (oi, ni, in_order) in pairs {
+        let (old_clip, new_clip) = (&old.clips[oi], &new.clips[ni]);
+        let mut changes = classify_changes(
+            old_clip.source_range.as_ref(), &old_rec[oi],
+            new_clip.source_range.as_ref(), &new_rec[ni],
+        );
+        if !in_order {
+            changes.insert(0, ClipChange::Reordered);
+        }
+        clip_diffs.push(matched_clip(old_clip, &old_rec[oi], new_clip, &new_rec[ni], changes, track_idx, ni, summary));
+    }
+
+    // Unmatched clips that occupy the same slot are replacements.
+    for (oi, ni) in match_replacements(&old_rec, &new_rec, &matched_old, &matched_new) {
+        matched_old[oi] = Some(ni);
+        matched_new[ni] = true;
+        let (old_clip, new_clip) = (&old.clips[oi], &new.clips[ni]);
+        let mut changes = vec![ClipChange::Replaced {
+            old_name: old_clip.name.clone(),
+            old_media_ref: old_clip.media_ref.clone(),
+        }];
+        if let Some(moved) = moved_frames(&old_rec[oi], &new_rec[ni]) {
+            changes.push(ClipChange::Moved { frames: moved });
+        }
+        clip_diffs.push(matched_clip(old_clip, &old_rec[oi], new_clip, &new_rec[ni], changes, track_idx, ni, summary));
+    }
+
+    for (oi, old_clip) in old.clips.iter().enumerate() {
+        if matched_old[oi].is_none() {
             summary.removed += 1;
-            clip_diffs.push(ClipDiff {
-                name: old_clip.name.clone(),
-                status: DiffStatus::Removed,
-                media_ref: old_clip.media_ref.clone(),
-                old_range: old_clip.source_range.clone(),
-                new_range: None,
-                track_index: track_idx,
-                clip_index: oi,
-            });
+            clip_diffs.push(removed_clip(old_clip, old_rec[oi].clone(), old_idx, oi));
         }
     }
 
     for (ni, new_clip) in new.clips.iter().enumerate() {
         if !matched_new[ni] {
             summary.added += 1;
-            clip_diffs.push(ClipDiff {
-                name: new_clip.name.clone(),
-                status: DiffStatus::Added,
-                media_ref: new_clip.media_ref.clone(),
-                old_range: None,
-                new_range: new_clip.source_range.clone(),
-                track_index: track_idx,
-                clip_index: ni,
-            });
+            clip_diffs.push(added_clip(new_clip, new_rec[ni].clone(), track_idx, ni));
         }
     }
 
+    clip_diffs.sort_by_key(diff_position);
+
     TrackDiff {
         name: new.name.clone(),
         kind: new.kind.clone(),
+        status: TrackStatus::Matched,
+        reordered: false,
+        old_name: None,
+        old_index: Some(old_idx),
+        new_index: Some(track_idx),
         clips: clip_diffs,
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn matched_clip(
+    old_clip: &Clip,
+    old_rec: &TimeRange,
+    new_clip: &Clip,
+    new_rec: &TimeRange,
+    changes: Vec<ClipChange>,
+    track_idx: usize,
+    clip_idx: usize,
+    summary: &mut DiffSummary,
+) -> ClipDiff {
+    let status = if changes.is_empty() {
+        summary.unchanged += 1;
+        DiffStatus::Unchanged
+    } else {
+        summary.modified += 1;
+        DiffStatus::Modified
+    };
+    ClipDiff {
+        name: new_clip.name.clone(),
+        status,
+        media_ref: new_clip.media_ref.clone(),
+        reel: new_clip.reel.clone().or_else(|| old_clip.reel.clone()),
+        old_range: old_clip.source_range.clone(),
+        new_range: new_clip.source_range.clone(),
+        old_record_range: Some(old_rec.clone()),
+        new_record_range: Some(new_rec.clone()),
+        old_record_timecode: None,
+        new_record_timecode: None,
+        old_record_out_timecode: None,
+        new_record_out_timecode: None,
+        changes,
+        track_index: track_idx,
+        clip_index: clip_idx,
+    }
+}
+
+fn added_clip(clip: &Clip, record: TimeRange, track_idx: usize, clip_idx: usize) -> ClipDiff {
+    ClipDiff {
+        name: clip.name.clone(),
+        status: DiffStatus::Added,
+        media_ref: clip.media_ref.clone(),
+        reel: clip.reel.clone(),
+        old_range: None,
+        new_range: clip.source_range.clone(),
+        old_record_range: None,
+        new_record_range: Some(record),
+        old_record_timecode: None,
+        new_record_timecode: None,
+        old_record_out_timecode: None,
+        new_record_out_timecode: None,
+        changes: Vec::new(),
+        track_index: track_idx,
+        clip_index: clip_idx,
+    }
+}
+
+fn removed_clip(clip: &Clip, record: TimeRange, track_idx: usize, clip_idx: usize) -> ClipDiff {
+    ClipDiff {
+        name: clip.name.clone(),
+        status: DiffStatus::Removed,
+        media_ref: clip.media_ref.clone(),
+        reel: clip.reel.clone(),
+        old_range: clip.source_range.clone(),
+        new_range: None,
+        old_record_range: Some(record),
+        new_record_range: None,
+        old_record_timecode: None,
+        new_record_timecode: None,
+        old_record_out_timecode: None,
+        new_record_out_timecode: None,
+        changes: Vec::new(),
+        track_index: track_idx,
+        clip_index: clip_idx,
+    }
+}
+
+fn diff_position(diff: &ClipDiff) -> ExactTime {
+    diff.new_record_range.as_ref()
+        .or(diff.old_record_range.as_ref())
+        .map(|r| r.exact_start())
+        .unwrap_or(ExactTime::ZERO)
+}
+
+/// Weighted LCS over clip identity. Every match is worth more than any
+/// combination of tie-breaks, so the number of in-order matches is maximised
+/// first; among equally long alignments, pairs whose source ranges overlap
+/// win. This keeps two uses of the same media from being crossed.
+pub(super) fn align_in_order(old: &Track, new: &Track) -> Vec<(usize, usize)> {
+    let (n, m) = (old.clips.len(), new.clips.len());
+    let match_weight = (n.min(m) + 1) as f64;
+    let mut dp = vec![vec![0.0f64; m + 1]; n + 1];
+
+    for i in 1..=n {
+        for j in 1..=m {
+            let mut best = dp[i - 1][j].max(dp[i][j - 1]);
+            let (a, b) = (&old.clips[i - 1], &new.clips[j - 1]);
+            if clips_match(a, b) {
+                let score = match_weight + source_overlap(a.source_range.as_ref(), b.source_range.as_ref());
+                best = best.max(dp[i - 1][j - 1] + score);
+            }
+            dp[i][j] = best;
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 && j > 0 {
+        let (a, b) = (&old.clips[i - 1], &new.clips[j - 1]);
+        if clips_match(a, b) {
+            let score = match_weight + source_overlap(a.source_range.as_ref(), b.source_range.as_ref());
+            if dp[i][j] == dp[i - 1][j - 1] + score {
+                pairs.push((i - 1, j - 1));
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if dp[i - 1][j] >= dp[i][j - 1] {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+/// Pair remaining clips that share media but fell outside the in-order
+/// alignment, cheapest (closest in source and record time) first.
+fn match_leftovers(
+    old: &Track,
+    new: &Track,
+    old_rec: &[TimeRange],
+    new_rec: &[TimeRange],
+    matched_old: &[Option<usize>],
+    matched_new: &[bool],
+) -> Vec<(usize, usize)> {
+    let mut candidates = Vec::new();
+    for (oi, a) in old.clips.iter().enumerate() {
+        if matched_old[oi].is_some() {
+            continue;
+        }
+        for (ni, b) in new.clips.iter().enumerate() {
+            if matched_new[ni] || !clips_match(a, b) {
+                continue;
+            }
+            let source_cost = 1.0 - source_overlap(a.source_range.as_ref(), b.source_range.as_ref());
+            let record_cost = (old_rec[oi].exact_start() - new_rec[ni].exact_start()).abs();
+            candidates.push((source_cost, record_cost, oi, ni));
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    take_pairs(candidates.into_iter().map(|(_, _, oi, ni)| (oi, ni)), matched_old.len(), matched_new.len())
+}
+
+/// Pair unmatched clips whose record ranges mostly overlap.
+fn match_replacements(
+    old_rec: &[TimeRange],
+    new_rec: &[TimeRange],
+    matched_old: &[Option<usize>],
+    matched_new: &[bool],
+) -> Vec<(usize, usize)> {
+    let mut candidates = Vec::new();
+    for (oi, a) in old_rec.iter().enumerate() {
+        if matched_old[oi].is_some() {
+            continue;
+        }
+        for (ni, b) in new_rec.iter().enumerate() {
+            if matched_new[ni] {
+                continue;
+            }
+            let overlap = range_overlap(a, b);
+            let shorter = a.exact_duration().min(b.exact_duration());
+            if shorter > ExactTime::ZERO && overlap + overlap >= shorter {
+                candidates.push((overlap, oi, ni));
+            }
+        }
+    }
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.0));
+
+    take_pairs(candidates.into_iter().map(|(_, oi, ni)| (oi, ni)), matched_old.len(), matched_new.len())
+}
+
+fn take_pairs(ordered: impl Iterator<Item = (usize, usize)>, n_old: usize, n_new: usize) -> Vec<(usize, usize)> {
+    let mut used_old = vec![false; n_old];
+    let mut used_new = vec![false; n_new];
+    let mut pairs = Vec::new();
+    for (oi, ni) in ordered {
+        if used_old[oi] || used_new[ni] {
+            continue;
+        }
+        used_old[oi] = true;
+        used_new[ni] = true;
+        pairs.push((oi, ni));
+    }
+    pairs
+}
+
+/// A removed clip and an added clip with the same media and overlapping
+/// source frames on different tracks are one clip that changed tracks.
+fn detect_track_moves(tracks: &mut [TrackDiff], summary: &mut DiffSummary) {
+    let mut candidates = Vec::new();
+    for (ti, track) in tracks.iter().enumerate() {
+        for (ci, removed) in track.clips.iter().enumerate() {
+            if removed.status != DiffStatus::Removed {
+                continue;
+            }
+            for (tj, other) in tracks.iter().enumerate() {
+                if ti == tj || other.kind != track.kind {
+                    continue;
+                }
+                for (cj, added) in other.clips.iter().enumerate() {
+                    if added.status != DiffStatus::Added || !diffs_match(removed, added) {
+                        continue;
+                    }
+                    let overlap = source_overlap(removed.old_range.as_ref(), added.new_range.as_ref());
+                    if overlap > 0.0 {
+                        candidates.push((overlap, (ti, ci), (tj, cj)));
+                    }
+                }
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut used = std::collections::HashSet::new();
+    let mut consumed = Vec::new();
+    for (_, from, to) in candidates {
+        if used.contains(&from) || used.contains(&to) {
+            continue;
+        }
+        used.insert(from);
+        used.insert(to);
+
+        let removed = tracks[from.0].clips[from.1].clone();
+        let added = &mut tracks[to.0].clips[to.1];
+        let mut changes = vec![ClipChange::MovedTrack {
+            from_track: removed.track_index,
+            to_track: added.track_index,
+        }];
+        if let (Some(old_rec), Some(new_rec)) = (&removed.old_record_range, &added.new_record_range) {
+            changes.extend(classify_changes(removed.old_range.as_ref(), old_rec, added.new_range.as_ref(), new_rec));
+        }
+
+        added.status = DiffStatus::Modified;
+        added.old_range = removed.old_range;
+        added.old_record_range = removed.old_record_range;
+        added.old_record_timecode = removed.old_record_timecode;
+        added.old_record_out_timecode = removed.old_record_out_timecode;
+        added.changes = changes;
+        consumed.push(from);
+
+        summary.added -= 1;
+        summary.removed -= 1;
+        summary.modified += 1;
+    }
+
+    consumed.sort_by(|a, b| b.cmp(a));
+    for (ti, ci) in consumed {
+        tracks[ti].clips.remove(ci);
+    }
+}
+
+fn classify_changes(
+    old_src: Option<&TimeRange>,
+    old_rec: &TimeRange,
+    new_src: Option<&TimeRange>,
+    new_rec: &TimeRange,
+) -> Vec<ClipChange> {
+    let mut changes = Vec::new();
+
+    if let (Some(os), Some(ns)) = (old_src, new_src) {
+        if let Some(rate) = FrameRate::from_f64(ns.duration.rate) {
+            let head = (ns.exact_start() - os.exact_start()).to_frames(rate);
+            let tail = (ns.exact_end() - os.exact_end()).to_frames(rate);
+            if head != 0 && head == tail {
+                changes.push(ClipChange::Slipped { frames: head });
+            } else {
+                if head != 0 {
+                    changes.push(ClipChange::TrimmedHead { frames: head });
+                }
+                if tail != 0 {
+                    changes.push(ClipChange::TrimmedTail { frames: tail });
+                }
+            }
+        }
+    }
+
+    if let Some(moved) = moved_frames(old_rec, new_rec) {
+        changes.push(ClipChange::Moved { frames: moved });
+    }
+
+    changes
+}
+
+/// A clip has moved when neither its record in nor its record out stayed put;
+/// trimming one edge in place leaves the other edge anchored.
+fn moved_frames(old_rec: &TimeRange, new_rec: &TimeRange) -> Option<i64> {
+    let rate = FrameRate::from_f64(new_rec.start.rate)?;
+    let start_delta = (new_rec.exact_start() - old_rec.exact_start()).to_frames(rate);
+    let end_delta = (new_rec.exact_end() - old_rec.exact_end()).to_frames(rate);
+    if start_delta != 0 && end_delta != 0 {
+        Some(start_delta)
+    } else {
+        None
+    }
+}
+
+fn range_overlap(a: &TimeRange, b: &TimeRange) -> ExactTime {
+    let start = a.exact_start().max(b.exact_start());
+    let end = a.exact_end().min(b.exact_end());
+    (end - start).max(ExactTime::ZERO)
+}
+
+/// Fraction (0..=1) of the longer source range shared by both clips.
+fn source_overlap(a: Option<&TimeRange>, b: Option<&TimeRange>) -> f64 {
+    match (a, b) {
+        (Some(ra), Some(rb)) => {
+            let longest = ra.exact_duration().max(rb.exact_duration());
+            if longest <= ExactTime::ZERO {
+                return if ranges_equal(ra, rb) { 1.0 } else { 0.0 };
+            }
+            range_overlap(ra, rb).seconds() / longest.seconds()
+        }
+        (None, None) => 1.0,
+        _ => 0.0,
+    }
+}
+
 fn clips_match(a: &Clip, b: &Clip) -> bool {
     match (&a.media_ref, &b.media_ref) {
         (Some(ref_a), Some(ref_b)) => ref_a == ref_b,
@@ -184,22 +791,126 @@ fn clips_match(a: &Clip, b: &Clip) -> bool {
     }
 }
 
-fn clips_identical(a: &Clip, b: &Clip) -> bool {
-    if !clips_match(a, b) {
-        return false;
+fn diffs_match(a: &ClipDiff, b: &ClipDiff) -> bool {
+    match (&a.media_ref, &b.media_ref) {
+        (Some(ref_a), Some(ref_b)) => ref_a == ref_b,
+        _ => a.name == b.name,
     }
-    ranges_equal(&a.source_range, &b.source_range)
 }
 
-fn ranges_equal(a: &Option<TimeRange>, b: &Option<TimeRange>) -> bool {
-    match (a, b) {
-        (Some(ra), Some(rb)) => {
-            (ra.start.value - rb.start.value).abs() < 0.001
-                && (ra.start.rate - rb.start.rate).abs() < 0.001
-                && (ra.duration.value - rb.duration.value).abs() < 0.001
-                && (ra.duration.rate - rb.duration.rate).abs() < 0.001
+fn ranges_equal(a: &TimeRange, b: &TimeRange) -> bool {
+    a.exact_start() == b.exact_start() && a.exact_duration() == b.exact_duration()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(media: &str, source_start: f64, duration: f64, record: f64) -> Clip {
+        Clip {
+            name: media.to_string(),
+            media_ref: Some(format!("{media}.mov")),
+            source_range: Some(TimeRange {
+                start: RationalTime::new(source_start, 24.0),
+                duration: RationalTime::new(duration, 24.0),
+            }),
+            trimmed_range: None,
+            record_start: Some(RationalTime::new(record, 24.0)),
+            reel: None,
         }
-        (None, None) => true,
-        _ => false,
+    }
+
+    fn track(name: &str, clips: Vec<Clip>) -> Track {
+        Track { name: name.to_string(), kind: TrackKind::Video, clips }
+    }
+
+    fn timeline(tracks: Vec<Track>) -> Timeline {
+        Timeline { name: "Edit".to_string(), tracks, duration: None, global_start_time: None, drop_frame: false }
+    }
+
+    fn changes_of<'a>(diff: &'a TimelineDiff, name: &str) -> &'a [ClipChange] {
+        &diff.tracks.iter().flat_map(|t| &t.clips).find(|c| c.name == name).unwrap().changes
+    }
+
+    #[test]
+    fn trims_slips_and_moves_are_told_apart() {
+        let old = timeline(vec![track("V1", vec![
+            clip("A", 0.0, 48.0, 0.0),
+            clip("B", 100.0, 48.0, 48.0),
+            clip("C", 200.0, 48.0, 96.0),
+            clip("D", 300.0, 48.0, 144.0),
+        ])]);
+        let new = timeline(vec![track("V1", vec![
+            clip("A", 12.0, 36.0, 12.0),
+            clip("B", 110.0, 48.0, 48.0),
+            clip("C", 200.0, 36.0, 96.0),
+            clip("D", 300.0, 48.0, 200.0),
+        ])]);
+
+        let diff = diff_timelines(&old, &new);
+        assert_eq!(changes_of(&diff, "A"), [ClipChange::TrimmedHead { frames: 12 }]);
+        assert_eq!(changes_of(&diff, "B"), [ClipChange::Slipped { frames: 10 }]);
+        assert_eq!(changes_of(&diff, "C"), [ClipChange::TrimmedTail { frames: -12 }]);
+        assert_eq!(changes_of(&diff, "D"), [ClipChange::Moved { frames: 56 }]);
+        assert_eq!(diff.summary.modified, 4);
+    }
+
+    #[test]
+    fn a_clip_taken_out_of_order_is_reordered() {
+        let old = timeline(vec![track("V1", vec![
+            clip("P", 0.0, 24.0, 0.0),
+            clip("Q", 0.0, 24.0, 24.0),
+            clip("R", 0.0, 24.0, 48.0),
+        ])]);
+        let new = timeline(vec![track("V1", vec![
+            clip("R", 0.0, 24.0, 0.0),
+            clip("P", 0.0, 24.0, 24.0),
+            clip("Q", 0.0, 24.0, 48.0),
+        ])]);
+
+        let diff = diff_timelines(&old, &new);
+        assert_eq!(changes_of(&diff, "R"), [ClipChange::Reordered, ClipChange::Moved { frames: -48 }]);
+        assert_eq!(changes_of(&diff, "P"), [ClipChange::Moved { frames: 24 }]);
+        assert_eq!(changes_of(&diff, "Q"), [ClipChange::Moved { frames: 24 }]);
+    }
+
+    #[test]
+    fn a_renamed_track_can_also_be_reordered() {
+        let v1 = track("V1", vec![clip("A", 0.0, 24.0, 0.0)]);
+        let v2 = track("V2", vec![clip("B", 0.0, 24.0, 0.0)]);
+        let v3 = track("V3", vec![clip("C", 0.0, 24.0, 0.0)]);
+        let top = Track { name: "Titles".to_string(), ..v3.clone() };
+        let old = timeline(vec![v1.clone(), v2.clone(), v3]);
+        let new = timeline(vec![top, v1, v2]);
+
+        let diff = diff_timelines(&old, &new);
+        let titles = diff.tracks.iter().find(|t| t.name == "Titles").unwrap();
+        assert_eq!(titles.status, TrackStatus::Renamed);
+        assert!(titles.reordered);
+        assert_eq!((titles.old_index, titles.new_index), (Some(2), Some(0)));
+        // The others kept their relative order.
+        assert!(diff.tracks.iter().filter(|t| t.name != "Titles").all(|t| !t.reordered));
+    }
+
+    #[test]
+    fn removed_clips_keep_old_indices() {
+        let old = timeline(vec![
+            track("V1", vec![clip("A", 0.0, 24.0, 0.0), clip("B", 0.0, 24.0, 24.0), clip("Z", 0.0, 24.0, 48.0)]),
+            track("V2", vec![clip("C", 0.0, 24.0, 0.0)]),
+        ]);
+        let new = timeline(vec![
+            track("Inserted", vec![clip("X", 0.0, 24.0, 0.0)]),
+            track("V1", vec![clip("A", 0.0, 24.0, 0.0)]),
+            track("V2", vec![clip("C", 0.0, 24.0, 0.0), clip("B", 0.0, 24.0, 24.0)]),
+        ]);
+
+        let diff = diff_timelines(&old, &new);
+        let removed = diff.tracks.iter().flat_map(|t| &t.clips).find(|c| c.name == "Z").unwrap();
+        assert_eq!(removed.status, DiffStatus::Removed);
+        assert_eq!((removed.track_index, removed.clip_index), (0, 2));
+
+        let moved = diff.tracks.iter().flat_map(|t| &t.clips).find(|c| c.name == "B").unwrap();
+        assert_eq!(moved.changes, [ClipChange::MovedTrack { from_track: 0, to_track: 2 }]);
+        assert_eq!((moved.track_index, moved.clip_index), (2, 1));
     }
 }
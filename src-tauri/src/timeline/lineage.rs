@@ -0,0 +1,112 @@
+//! Following one clip back through successive versions of a timeline.
+
+use serde::{Deserialize, Serialize};
+use super::{diff, Timeline};
+use super::diff::{ClipChange, DiffStatus};
+use super::time::{self, ExactTime};
+
+/// A clip's place in a timeline: track index and clip index on that track.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ClipPosition {
+    pub track: usize,
+    pub clip: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LineageEvent {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// How a clip in a newer version relates to the version before it.
+#[derive(Debug, Clone)]
+pub enum LineageStep {
+    Unchanged(ClipPosition),
+    /// The clip was edited; `older` is where it sat before.
+    Changed { older: ClipPosition, changes: Vec<ClipChange> },
+    /// The clip has no counterpart in the older version. `changes` holds a
+    /// `Replaced` entry when it took another clip's place.
+    Introduced { changes: Vec<ClipChange> },
+}
+
+/// Position from the start of the timeline named by a timecode on the
+/// timeline's own clock.
+pub fn timecode_position(timeline: &Timeline, timecode: &str) -> Result<ExactTime, String> {
+    let rate = timeline.frame_rate()
+        .ok_or_else(|| "Timeline has no frame rate to read timecode against".to_string())?;
+    let frames = time::parse_timecode_with(timecode, rate, timeline.drop_frame)?;
+    let start = timeline.global_start_time.as_ref().map(ExactTime::from).unwrap_or(ExactTime::ZERO);
+    Ok(ExactTime::from_frames(frames, rate) - start)
+}
+
+/// The clip using `media_ref` closest to `near`: one whose record range
+/// contains it if any, otherwise the one whose nearest edge is closest.
+pub fn locate_clip(timeline: &Timeline, media_ref: &str, near: ExactTime) -> Option<ClipPosition> {
+    let mut best: Option<(ExactTime, ClipPosition)> = None;
+    for (ti, track) in timeline.tracks.iter().enumerate() {
+        for (ci, (clip, record)) in track.clips.iter().zip(track.record_ranges()).enumerate() {
+            if clip.media_ref.as_deref() != Some(media_ref) {
+                continue;
+            }
+            let (start, end) = (record.exact_start(), record.exact_end());
+            let distance = if near < start {
+                start - near
+            } else if near >= end {
+                near - end
+            } else {
+                ExactTime::ZERO
+            };
+            let closer = match &best {
+                Some((d, _)) => distance < *d,
+                None => true,
+            };
+            if closer {
+                best = Some((distance, ClipPosition { track: ti, clip: ci }));
+            }
+        }
+    }
+    best.map(|(_, pos)| pos)
+}
+
+/// Relate the clip at `at` in `newer` to `older`.
+pub fn step_back(older: &Timeline, newer: &Timeline, at: ClipPosition) -> LineageStep {
+    let timeline_diff = diff::diff_timelines(older, newer);
+    let found = timeline_diff.tracks.iter()
+        .filter(|t| t.new_index == Some(at.track))
+        .flat_map(|t| t.clips.iter().map(move |c| (t, c)))
+        .find(|(_, c)| c.status != DiffStatus::Removed && c.track_index == at.track && c.clip_index == at.clip);
+
+    let Some((track_diff, clip_diff)) = found else {
+        return LineageStep::Introduced { changes: Vec::new() };
+    };
+    match clip_diff.status {
+        DiffStatus::Added => return LineageStep::Introduced { changes: Vec::new() },
+        _ if clip_diff.changes.iter().any(|c| matches!(c, ClipChange::Replaced { .. })) => {
+            return LineageStep::Introduced { changes: clip_diff.changes.clone() };
+        }
+        _ => {}
+    }
+
+    let older_track = clip_diff.changes.iter()
+        .find_map(|c| match c {
+            ClipChange::MovedTrack { from_track, .. } => Some(*from_track),
+            _ => None,
+        })
+        .or(track_diff.old_index);
+    let older_pos = older_track.and_then(|ti| {
+        let track = older.tracks.get(ti)?;
+        let old_start = clip_diff.old_record_range.as_ref()?.exact_start();
+        track.clips.iter().zip(track.record_ranges())
+            .position(|(clip, record)| {
+                clip.media_ref == clip_diff.media_ref && record.exact_start() == old_start
+            })
+            .map(|ci| ClipPosition { track: ti, clip: ci })
+    });
+
+    match (older_pos, clip_diff.status.clone()) {
+        (Some(pos), DiffStatus::Unchanged) => LineageStep::Unchanged(pos),
+        (Some(pos), _) => LineageStep::Changed { older: pos, changes: clip_diff.changes.clone() },
+        (None, _) => LineageStep::Introduced { changes: clip_diff.changes.clone() },
+    }
+}
@@ -0,0 +1,566 @@
+//! Three-way timeline merge.
+//!
+//! Each track is merged as a sequence of clips, the way diff3 merges lines.
+//! A clip carries the gap in front of it rather than an absolute record
+//! position, so a ripple insert on one side does not collide with every clip
+//! the other side left downstream of it. Record positions are laid out again
+//! when the merged timeline is built.
+
+use serde::{Deserialize, Serialize};
+use super::{diff, writer, Clip, RationalTime, TimeRange, Timeline, Track, TrackKind};
+use super::time::{ExactTime, FrameRate};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Side {
+    Base,
+    Ours,
+    Theirs,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ConflictKind {
+    /// Both sides trimmed or slipped the same clip differently.
+    BothTrimmed,
+    /// Both sides changed the gap in front of the same clip differently.
+    BothMoved,
+    /// Both sides renamed the same clip, or gave it a different reel.
+    BothRenamed,
+    /// Both sides inserted different clips at the same point.
+    BothInserted,
+    /// One side removed clips the other side edited.
+    DeleteEdit,
+    /// Both sides reworked the same run of clips.
+    OverlappingEdits,
+    /// One side deleted a track the other side edited.
+    TrackDeleteEdit,
+}
+
+/// A region both sides changed incompatibly. `base`, `ours` and `theirs`
+/// hold the clips each resolution puts in place; for a single clip edited on
+/// both sides, the changes that did not conflict are already folded into
+/// both `ours` and `theirs`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeConflict {
+    pub id: usize,
+    pub kind: ConflictKind,
+    pub track: String,
+    pub track_kind: TrackKind,
+    /// Record position of the first clip involved, as it sits in its own version.
+    pub record_start: Option<RationalTime>,
+    pub base: Vec<Clip>,
+    pub ours: Vec<Clip>,
+    pub theirs: Vec<Clip>,
+    pub resolution: Option<Side>,
+    #[serde(skip)]
+    choices: [Vec<Item>; 3],
+}
+
+impl MergeConflict {
+    fn items(&self, side: Side) -> &[Item] {
+        match side {
+            Side::Base => &self.choices[0],
+            Side::Ours => &self.choices[1],
+            Side::Theirs => &self.choices[2],
+        }
+    }
+}
+
+/// The outcome of `merge_timelines`. Timeline-level settings that both
+/// sides changed differently (name, start timecode, drop frame) keep ours.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeResult {
+    pub name: String,
+    pub global_start_time: Option<RationalTime>,
+    pub drop_frame: bool,
+    pub conflicts: Vec<MergeConflict>,
+    #[serde(skip)]
+    tracks: Vec<MergedTrack>,
+}
+
+impl MergeResult {
+    pub fn resolve(&mut self, conflict_id: usize, side: Side) -> Result<(), String> {
+        let conflict = self.conflicts.get_mut(conflict_id)
+            .ok_or_else(|| format!("No merge conflict with id {conflict_id}"))?;
+        conflict.resolution = Some(side);
+        Ok(())
+    }
+
+    pub fn resolve_all(&mut self, side: Side) {
+        for conflict in self.conflicts.iter_mut().filter(|c| c.resolution.is_none()) {
+            conflict.resolution = Some(side);
+        }
+    }
+
+    pub fn unresolved(&self) -> usize {
+        self.conflicts.iter().filter(|c| c.resolution.is_none()).count()
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.unresolved() == 0
+    }
+
+    /// The merged timeline. Fails while any conflict is unresolved.
+    pub fn timeline(&self) -> Result<Timeline, String> {
+        let mut tracks = Vec::new();
+        for track in &self.tracks {
+            let clips = self.build_clips(track)?;
+            if clips.is_empty() && track.removed_in.is_some() {
+                continue;
+            }
+            tracks.push(Track { name: track.name.clone(), kind: track.kind.clone(), clips });
+        }
+        Ok(Timeline {
+            name: self.name.clone(),
+            tracks,
+            duration: None,
+            global_start_time: self.global_start_time.clone(),
+            drop_frame: self.drop_frame,
+        })
+    }
+
+    pub fn to_otio_json(&self) -> Result<String, String> {
+        writer::to_otio_json(&self.timeline()?)
+    }
+
+    fn build_clips(&self, track: &MergedTrack) -> Result<Vec<Clip>, String> {
+        let mut clips = Vec::new();
+        let mut previous_end = ExactTime::ZERO;
+        for segment in &track.segments {
+            let items = match segment {
+                Segment::Clean(items) => items.as_slice(),
+                Segment::Conflict(id) => {
+                    let conflict = &self.conflicts[*id];
+                    let side = conflict.resolution
+                        .ok_or_else(|| format!("Merge conflict {id} on track {} is unresolved", track.name))?;
+                    conflict.items(side)
+                }
+            };
+            for item in items {
+                let start = previous_end + item.lead;
+                let duration = item.clip.source_range.as_ref()
+                    .map(|r| r.exact_duration())
+                    .unwrap_or(ExactTime::ZERO);
+                let mut clip = item.clip.clone();
+                clip.record_start = Some(start.rescaled_to(record_rate(&item.clip)));
+                clips.push(clip);
+                previous_end = start + duration;
+            }
+        }
+        Ok(clips)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Item {
+    clip: Clip,
+    /// Time from the end of the previous clip (or the track start) to this clip.
+    lead: ExactTime,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Clean(Vec<Item>),
+    Conflict(usize),
+}
+
+#[derive(Debug, Clone)]
+struct MergedTrack {
+    name: String,
+    kind: TrackKind,
+    /// Set when one side deleted the track; it is dropped if it ends up empty.
+    removed_in: Option<Side>,
+    segments: Vec<Segment>,
+    order: (u8, usize, usize),
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Source,
+    Lead,
+    Label,
+}
+
+/// Merge `ours` and `theirs`, both descended from `base`. Tracks pair up the
+/// same way `diff::diff_timelines` pairs them; tracks added on both sides
+/// are merged against an empty base so identical additions collapse.
+pub fn merge_timelines(base: &Timeline, ours: &Timeline, theirs: &Timeline) -> MergeResult {
+    let to_ours = track_map(diff::match_tracks(&base.tracks, &ours.tracks), base.tracks.len());
+    let to_theirs = track_map(diff::match_tracks(&base.tracks, &theirs.tracks), base.tracks.len());
+    let mut conflicts = Vec::new();
+    let mut tracks = Vec::new();
+
+    for (bi, bt) in base.tracks.iter().enumerate() {
+        match (to_ours[bi], to_theirs[bi]) {
+            (Some(oi), Some(ti)) => {
+                let (ot, tt) = (&ours.tracks[oi], &theirs.tracks[ti]);
+                let name = pick3(&bt.name, &ot.name, &tt.name, |a, b| a == b).clone();
+                let segments = merge_track(&name, bt, ot, tt, &mut conflicts);
+                tracks.push(merged_track(name, ot.kind.clone(), None, segments, (oi, 0)));
+            }
+            (Some(oi), None) => {
+                let ot = &ours.tracks[oi];
+                if let Some(segments) = deleted_track(bt, ot, Side::Theirs, &mut conflicts) {
+                    tracks.push(merged_track(ot.name.clone(), ot.kind.clone(), Some(Side::Theirs), segments, (oi, 0)));
+                }
+            }
+            (None, Some(ti)) => {
+                let tt = &theirs.tracks[ti];
+                if let Some(segments) = deleted_track(bt, tt, Side::Ours, &mut conflicts) {
+                    tracks.push(merged_track(tt.name.clone(), tt.kind.clone(), Some(Side::Ours), segments, (usize::MAX, bi)));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    let ours_added: Vec<usize> = (0..ours.tracks.len()).filter(|i| !to_ours.contains(&Some(*i))).collect();
+    let theirs_added: Vec<usize> = (0..theirs.tracks.len()).filter(|i| !to_theirs.contains(&Some(*i))).collect();
+    let ours_new: Vec<Track> = ours_added.iter().map(|&i| ours.tracks[i].clone()).collect();
+    let theirs_new: Vec<Track> = theirs_added.iter().map(|&i| theirs.tracks[i].clone()).collect();
+    let both_added = diff::match_tracks(&ours_new, &theirs_new);
+
+    for (a, ot) in ours_new.iter().enumerate() {
+        let oi = ours_added[a];
+        let segments = match both_added.iter().find(|&&(o, _)| o == a) {
+            Some(&(_, t)) => {
+                let empty = Track { name: ot.name.clone(), kind: ot.kind.clone(), clips: Vec::new() };
+                merge_track(&ot.name, &empty, ot, &theirs_new[t], &mut conflicts)
+            }
+            None => vec![Segment::Clean(items(ot))],
+        };
+        tracks.push(merged_track(ot.name.clone(), ot.kind.clone(), None, segments, (oi, 0)));
+    }
+    for (a, tt) in theirs_new.iter().enumerate() {
+        if both_added.iter().any(|&(_, t)| t == a) {
+            continue;
+        }
+        let segments = vec![Segment::Clean(items(tt))];
+        tracks.push(merged_track(tt.name.clone(), tt.kind.clone(), None, segments, (usize::MAX, theirs_added[a])));
+    }
+
+    tracks.sort_by_key(|t| t.order);
+
+    let same_time = |a: &Option<RationalTime>, b: &Option<RationalTime>| {
+        a.as_ref().map(ExactTime::from) == b.as_ref().map(ExactTime::from)
+    };
+    MergeResult {
+        name: pick3(&base.name, &ours.name, &theirs.name, |a, b| a == b).clone(),
+        global_start_time: pick3(&base.global_start_time, &ours.global_start_time, &theirs.global_start_time, same_time).clone(),
+        drop_frame: *pick3(&base.drop_frame, &ours.drop_frame, &theirs.drop_frame, |a, b| a == b),
+        conflicts,
+        tracks,
+    }
+}
+
+fn merged_track(
+    name: String,
+    kind: TrackKind,
+    removed_in: Option<Side>,
+    segments: Vec<Segment>,
+    position: (usize, usize),
+) -> MergedTrack {
+    // Tracks keep their order in ours, then theirs; video stays above audio
+    // whichever side added it.
+    let kind_order = if kind == TrackKind::Video { 0 } else { 1 };
+    MergedTrack { name, kind, removed_in, segments, order: (kind_order, position.0, position.1) }
+}
+
+/// A track one side deleted: gone if the other side left it alone, a
+/// conflict if the other side edited it.
+fn deleted_track(
+    base: &Track,
+    kept: &Track,
+    deleted_by: Side,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<Vec<Segment>> {
+    let (base_items, kept_items) = (items(base), items(kept));
+    if same_items(&base_items, &kept_items) {
+        return None;
+    }
+    let (ours, theirs) = match deleted_by {
+        Side::Theirs => (kept_items, Vec::new()),
+        _ => (Vec::new(), kept_items),
+    };
+    let id = push_conflict(conflicts, ConflictKind::TrackDeleteEdit, kept, base_items, ours, theirs);
+    Some(vec![Segment::Conflict(id)])
+}
+
+fn track_map(pairs: Vec<(usize, usize)>, len: usize) -> Vec<Option<usize>> {
+    let mut map = vec![None; len];
+    for (b, other) in pairs {
+        map[b] = Some(other);
+    }
+    map
+}
+
+fn items(track: &Track) -> Vec<Item> {
+    let mut previous_end = ExactTime::ZERO;
+    track.clips.iter().zip(track.record_ranges())
+        .map(|(clip, record)| {
+            let lead = record.exact_start() - previous_end;
+            previous_end = record.exact_end();
+            Item { clip: clip.clone(), lead }
+        })
+        .collect()
+}
+
+/// diff3 over one track: clips matched in all three versions anchor the
+/// merge, and the runs between anchors are taken from whichever side changed
+/// them.
+fn merge_track(name: &str, base: &Track, ours: &Track, theirs: &Track, conflicts: &mut Vec<MergeConflict>) -> Vec<Segment> {
+    let (base_items, ours_items, theirs_items) = (items(base), items(ours), items(theirs));
+    let to_ours = track_map(diff::align_in_order(base, ours), base_items.len());
+    let to_theirs = track_map(diff::align_in_order(base, theirs), base_items.len());
+    let track = Track { name: name.to_string(), kind: ours.kind.clone(), clips: Vec::new() };
+
+    let mut segments = Vec::new();
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    for i in 0..base_items.len() {
+        let (Some(oi), Some(ti)) = (to_ours[i], to_theirs[i]) else { continue };
+        merge_run(&track, &base_items[b..i], &ours_items[o..oi], &theirs_items[t..ti], &mut segments, conflicts);
+        merge_anchor(&track, &base_items[i], &ours_items[oi], &theirs_items[ti], &mut segments, conflicts);
+        (b, o, t) = (i + 1, oi + 1, ti + 1);
+    }
+    merge_run(&track, &base_items[b..], &ours_items[o..], &theirs_items[t..], &mut segments, conflicts);
+    segments
+}
+
+fn merge_run(
+    track: &Track,
+    base: &[Item],
+    ours: &[Item],
+    theirs: &[Item],
+    segments: &mut Vec<Segment>,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    if same_items(ours, base) {
+        push_clean(segments, theirs);
+    } else if same_items(theirs, base) || same_items(ours, theirs) {
+        push_clean(segments, ours);
+    } else {
+        let kind = if base.is_empty() {
+            ConflictKind::BothInserted
+        } else if ours.is_empty() || theirs.is_empty() {
+            ConflictKind::DeleteEdit
+        } else {
+            ConflictKind::OverlappingEdits
+        };
+        let id = push_conflict(conflicts, kind, track, base.to_vec(), ours.to_vec(), theirs.to_vec());
+        segments.push(Segment::Conflict(id));
+    }
+}
+
+/// Merge one clip present in all three versions field by field, so one side
+/// trimming it and the other renaming it both survive.
+fn merge_anchor(
+    track: &Track,
+    base: &Item,
+    ours: &Item,
+    theirs: &Item,
+    segments: &mut Vec<Segment>,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    let mut merged = base.clone();
+    let mut conflicting = Vec::new();
+    for field in [Field::Source, Field::Lead, Field::Label] {
+        let ours_changed = !field_eq(base, ours, field);
+        let theirs_changed = !field_eq(base, theirs, field);
+        match (ours_changed, theirs_changed) {
+            (false, false) => {}
+            (true, false) => apply(&mut merged, ours, field),
+            (false, true) => apply(&mut merged, theirs, field),
+            (true, true) if field_eq(ours, theirs, field) => apply(&mut merged, ours, field),
+            (true, true) => conflicting.push(field),
+        }
+    }
+
+    let Some(&first) = conflicting.first() else {
+        push_clean(segments, std::slice::from_ref(&merged));
+        return;
+    };
+    let (mut ours_version, mut theirs_version) = (merged.clone(), merged);
+    for &field in &conflicting {
+        apply(&mut ours_version, ours, field);
+        apply(&mut theirs_version, theirs, field);
+    }
+    let kind = match first {
+        Field::Source => ConflictKind::BothTrimmed,
+        Field::Lead => ConflictKind::BothMoved,
+        Field::Label => ConflictKind::BothRenamed,
+    };
+    let id = push_conflict(conflicts, kind, track, vec![base.clone()], vec![ours_version], vec![theirs_version]);
+    segments.push(Segment::Conflict(id));
+}
+
+fn push_clean(segments: &mut Vec<Segment>, items: &[Item]) {
+    if items.is_empty() {
+        return;
+    }
+    if let Some(Segment::Clean(existing)) = segments.last_mut() {
+        existing.extend_from_slice(items);
+    } else {
+        segments.push(Segment::Clean(items.to_vec()));
+    }
+}
+
+fn push_conflict(
+    conflicts: &mut Vec<MergeConflict>,
+    kind: ConflictKind,
+    track: &Track,
+    base: Vec<Item>,
+    ours: Vec<Item>,
+    theirs: Vec<Item>,
+) -> usize {
+    let id = conflicts.len();
+    let clips = |items: &[Item]| items.iter().map(|i| i.clip.clone()).collect::<Vec<_>>();
+    let record_start = ours.first()
+        .or(theirs.first())
+        .or(base.first())
+        .and_then(|i| i.clip.record_start.clone());
+    conflicts.push(MergeConflict {
+        id,
+        kind,
+        track: track.name.clone(),
+        track_kind: track.kind.clone(),
+        record_start,
+        base: clips(&base),
+        ours: clips(&ours),
+        theirs: clips(&theirs),
+        resolution: None,
+        choices: [base, ours, theirs],
+    });
+    id
+}
+
+fn field_eq(a: &Item, b: &Item, field: Field) -> bool {
+    match field {
+        Field::Source => {
+            range_eq(a.clip.source_range.as_ref(), b.clip.source_range.as_ref())
+                && range_eq(a.clip.trimmed_range.as_ref(), b.clip.trimmed_range.as_ref())
+        }
+        Field::Lead => a.lead == b.lead,
+        Field::Label => a.clip.name == b.clip.name && a.clip.reel == b.clip.reel,
+    }
+}
+
+fn apply(dst: &mut Item, src: &Item, field: Field) {
+    match field {
+        Field::Source => {
+            dst.clip.source_range = src.clip.source_range.clone();
+            dst.clip.trimmed_range = src.clip.trimmed_range.clone();
+        }
+        Field::Lead => dst.lead = src.lead,
+        Field::Label => {
+            dst.clip.name = src.clip.name.clone();
+            dst.clip.reel = src.clip.reel.clone();
+        }
+    }
+}
+
+fn same_items(a: &[Item], b: &[Item]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(x, y)| {
+            x.clip.media_ref == y.clip.media_ref
+                && field_eq(x, y, Field::Source)
+                && field_eq(x, y, Field::Lead)
+                && field_eq(x, y, Field::Label)
+        })
+}
+
+fn range_eq(a: Option<&TimeRange>, b: Option<&TimeRange>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.exact_start() == b.exact_start() && a.exact_duration() == b.exact_duration(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// The value a three-way merge keeps: whichever side changed it, ours when
+/// both did.
+fn pick3<'a, T>(base: &'a T, ours: &'a T, theirs: &'a T, eq: impl Fn(&T, &T) -> bool) -> &'a T {
+    if eq(base, ours) { theirs } else { ours }
+}
+
+fn record_rate(clip: &Clip) -> FrameRate {
+    clip.record_start.as_ref()
+        .map(|t| t.rate)
+        .or_else(|| clip.source_range.as_ref().map(|r| r.duration.rate))
+        .and_then(FrameRate::from_f64)
+        .unwrap_or(FrameRate::new(24, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(name: &str, start: f64, duration: f64) -> Clip {
+        Clip {
+            name: name.to_string(),
+            media_ref: Some(format!("{name}.mov")),
+            source_range: Some(TimeRange {
+                start: RationalTime::new(start, 24.0),
+                duration: RationalTime::new(duration, 24.0),
+            }),
+            trimmed_range: None,
+            record_start: None,
+            reel: None,
+        }
+    }
+
+    fn timeline(clips: Vec<Clip>) -> Timeline {
+        Timeline {
+            name: "Edit".to_string(),
+            tracks: vec![Track { name: "V1".to_string(), kind: TrackKind::Video, clips }],
+            duration: None,
+            global_start_time: None,
+            drop_frame: false,
+        }
+    }
+
+    fn summary(timeline: &Timeline) -> Vec<(String, f64, f64)> {
+        timeline.tracks[0].clips.iter()
+            .map(|c| {
+                let range = c.source_range.as_ref().unwrap();
+                (c.name.clone(), range.start.value, range.duration.value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn separate_edits_merge_cleanly() {
+        let base = timeline(vec![clip("A", 0.0, 48.0), clip("B", 0.0, 48.0), clip("C", 0.0, 48.0)]);
+        let ours = timeline(vec![clip("A", 0.0, 48.0), clip("B", 12.0, 36.0), clip("C", 0.0, 48.0)]);
+        let theirs = timeline(vec![
+            clip("A", 0.0, 48.0), clip("B", 0.0, 48.0), clip("C", 0.0, 48.0), clip("D", 0.0, 24.0),
+        ]);
+
+        let merged = merge_timelines(&base, &ours, &theirs);
+        assert!(merged.conflicts.is_empty());
+        let result = merged.timeline().unwrap();
+        assert_eq!(summary(&result), vec![
+            ("A".to_string(), 0.0, 48.0),
+            ("B".to_string(), 12.0, 36.0),
+            ("C".to_string(), 0.0, 48.0),
+            ("D".to_string(), 0.0, 24.0),
+        ]);
+    }
+
+    #[test]
+    fn trimming_the_same_clip_twice_conflicts() {
+        let base = timeline(vec![clip("A", 0.0, 48.0), clip("B", 0.0, 48.0)]);
+        let ours = timeline(vec![clip("A", 0.0, 48.0), clip("B", 12.0, 36.0)]);
+        let theirs = timeline(vec![clip("A", 0.0, 48.0), clip("B", 0.0, 24.0)]);
+
+        let mut merged = merge_timelines(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].kind, ConflictKind::BothTrimmed);
+        assert!(merged.timeline().is_err());
+
+        merged.resolve(0, Side::Theirs).unwrap();
+        assert!(merged.is_resolved());
+        assert_eq!(summary(&merged.timeline().unwrap()), vec![
+            ("A".to_string(), 0.0, 48.0),
+            ("B".to_string(), 0.0, 24.0),
+        ]);
+    }
+}
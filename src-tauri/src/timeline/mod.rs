@@ -1,5 +1,15 @@
 pub mod parser;
 pub mod diff;
+pub mod writer;
+pub mod time;
+pub mod report;
+pub mod merge;
+pub mod validate;
+pub mod usage;
+pub mod lineage;
+pub mod subtitle;
+#[cfg(feature = "otio-native")]
+pub mod native;
 
 use serde::{Deserialize, Serialize};
 
@@ -17,6 +27,14 @@ impl RationalTime {
     pub fn seconds(&self) -> f64 {
         if self.rate == 0.0 { 0.0 } else { self.value / self.rate }
     }
+
+    /// Express the same instant at a different rate.
+    pub fn rescaled_to(&self, rate: f64) -> RationalTime {
+        if self.rate == rate || self.rate == 0.0 {
+            return RationalTime::new(self.value, rate);
+        }
+        RationalTime::new(self.value * rate / self.rate, rate)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +55,11 @@ pub struct Clip {
     pub media_ref: Option<String>,
     pub source_range: Option<TimeRange>,
     pub trimmed_range: Option<TimeRange>,
+    /// Position of the clip's first frame on its track, relative to the
+    /// start of the timeline.
+    pub record_start: Option<RationalTime>,
+    /// Tape or camera-roll name of the source, as used in EDLs.
+    pub reel: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,9 +69,58 @@ pub struct Track {
     pub clips: Vec<Clip>,
 }
 
+impl Track {
+    /// Where each clip sits on the track. Clips without an explicit
+    /// `record_start` are placed directly after the previous clip.
+    pub fn record_ranges(&self) -> Vec<TimeRange> {
+        let mut ranges = Vec::with_capacity(self.clips.len());
+        let mut position: Option<RationalTime> = None;
+
+        for clip in &self.clips {
+            let rate = clip.source_range.as_ref()
+                .map(|r| r.duration.rate)
+                .or_else(|| clip.record_start.as_ref().map(|t| t.rate))
+                .unwrap_or(0.0);
+            let start = match (&clip.record_start, &position) {
+                (Some(rs), _) => rs.clone(),
+                (None, Some(pos)) => pos.clone(),
+                (None, None) => RationalTime::new(0.0, rate),
+            };
+            let duration = clip.source_range.as_ref()
+                .map(|r| r.duration.rescaled_to(start.rate))
+                .unwrap_or_else(|| RationalTime::new(0.0, start.rate));
+
+            position = Some(RationalTime::new(start.value + duration.value, start.rate));
+            ranges.push(TimeRange { start, duration });
+        }
+
+        ranges
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timeline {
     pub name: String,
     pub tracks: Vec<Track>,
     pub duration: Option<RationalTime>,
+    pub global_start_time: Option<RationalTime>,
+    /// Whether the source presents timecode as drop-frame.
+    #[serde(default)]
+    pub drop_frame: bool,
+}
+
+impl Timeline {
+    /// The timeline's frame rate: that of its start time, or failing that
+    /// of its first clip.
+    pub fn frame_rate(&self) -> Option<time::FrameRate> {
+        self.global_start_time.as_ref()
+            .map(|t| t.rate)
+            .or_else(|| {
+                self.tracks.iter()
+                    .flat_map(|t| &t.clips)
+                    .find_map(|c| c.record_start.as_ref().map(|t| t.rate)
+                        .or_else(|| c.source_range.as_ref().map(|r| r.duration.rate)))
+            })
+            .and_then(time::FrameRate::from_f64)
+    }
 }
@@ -0,0 +1,70 @@
+//! OTIO reading through the OpenTimelineIO library itself, via `otio-sys`.
+//! Only built with the `otio-native` feature; the JSON parser in `parser`
+//! remains the default.
+
+use otio_sys::safe;
+use super::{Clip, RationalTime, TimeRange, Timeline, Track, TrackKind};
+
+/// Read an OTIO document whose root is a `Timeline`.
+///
+/// Record positions come from OTIO's own `range_in_parent`, so gaps and
+/// transitions are accounted for the way the library sees them. Metadata the
+/// bindings do not expose (reels, drop-frame, TurnAround's stored trimmed
+/// ranges) is left unset here; `parser` fills it in from the JSON.
+pub fn parse_otio_native(json_str: &str) -> Result<Timeline, String> {
+    let native = safe::Timeline::from_json_str(json_str).map_err(|e| e.to_string())?;
+    convert_timeline(&native)
+}
+
+pub fn convert_timeline(native: &safe::Timeline) -> Result<Timeline, String> {
+    let mut tracks = Vec::new();
+    for (i, track) in native.tracks().iter().enumerate() {
+        let kind = match track.kind() {
+            safe::TrackKind::Audio => TrackKind::Audio,
+            _ => TrackKind::Video,
+        };
+        let name = match track.name() {
+            n if n.is_empty() => format!("Track {}", i + 1),
+            n => n,
+        };
+
+        let mut clips = Vec::new();
+        for clip in track.clips() {
+            let record = clip.range_in_parent().map_err(|e| e.to_string())?;
+            clips.push(Clip {
+                name: match clip.name() {
+                    n if n.is_empty() => "Untitled Clip".to_string(),
+                    n => n,
+                },
+                media_ref: clip.media_url(),
+                source_range: clip.source_range().map(convert_range),
+                trimmed_range: None,
+                record_start: Some(convert_time(record.start_time)),
+                reel: None,
+            });
+        }
+        tracks.push(Track { name, kind, clips });
+    }
+
+    Ok(Timeline {
+        name: match native.name() {
+            n if n.is_empty() => "Untitled".to_string(),
+            n => n,
+        },
+        tracks,
+        duration: None,
+        global_start_time: native.global_start_time().map(convert_time),
+        drop_frame: false,
+    })
+}
+
+fn convert_time(time: safe::RationalTime) -> RationalTime {
+    RationalTime::new(time.value, time.rate)
+}
+
+fn convert_range(range: safe::TimeRange) -> TimeRange {
+    TimeRange {
+        start: convert_time(range.start_time),
+        duration: convert_time(range.duration),
+    }
+}
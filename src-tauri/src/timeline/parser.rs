@@ -1,5 +1,7 @@
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use super::{Timeline, Track, TrackKind, Clip, TimeRange, RationalTime};
+use super::time::{self, FrameRate};
 
 /// Parse an OTIO JSON file into our Timeline model.
 /// OTIO files use a well-defined JSON schema that we parse natively.
@@ -10,6 +12,16 @@ pub fn parse_otio_file(path: &Path) -> Result<Timeline, String> {
 }
 
 pub fn parse_otio_json(json_str: &str) -> Result<Timeline, String> {
+    parse_otio_json_all(json_str)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Empty collection".to_string())
+}
+
+/// Every timeline in an OTIO document. A `SerializableCollection` (as
+/// written for a bin of sequences) yields all of its timelines, including
+/// those in nested collections.
+pub fn parse_otio_json_all(json_str: &str) -> Result<Vec<Timeline>, String> {
     let value: serde_json::Value = serde_json::from_str(json_str)
         .map_err(|e| format!("Invalid JSON: {e}"))?;
 
@@ -18,27 +30,81 @@ pub fn parse_otio_json(json_str: &str) -> Result<Timeline, String> {
         .unwrap_or("");
 
     if schema_type.starts_with("Timeline") {
-        parse_timeline_object(&value)
+        Ok(vec![parse_timeline_value(&value)?])
     } else if schema_type.starts_with("SerializableCollection") {
-        let children = value.get("children")
-            .and_then(|v| v.as_array())
-            .ok_or("No children in collection")?;
-        if let Some(first) = children.first() {
-            parse_timeline_object(first)
-        } else {
-            Err("Empty collection".to_string())
-        }
+        let mut timelines = Vec::new();
+        collect_otio_timelines(&value, &mut timelines)?;
+        Ok(timelines)
     } else {
         Err(format!("Unsupported OTIO schema: {schema_type}"))
     }
 }
 
+/// A `Timeline` object, whether the document root or inside a collection.
+/// With the `otio-native` feature it is read by OpenTimelineIO itself, and
+/// what the bindings don't expose (trimmed ranges, reels, drop-frame) is
+/// taken from the JSON.
+#[cfg(feature = "otio-native")]
+fn parse_timeline_value(value: &serde_json::Value) -> Result<Timeline, String> {
+    let mut timeline = super::native::parse_otio_native(&value.to_string())?;
+    timeline.drop_frame = timeline_drop_frame(value);
+
+    // The bindings keep only the Track children of the stack and the Clip
+    // children of a track, so skip the same children here to keep the
+    // native and JSON sides paired.
+    let track_vals = value.get("tracks").into_iter().flat_map(|stack| children_of(stack, "Track"));
+    for (track, track_val) in timeline.tracks.iter_mut().zip(track_vals) {
+        for (clip, clip_val) in track.clips.iter_mut().zip(children_of(track_val, "Clip")) {
+            clip.trimmed_range = clip_trimmed_range(clip_val);
+            clip.reel = clip_reel(clip_val);
+        }
+    }
+    Ok(timeline)
+}
+
+/// The children of a composition whose `OTIO_SCHEMA` is `schema` at any
+/// version.
+#[cfg(feature = "otio-native")]
+fn children_of<'a>(value: &'a serde_json::Value, schema: &'a str) -> impl Iterator<Item = &'a serde_json::Value> {
+    value.get("children")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(move |child| {
+            let name = child.get("OTIO_SCHEMA").and_then(|v| v.as_str()).unwrap_or("");
+            name.split('.').next() == Some(schema)
+        })
+}
+
+#[cfg(not(feature = "otio-native"))]
+fn parse_timeline_value(value: &serde_json::Value) -> Result<Timeline, String> {
+    parse_timeline_object(value)
+}
+
+fn collect_otio_timelines(collection: &serde_json::Value, timelines: &mut Vec<Timeline>) -> Result<(), String> {
+    let children = collection.get("children")
+        .and_then(|v| v.as_array())
+        .ok_or("No children in collection")?;
+    for child in children {
+        let schema = child.get("OTIO_SCHEMA").and_then(|v| v.as_str()).unwrap_or("");
+        if schema.starts_with("Timeline") {
+            timelines.push(parse_timeline_value(child)?);
+        } else if schema.starts_with("SerializableCollection") {
+            collect_otio_timelines(child, timelines)?;
+        }
+    }
+    Ok(())
+}
+
 fn parse_timeline_object(value: &serde_json::Value) -> Result<Timeline, String> {
     let name = value.get("name")
         .and_then(|v| v.as_str())
         .unwrap_or("Untitled")
         .to_string();
 
+    let global_start_time = value.get("global_start_time").and_then(parse_rational_time);
+    let drop_frame = timeline_drop_frame(value);
+
     let mut tracks = Vec::new();
 
     if let Some(stack) = value.get("tracks") {
@@ -58,14 +124,38 @@ fn parse_timeline_object(value: &serde_json::Value) -> Result<Timeline, String>
                 let kind = if kind_str == "Audio" { TrackKind::Audio } else { TrackKind::Video };
 
                 let mut clips = Vec::new();
+                let mut position: Option<RationalTime> = None;
                 if let Some(children) = track_val.get("children").and_then(|v| v.as_array()) {
                     for child in children {
                         let schema = child.get("OTIO_SCHEMA")
                             .and_then(|v| v.as_str())
                             .unwrap_or("");
+                        // Transitions overlap their neighbours and take no time of their own.
+                        if schema.starts_with("Transition") {
+                            continue;
+                        }
+
+                        let duration = item_duration(child);
+                        let start = match (&position, &duration) {
+                            (Some(pos), _) => pos.clone(),
+                            (None, Some(d)) => RationalTime::new(0.0, d.rate),
+                            (None, None) => RationalTime::new(0.0, 0.0),
+                        };
+
                         if schema.starts_with("Clip") {
-                            clips.push(parse_clip(child));
+                            let mut clip = parse_clip(child);
+                            clip.record_start = Some(start.clone());
+                            clips.push(clip);
                         }
+
+                        position = Some(match duration {
+                            Some(d) if start.rate > 0.0 => {
+                                let d = d.rescaled_to(start.rate);
+                                RationalTime::new(start.value + d.value, start.rate)
+                            }
+                            Some(d) => d,
+                            None => start,
+                        });
                     }
                 }
 
@@ -74,7 +164,7 @@ fn parse_timeline_object(value: &serde_json::Value) -> Result<Timeline, String>
         }
     }
 
-    Ok(Timeline { name, tracks, duration: None })
+    Ok(Timeline { name, tracks, duration: None, global_start_time, drop_frame })
 }
 
 fn parse_clip(value: &serde_json::Value) -> Clip {
@@ -89,24 +179,50 @@ fn parse_clip(value: &serde_json::Value) -> Clip {
         .map(|s| s.to_string());
 
     let source_range = value.get("source_range").and_then(parse_time_range);
-    let trimmed_range = value.get("trimmed_range").and_then(parse_time_range);
+    let trimmed_range = clip_trimmed_range(value);
+    let reel = clip_reel(value);
 
-    Clip { name, media_ref, source_range, trimmed_range }
+    Clip { name, media_ref, source_range, trimmed_range, record_start: None, reel }
 }
 
-fn parse_time_range(value: &serde_json::Value) -> Option<TimeRange> {
-    let start_time = value.get("start_time")?;
-    let duration = value.get("duration")?;
+fn timeline_drop_frame(value: &serde_json::Value) -> bool {
+    value.pointer("/metadata/turnaround/drop_frame")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
 
+fn clip_trimmed_range(value: &serde_json::Value) -> Option<TimeRange> {
+    value.get("trimmed_range")
+        .or_else(|| value.pointer("/metadata/turnaround/trimmed_range"))
+        .and_then(parse_time_range)
+}
+
+fn clip_reel(value: &serde_json::Value) -> Option<String> {
+    value.pointer("/metadata/cmx_3600/reel")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+/// Length an item occupies on its track: its own source range, falling back to
+/// the media's available range the way OTIO computes `trimmed_range`.
+fn item_duration(value: &serde_json::Value) -> Option<RationalTime> {
+    value.get("source_range")
+        .and_then(parse_time_range)
+        .or_else(|| value.pointer("/media_reference/available_range").and_then(parse_time_range))
+        .map(|r| r.duration)
+}
+
+fn parse_time_range(value: &serde_json::Value) -> Option<TimeRange> {
     Some(TimeRange {
-        start: RationalTime {
-            value: start_time.get("value")?.as_f64()?,
-            rate: start_time.get("rate")?.as_f64()?,
-        },
-        duration: RationalTime {
-            value: duration.get("value")?.as_f64()?,
-            rate: duration.get("rate")?.as_f64()?,
-        },
+        start: parse_rational_time(value.get("start_time")?)?,
+        duration: parse_rational_time(value.get("duration")?)?,
+    })
+}
+
+fn parse_rational_time(value: &serde_json::Value) -> Option<RationalTime> {
+    Some(RationalTime {
+        value: value.get("value")?.as_f64()?,
+        rate: value.get("rate")?.as_f64()?,
     })
 }
 
@@ -117,25 +233,697 @@ pub fn parse_fcpxml_file(path: &Path) -> Result<Timeline, String> {
     parse_fcpxml(&content)
 }
 
-fn parse_fcpxml(_content: &str) -> Result<Timeline, String> {
-    // FCPXML is an XML format. For MVP, we provide a basic parser.
-    // Full OTIO C++ FFI will handle this more robustly.
+pub fn parse_fcpxml(content: &str) -> Result<Timeline, String> {
+    parse_fcpxml_all(content)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No sequence found in FCPXML".to_string())
+}
+
+/// Every project in an FCPXML document; a library or event export can hold
+/// several. Sequences inside compound clips are not projects and are skipped.
+pub fn parse_fcpxml_all(content: &str) -> Result<Vec<Timeline>, String> {
+    let doc = parse_xml(content)?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "fcpxml" {
+        return Err("Not an FCPXML document".to_string());
+    }
+
+    let mut resources = FcpxmlResources::default();
+    for node in root.descendants().filter(|n| n.is_element()) {
+        let Some(id) = node.attribute("id") else { continue };
+        match node.tag_name().name() {
+            "format" => {
+                if let Some(fd) = node.attribute("frameDuration").and_then(parse_fcpxml_time) {
+                    if fd > 0.0 {
+                        resources.frame_rates.insert(id, 1.0 / fd);
+                    }
+                }
+            }
+            "asset" => {
+                let src = node.attribute("src")
+                    .map(|s| s.to_string())
+                    .or_else(|| {
+                        node.children()
+                            .find(|c| c.has_tag_name("media-rep"))
+                            .and_then(|c| c.attribute("src"))
+                            .map(|s| s.to_string())
+                    });
+                if let Some(src) = src {
+                    resources.assets.insert(id, src);
+                }
+                let reel = node.descendants()
+                    .find(|c| c.has_tag_name("md") && c.attribute("key") == Some("com.apple.proapps.studio.reel"))
+                    .and_then(|c| c.attribute("value"));
+                if let Some(reel) = reel {
+                    resources.reels.insert(id, reel.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut sequences: Vec<_> = root.descendants()
+        .filter(|n| n.has_tag_name("sequence") && n.parent().is_some_and(|p| p.has_tag_name("project")))
+        .collect();
+    if sequences.is_empty() {
+        sequences.extend(root.descendants().find(|n| n.has_tag_name("sequence")));
+    }
+    if sequences.is_empty() {
+        return Err("No sequence found in FCPXML".to_string());
+    }
+
+    Ok(sequences.into_iter().map(|seq| parse_fcpxml_sequence(seq, &resources)).collect())
+}
+
+#[derive(Default)]
+struct FcpxmlResources<'a> {
+    frame_rates: HashMap<&'a str, f64>,
+    assets: HashMap<&'a str, String>,
+    reels: HashMap<&'a str, String>,
+}
+
+/// FCPXML describes a sequence as a primary storyline (the `spine`) with
+/// connected clips hanging off it in numbered lanes. The primary storyline
+/// becomes the first video track; each lane becomes its own track, positive
+/// lanes as video and negative lanes as audio.
+fn parse_fcpxml_sequence(sequence: roxmltree::Node, resources: &FcpxmlResources) -> Timeline {
+    let name = sequence.parent()
+        .filter(|p| p.has_tag_name("project"))
+        .and_then(|p| p.attribute("name"))
+        .unwrap_or("Untitled")
+        .to_string();
+
+    let rate = sequence.attribute("format")
+        .and_then(|f| resources.frame_rates.get(f).copied())
+        .unwrap_or(24.0);
+    let to_time = |seconds: f64| RationalTime::new((seconds * rate).round(), rate);
+
+    let tc_start = sequence.attribute("tcStart").and_then(parse_fcpxml_time).unwrap_or(0.0);
+    let duration = sequence.attribute("duration").and_then(parse_fcpxml_time).map(to_time);
+
+    let mut lanes: BTreeMap<i32, Vec<Clip>> = BTreeMap::new();
+    lanes.insert(0, Vec::new());
+
+    if let Some(spine) = sequence.children().find(|n| n.has_tag_name("spine")) {
+        let mut ctx = FcpxmlContext { assets: &resources.assets, reels: &resources.reels, to_time: &to_time, lanes: &mut lanes };
+        collect_fcpxml_storyline(spine, 0, 0.0, &mut ctx);
+    }
+
+    let mut tracks = Vec::new();
+    let mut audio_tracks = Vec::new();
+    for (lane, mut clips) in lanes {
+        clips.sort_by(|a, b| record_seconds(a).total_cmp(&record_seconds(b)));
+        if lane == 0 {
+            tracks.push(Track { name: "Primary Storyline".to_string(), kind: TrackKind::Video, clips });
+        } else if lane > 0 {
+            tracks.push(Track { name: format!("Connected V{lane}"), kind: TrackKind::Video, clips });
+        } else {
+            audio_tracks.push(Track { name: format!("Connected A{}", -lane), kind: TrackKind::Audio, clips });
+        }
+    }
+    audio_tracks.reverse();
+    tracks.extend(audio_tracks);
+
+    Timeline {
+        name,
+        tracks,
+        duration,
+        global_start_time: Some(to_time(tc_start)),
+        drop_frame: sequence.attribute("tcFormat") == Some("DF"),
+    }
+}
+
+struct FcpxmlContext<'a, 'd> {
+    assets: &'a HashMap<&'d str, String>,
+    reels: &'a HashMap<&'d str, String>,
+    to_time: &'a dyn Fn(f64) -> RationalTime,
+    lanes: &'a mut BTreeMap<i32, Vec<Clip>>,
+}
+
+/// Walk a storyline whose first item starts at `record_base` seconds
+/// (relative to the sequence start). Items in a storyline are contiguous.
+fn collect_fcpxml_storyline(spine: roxmltree::Node, lane: i32, record_base: f64, ctx: &mut FcpxmlContext) {
+    let mut record = record_base;
+    for item in spine.children().filter(|n| n.is_element()) {
+        let tag = item.tag_name().name();
+        if tag == "transition" {
+            continue;
+        }
+        let duration = item.attribute("duration").and_then(parse_fcpxml_time).unwrap_or(0.0);
+        collect_fcpxml_item(item, lane, record, ctx);
+        record += duration;
+    }
+}
+
+fn collect_fcpxml_item(item: roxmltree::Node, lane: i32, record: f64, ctx: &mut FcpxmlContext) {
+    let tag = item.tag_name().name();
+    let start = item.attribute("start").and_then(parse_fcpxml_time).unwrap_or(0.0);
+    let duration = item.attribute("duration").and_then(parse_fcpxml_time).unwrap_or(0.0);
+
+    if tag != "gap" {
+        let asset_id = item.attribute("ref")
+            .filter(|r| ctx.assets.contains_key(r))
+            .or_else(|| {
+                item.children()
+                    .filter(|c| c.has_tag_name("video") || c.has_tag_name("audio"))
+                    .find_map(|c| c.attribute("ref").filter(|r| ctx.assets.contains_key(r)))
+            });
+        let media_ref = asset_id.and_then(|id| ctx.assets.get(id).cloned());
+        let reel = asset_id.and_then(|id| ctx.reels.get(id).cloned());
+        let name = item.attribute("name").unwrap_or("Untitled Clip").to_string();
+        let clip = Clip {
+            name,
+            media_ref,
+            source_range: Some(TimeRange {
+                start: (ctx.to_time)(start),
+                duration: (ctx.to_time)(duration),
+            }),
+            trimmed_range: None,
+            record_start: Some((ctx.to_time)(record)),
+            reel,
+        };
+        ctx.lanes.entry(lane).or_default().push(clip);
+    }
+
+    // Connected clips and secondary storylines are positioned in the parent's
+    // local time, which starts at the parent's `start`.
+    for child in item.children().filter(|n| n.is_element()) {
+        let Some(child_lane) = child.attribute("lane").and_then(|l| l.parse::<i32>().ok()) else {
+            continue;
+        };
+        let offset = child.attribute("offset").and_then(parse_fcpxml_time).unwrap_or(start);
+        let child_record = record + (offset - start);
+        if child.has_tag_name("spine") {
+            collect_fcpxml_storyline(child, child_lane, child_record, ctx);
+        } else {
+            collect_fcpxml_item(child, child_lane, child_record, ctx);
+        }
+    }
+}
+
+/// Final Cut and Premiere start their exports with a `<!DOCTYPE>` line,
+/// which roxmltree refuses unless told otherwise.
+fn parse_xml(content: &str) -> Result<roxmltree::Document<'_>, String> {
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    roxmltree::Document::parse_with_options(content, options)
+        .map_err(|e| format!("Invalid XML: {e}"))
+}
+
+/// FCPXML times are rational seconds such as `1001/24000s` or `3600s`.
+fn parse_fcpxml_time(value: &str) -> Option<f64> {
+    let value = value.trim().strip_suffix('s')?;
+    match value.split_once('/') {
+        Some((num, den)) => {
+            let num: f64 = num.parse().ok()?;
+            let den: f64 = den.parse().ok()?;
+            if den == 0.0 { None } else { Some(num / den) }
+        }
+        None => value.parse().ok(),
+    }
+}
+
+fn record_seconds(clip: &Clip) -> f64 {
+    clip.record_start.as_ref().map(|t| t.seconds()).unwrap_or(0.0)
+}
+
+/// Parse Final Cut Pro 7 / Premiere Pro XML (`xmeml`) into our Timeline model.
+pub fn parse_xmeml(content: &str) -> Result<Timeline, String> {
+    parse_xmeml_all(content)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No sequence found in XML".to_string())
+}
+
+/// Every top-level sequence in an xmeml document. Premiere exports a bin of
+/// sequences into one file; sequences nested inside clips are left to their
+/// parent.
+pub fn parse_xmeml_all(content: &str) -> Result<Vec<Timeline>, String> {
+    let doc = parse_xml(content)?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "xmeml" {
+        return Err("Not an xmeml document".to_string());
+    }
+
+    // File elements are written in full once and referenced by id afterwards,
+    // possibly from a different sequence.
+    let mut files: HashMap<&str, XmemlFile> = HashMap::new();
+    for file in root.descendants().filter(|n| n.has_tag_name("file")) {
+        if let Some(id) = file.attribute("id") {
+            let parsed = xmeml_file(file);
+            if parsed.pathurl.is_some() || parsed.reel.is_some() {
+                files.insert(id, parsed);
+            }
+        }
+    }
+
+    let sequences: Vec<_> = root.descendants()
+        .filter(|n| n.has_tag_name("sequence") && !n.ancestors().skip(1).any(|a| a.has_tag_name("sequence")))
+        .collect();
+    if sequences.is_empty() {
+        return Err("No sequence found in XML".to_string());
+    }
+
+    Ok(sequences.into_iter().map(|seq| parse_xmeml_sequence(seq, &files)).collect())
+}
+
+fn parse_xmeml_sequence(sequence: roxmltree::Node, files: &HashMap<&str, XmemlFile>) -> Timeline {
+    let name = child_text(sequence, "name").unwrap_or("Untitled").to_string();
+    let rate = sequence.children()
+        .find(|n| n.has_tag_name("rate"))
+        .map(xmeml_rate)
+        .unwrap_or(24.0);
+
+    let timecode = sequence.children().find(|n| n.has_tag_name("timecode"));
+    let drop_frame = timecode
+        .and_then(|tc| child_text(tc, "displayformat"))
+        .map(|f| f.trim() == "DF")
+        .unwrap_or(false);
+    let global_start_time = timecode
+        .and_then(|tc| child_text(tc, "frame"))
+        .and_then(|f| f.trim().parse::<f64>().ok())
+        .map(|f| RationalTime::new(f, rate));
+    let duration = child_text(sequence, "duration")
+        .and_then(|d| d.trim().parse::<f64>().ok())
+        .map(|d| RationalTime::new(d, rate));
+
+    let mut tracks = Vec::new();
+    if let Some(media) = sequence.children().find(|n| n.has_tag_name("media")) {
+        for (tag, kind, prefix) in [("video", TrackKind::Video, "V"), ("audio", TrackKind::Audio, "A")] {
+            let Some(section) = media.children().find(|n| n.has_tag_name(tag)) else { continue };
+            for (i, track) in section.children().filter(|n| n.has_tag_name("track")).enumerate() {
+                let clips = parse_xmeml_track(track, rate, files);
+                tracks.push(Track { name: format!("{prefix}{}", i + 1), kind: kind.clone(), clips });
+            }
+        }
+    }
+
+    Timeline { name, tracks, duration, global_start_time, drop_frame }
+}
+
+struct XmemlFile {
+    pathurl: Option<String>,
+    reel: Option<String>,
+}
+
+fn xmeml_file(file: roxmltree::Node) -> XmemlFile {
+    XmemlFile {
+        pathurl: child_text(file, "pathurl").map(|s| s.to_string()),
+        reel: file.descendants()
+            .find(|n| n.has_tag_name("reel"))
+            .and_then(|r| child_text(r, "name"))
+            .map(|s| s.trim().to_string()),
+    }
+}
+
+fn parse_xmeml_track(track: roxmltree::Node, rate: f64, files: &HashMap<&str, XmemlFile>) -> Vec<Clip> {
+    let mut clips = Vec::new();
+    let mut previous_end = 0.0;
+
+    for item in track.children().filter(|n| n.has_tag_name("clipitem")) {
+        let frames = |tag: &str| child_text(item, tag).and_then(|v| v.trim().parse::<f64>().ok());
+        let src_in = frames("in").unwrap_or(0.0);
+        let src_out = frames("out").unwrap_or(src_in);
+        let length = src_out - src_in;
+
+        // Clips adjacent to a transition carry -1 for the edge that sits under it.
+        let start = match frames("start") {
+            Some(s) if s >= 0.0 => s,
+            _ => match frames("end") {
+                Some(e) if e >= 0.0 => e - length,
+                _ => previous_end,
+            },
+        };
+        previous_end = start + length;
+
+        let file = item.children().find(|n| n.has_tag_name("file"));
+        let inline = file.map(xmeml_file);
+        let shared = file.and_then(|f| f.attribute("id")).and_then(|id| files.get(id));
+        let media_ref = inline.as_ref().and_then(|f| f.pathurl.clone())
+            .or_else(|| shared.and_then(|f| f.pathurl.clone()));
+        let reel = inline.and_then(|f| f.reel)
+            .or_else(|| shared.and_then(|f| f.reel.clone()));
+
+        clips.push(Clip {
+            name: child_text(item, "name").unwrap_or("Untitled Clip").to_string(),
+            media_ref,
+            source_range: Some(TimeRange {
+                start: RationalTime::new(src_in, rate),
+                duration: RationalTime::new(length, rate),
+            }),
+            trimmed_range: None,
+            record_start: Some(RationalTime::new(start, rate)),
+            reel,
+        });
+    }
+
+    clips
+}
+
+fn xmeml_rate(rate: roxmltree::Node) -> f64 {
+    let timebase = child_text(rate, "timebase")
+        .and_then(|t| t.trim().parse::<f64>().ok())
+        .unwrap_or(24.0);
+    let ntsc = child_text(rate, "ntsc")
+        .map(|n| n.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if ntsc { timebase * 1000.0 / 1001.0 } else { timebase }
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    node.children().find(|n| n.has_tag_name(tag)).and_then(|n| n.text())
+}
+
+/// Parse a CMX3600 EDL. EDLs do not record their frame rate: drop-frame
+/// lists are read at 29.97, others at the lowest common rate their largest
+/// frame field fits (24, 25, 29.97, 50 or 59.94). Use `parse_edl_with_rate`
+/// when the rate is known.
+pub fn parse_edl(content: &str) -> Result<Timeline, String> {
+    let drop_frame = content.lines()
+        .any(|l| l.trim().to_uppercase().starts_with("FCM:") && !l.to_uppercase().contains("NON"));
+    let rate = if drop_frame { 30000.0 / 1001.0 } else { infer_edl_rate(content)? };
+    parse_edl_with_rate(content, rate, drop_frame)
+}
+
+fn infer_edl_rate(content: &str) -> Result<f64, String> {
+    let max_frame = content.lines()
+        .filter_map(|line| edl_event_fields(line.trim()))
+        .flat_map(|fields| fields[fields.len() - 4..].to_vec())
+        .filter_map(|tc| tc.rsplit([':', ';', ',', '.']).next()?.parse::<i64>().ok())
+        .max()
+        .unwrap_or(0);
+    match max_frame {
+        0..=23 => Ok(24.0),
+        24 => Ok(25.0),
+        25..=29 => Ok(30000.0 / 1001.0),
+        30..=49 => Ok(50.0),
+        50..=59 => Ok(60000.0 / 1001.0),
+        _ => Err(format!("EDL frame field {max_frame} is too large for any supported frame rate")),
+    }
+}
+
+/// The whitespace-separated fields of an event line, `None` for anything else.
+fn edl_event_fields(line: &str) -> Option<Vec<&str>> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    (fields.len() >= 8 && fields[0].chars().all(|c| c.is_ascii_digit())).then_some(fields)
+}
+
+struct EdlEvent {
+    channels: Vec<(TrackKind, usize)>,
+    reel: String,
+    src_in: f64,
+    src_out: f64,
+    rec_in: f64,
+    clip_name: Option<String>,
+    source_file: Option<String>,
+}
+
+pub fn parse_edl_with_rate(content: &str, rate: f64, drop_frame: bool) -> Result<Timeline, String> {
+    let frame_rate = FrameRate::from_f64(rate).ok_or_else(|| format!("Invalid frame rate: {rate}"))?;
+    let mut name = "EDL Timeline".to_string();
+    let mut events: Vec<EdlEvent> = Vec::new();
+
+    for (line_no, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(title) = trimmed.strip_prefix("TITLE:") {
+            name = title.trim().to_string();
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('*') {
+            let comment = comment.trim();
+            let Some(event) = events.last_mut() else { continue };
+            if let Some(clip) = comment.strip_prefix("FROM CLIP NAME:") {
+                event.clip_name = Some(clip.trim().to_string());
+            } else if let Some(file) = comment.strip_prefix("SOURCE FILE:") {
+                event.source_file = Some(file.trim().to_string());
+            }
+            continue;
+        }
+
+        let Some(fields) = edl_event_fields(trimmed) else { continue };
+        let times: Vec<f64> = fields[fields.len() - 4..]
+            .iter()
+            .map(|tc| time::parse_timecode_with(tc, frame_rate, drop_frame).map(|frames| frames as f64))
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("EDL line {}: {e}", line_no + 1))?;
+
+        events.push(EdlEvent {
+            channels: edl_channels(fields[2]),
+            reel: fields[1].to_string(),
+            src_in: times[0],
+            src_out: times[1],
+            rec_in: times[2],
+            clip_name: None,
+            source_file: None,
+        });
+    }
+
+    let record_origin = events.iter().map(|e| e.rec_in).fold(f64::INFINITY, f64::min);
+    let record_origin = if record_origin.is_finite() { record_origin } else { 0.0 };
+    let record_end = events.iter().map(|e| e.rec_in + (e.src_out - e.src_in)).fold(record_origin, f64::max);
+
+    let mut channels: BTreeMap<(u8, usize), Vec<Clip>> = BTreeMap::new();
+    for event in &events {
+        let length = event.src_out - event.src_in;
+        // The outgoing side of a dissolve is written as a zero-length event.
+        if length <= 0.0 {
+            continue;
+        }
+        for (kind, index) in &event.channels {
+            let order = if *kind == TrackKind::Video { 0 } else { 1 };
+            channels.entry((order, *index)).or_default().push(Clip {
+                name: event.clip_name.clone().unwrap_or_else(|| event.reel.clone()),
+                media_ref: event.source_file.clone(),
+                source_range: Some(TimeRange {
+                    start: RationalTime::new(event.src_in, rate),
+                    duration: RationalTime::new(length, rate),
+                }),
+                trimmed_range: None,
+                record_start: Some(RationalTime::new(event.rec_in - record_origin, rate)),
+                reel: Some(event.reel.clone()),
+            });
+        }
+    }
+
+    let tracks = channels.into_iter()
+        .map(|((order, index), mut clips)| {
+            clips.sort_by(|a, b| record_seconds(a).total_cmp(&record_seconds(b)));
+            let (kind, prefix) = if order == 0 { (TrackKind::Video, "V") } else { (TrackKind::Audio, "A") };
+            Track { name: format!("{prefix}{index}"), kind, clips }
+        })
+        .collect();
+
     Ok(Timeline {
-        name: "FCPXML Timeline".to_string(),
-        tracks: Vec::new(),
-        duration: None,
+        name,
+        tracks,
+        duration: Some(RationalTime::new(record_end - record_origin, rate)),
+        global_start_time: Some(RationalTime::new(record_origin, rate)),
+        drop_frame,
     })
 }
 
+/// Map an EDL channel field (`V`, `A`, `A2`, `AA`, `B`, `AA/V`, ...) to tracks.
+fn edl_channels(spec: &str) -> Vec<(TrackKind, usize)> {
+    let upper = spec.to_uppercase();
+    let mut channels = Vec::new();
+    if upper.contains('V') || upper == "B" {
+        channels.push((TrackKind::Video, 1));
+    }
+    for part in upper.split('/') {
+        match part {
+            "B" | "A" => channels.push((TrackKind::Audio, 1)),
+            "AA" => {
+                channels.push((TrackKind::Audio, 1));
+                channels.push((TrackKind::Audio, 2));
+            }
+            p if p.starts_with('A') => {
+                if let Ok(n) = p[1..].parse::<usize>() {
+                    channels.push((TrackKind::Audio, n));
+                }
+            }
+            _ => {}
+        }
+    }
+    channels
+}
+
 pub fn parse_timeline_from_path(path: &Path) -> Result<Timeline, String> {
     let ext = path.extension()
         .and_then(|e| e.to_str())
         .unwrap_or("")
         .to_lowercase();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read file: {e}"))?;
+    parse_timeline_str(&content, &ext)
+}
+
+/// Parse timeline content whose format is given by its original file extension.
+/// Used for committed objects, which are stored without an extension.
+/// Containers holding several timelines yield the first one.
+pub fn parse_timeline_str(content: &str, ext: &str) -> Result<Timeline, String> {
+    parse_timelines_str(content, ext)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No timeline found".to_string())
+}
+
+/// Every timeline in the content, in document order.
+pub fn parse_timelines_str(content: &str, ext: &str) -> Result<Vec<Timeline>, String> {
+    match ext.to_lowercase().as_str() {
+        "otio" => parse_otio_json_all(content),
+        "fcpxml" => parse_fcpxml_all(content),
+        "edl" => parse_edl(content).map(|tl| vec![tl]),
+        "xml" => {
+            let head = content.get(..content.len().min(4096)).unwrap_or(content);
+            if head.contains("<fcpxml") {
+                parse_fcpxml_all(content)
+            } else if head.contains("<xmeml") {
+                parse_xmeml_all(content)
+            } else {
+                Err("Unrecognised XML timeline (expected FCPXML or xmeml)".to_string())
+            }
+        }
+        other => Err(format!("Unsupported timeline format: .{other}")),
+    }
+}
+
+pub fn is_timeline_extension(ext: &str) -> bool {
+    matches!(ext.to_lowercase().as_str(), "otio" | "fcpxml" | "xml" | "edl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDL_25: &str = "TITLE: Promo
+FCM: NON-DROP FRAME
+
+001  TAPE1    V     C        01:00:00:00 01:00:02:12 00:00:00:00 00:00:02:12
+* FROM CLIP NAME: Shot A
+002  TAPE2    V     C        02:00:10:24 02:00:12:00 00:00:02:12 00:00:03:13
+* FROM CLIP NAME: Shot B
+";
+
+    #[test]
+    fn edl_rate_is_inferred_from_frame_fields() {
+        let timeline = parse_edl(EDL_25).unwrap();
+        assert_eq!(timeline.frame_rate(), Some(FrameRate::new(25, 1)));
+        assert!(!timeline.drop_frame);
+
+        let clips = &timeline.tracks[0].clips;
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips[1].name, "Shot B");
+        let source = clips[1].source_range.as_ref().unwrap();
+        assert_eq!(source.start.value, ((2 * 3600 + 10) * 25 + 24) as f64);
+        assert_eq!(source.duration.value, 26.0);
+        assert_eq!(clips[1].record_start.as_ref().unwrap().value, 62.0);
+    }
+
+    const FCPXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE fcpxml>
+<fcpxml version="1.9">
+  <resources>
+    <format id="r1" frameDuration="1/25s"/>
+    <asset id="r2" name="Interview" src="file:///media/interview.mov">
+      <metadata><md key="com.apple.proapps.studio.reel" value="A002"/></metadata>
+    </asset>
+    <asset id="r3" name="Broll" src="file:///media/broll.mov"/>
+  </resources>
+  <library><event name="Day 1">
+    <project name="Cut 1">
+      <sequence format="r1" tcStart="3600s" tcFormat="NDF">
+        <spine>
+          <asset-clip ref="r2" name="Interview" start="10s" duration="5s">
+            <asset-clip ref="r3" name="Broll" lane="1" offset="12s" start="0s" duration="2s"/>
+          </asset-clip>
+          <gap name="Gap" start="0s" duration="1s"/>
+          <asset-clip ref="r2" name="Interview end" start="30s" duration="2s"/>
+        </spine>
+      </sequence>
+    </project>
+  </event></library>
+</fcpxml>"#;
+
+    #[test]
+    fn fcpxml_spine_and_connected_clips() {
+        let timeline = parse_fcpxml(FCPXML).unwrap();
+        assert_eq!(timeline.name, "Cut 1");
+        assert_eq!(timeline.frame_rate(), Some(FrameRate::new(25, 1)));
+        assert_eq!(timeline.global_start_time.as_ref().unwrap().value, 90000.0);
+        assert!(!timeline.drop_frame);
+
+        let primary = &timeline.tracks[0];
+        assert_eq!(primary.name, "Primary Storyline");
+        let placed: Vec<(&str, f64, f64)> = primary.clips.iter()
+            .map(|c| (c.name.as_str(), c.record_start.as_ref().unwrap().value, c.source_range.as_ref().unwrap().start.value))
+            .collect();
+        assert_eq!(placed, vec![("Interview", 0.0, 250.0), ("Interview end", 150.0, 750.0)]);
+        assert_eq!(primary.clips[0].reel.as_deref(), Some("A002"));
+        assert_eq!(primary.clips[0].media_ref.as_deref(), Some("file:///media/interview.mov"));
+
+        let connected = &timeline.tracks[1];
+        assert_eq!(connected.name, "Connected V1");
+        assert_eq!(connected.clips[0].name, "Broll");
+        assert_eq!(connected.clips[0].record_start.as_ref().unwrap().value, 50.0);
+        assert_eq!(connected.clips[0].source_range.as_ref().unwrap().duration.value, 50.0);
+    }
+
+    const XMEML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE xmeml>
+<xmeml version="4">
+  <sequence id="seq1">
+    <name>Assembly</name>
+    <duration>150</duration>
+    <rate><timebase>30</timebase><ntsc>TRUE</ntsc></rate>
+    <timecode><frame>107892</frame><displayformat>DF</displayformat></timecode>
+    <media>
+      <video><track>
+        <clipitem id="c1"><name>Shot 1</name><start>0</start><end>60</end><in>100</in><out>160</out>
+          <file id="f1"><pathurl>file:///media/shot1.mov</pathurl><timecode><reel><name>R1</name></reel></timecode></file>
+        </clipitem>
+        <clipitem id="c2"><name>Shot 1 again</name><start>90</start><end>120</end><in>200</in><out>230</out>
+          <file id="f1"/>
+        </clipitem>
+      </track></video>
+      <audio><track>
+        <clipitem id="c3"><name>Music</name><start>0</start><end>150</end><in>0</in><out>150</out>
+          <file id="f2"><pathurl>file:///media/music.wav</pathurl></file>
+        </clipitem>
+      </track></audio>
+    </media>
+  </sequence>
+</xmeml>"#;
+
+    #[test]
+    fn xmeml_tracks_and_shared_files() {
+        let timeline = parse_xmeml(XMEML).unwrap();
+        assert_eq!(timeline.name, "Assembly");
+        assert_eq!(timeline.frame_rate(), Some(FrameRate::new(30000, 1001)));
+        assert!(timeline.drop_frame);
+        assert_eq!(timeline.global_start_time.as_ref().unwrap().value, 107892.0);
+
+        let names: Vec<&str> = timeline.tracks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["V1", "A1"]);
+
+        // The second clip only refers to the file the first one wrote out.
+        let reused = &timeline.tracks[0].clips[1];
+        assert_eq!(reused.media_ref.as_deref(), Some("file:///media/shot1.mov"));
+        assert_eq!(reused.reel.as_deref(), Some("R1"));
+        assert_eq!(reused.record_start.as_ref().unwrap().value, 90.0);
+        let source = reused.source_range.as_ref().unwrap();
+        assert_eq!((source.start.value, source.duration.value), (200.0, 30.0));
+
+        assert_eq!(timeline.tracks[1].kind, TrackKind::Audio);
+        assert_eq!(timeline.tracks[1].clips[0].media_ref.as_deref(), Some("file:///media/music.wav"));
+    }
 
-    match ext.as_str() {
-        "otio" => parse_otio_file(path),
-        "fcpxml" => parse_fcpxml_file(path),
-        _ => Err(format!("Unsupported timeline format: .{ext}")),
+    #[test]
+    fn edl_with_unreadable_timecode_is_an_error() {
+        let broken = EDL_25.replace("02:00:12:00", "02:00:1x:00");
+        let err = parse_edl(&broken).unwrap_err();
+        assert!(err.starts_with("EDL line 6:"), "{err}");
     }
 }
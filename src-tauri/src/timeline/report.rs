@@ -0,0 +1,414 @@
+//! Change lists between two versions of a timeline, the "what changed" notes
+//! sent to sound and VFX vendors after a recut.
+
+use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+use super::TrackKind;
+use super::diff::{ClipChange, ClipDiff, DiffStatus, TimelineDiff};
+use super::time::{self, ExactTime, FrameRate};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ReportFormat {
+    Csv,
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum TrackFilter {
+    #[default]
+    All,
+    Video,
+    Audio,
+}
+
+impl TrackFilter {
+    fn includes(&self, kind: &TrackKind) -> bool {
+        match self {
+            Self::All => true,
+            Self::Video => *kind == TrackKind::Video,
+            Self::Audio => *kind == TrackKind::Audio,
+        }
+    }
+}
+
+/// One line of a change list. Durations are in frames at the list's rate.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeListEntry {
+    pub event: usize,
+    pub track: String,
+    pub kind: TrackKind,
+    pub status: DiffStatus,
+    pub change: String,
+    pub clip_name: String,
+    pub media_ref: Option<String>,
+    pub reel: Option<String>,
+    pub record_in: Option<String>,
+    pub record_out: Option<String>,
+    pub old_duration: Option<i64>,
+    pub new_duration: Option<i64>,
+    pub duration_delta: i64,
+    #[serde(skip)]
+    position: ExactTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeList {
+    pub title: String,
+    pub from_label: String,
+    pub to_label: String,
+    pub frame_rate: Option<FrameRate>,
+    pub drop_frame: bool,
+    pub filter: TrackFilter,
+    pub entries: Vec<ChangeListEntry>,
+    pub affected_media: Vec<String>,
+    pub affected_reels: Vec<String>,
+}
+
+/// Collect every changed clip in `diff` on the tracks `filter` admits,
+/// ordered by record time.
+pub fn build_change_list(diff: &TimelineDiff, from_label: &str, to_label: &str, filter: TrackFilter) -> ChangeList {
+    let rate = diff.frame_rate;
+    let mut entries = Vec::new();
+
+    for track in diff.tracks.iter().filter(|t| filter.includes(&t.kind)) {
+        for clip in track.clips.iter().filter(|c| c.status != DiffStatus::Unchanged) {
+            entries.push(entry_for(clip, &track.name, &track.kind, rate));
+        }
+    }
+
+    entries.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.track.cmp(&b.track)));
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.event = i + 1;
+    }
+
+    let affected_media: BTreeSet<String> = entries.iter().filter_map(|e| e.media_ref.clone()).collect();
+    let affected_reels: BTreeSet<String> = entries.iter().filter_map(|e| e.reel.clone()).collect();
+
+    ChangeList {
+        title: if diff.old_name == diff.new_name {
+            diff.new_name.clone()
+        } else {
+            format!("{} → {}", diff.old_name, diff.new_name)
+        },
+        from_label: from_label.to_string(),
+        to_label: to_label.to_string(),
+        frame_rate: rate,
+        drop_frame: diff.drop_frame,
+        filter,
+        entries,
+        affected_media: affected_media.into_iter().collect(),
+        affected_reels: affected_reels.into_iter().collect(),
+    }
+}
+
+fn entry_for(clip: &ClipDiff, track: &str, kind: &TrackKind, rate: Option<FrameRate>) -> ChangeListEntry {
+    let frames = |t: ExactTime| rate.map(|r| t.to_frames(r));
+    let old_duration = clip.old_record_range.as_ref().and_then(|r| frames(r.exact_duration()));
+    let new_duration = clip.new_record_range.as_ref().and_then(|r| frames(r.exact_duration()));
+
+    let (record_range, record_in, record_out) = match clip.status {
+        DiffStatus::Removed => (
+            clip.old_record_range.as_ref(),
+            clip.old_record_timecode.clone(),
+            clip.old_record_out_timecode.clone(),
+        ),
+        _ => (
+            clip.new_record_range.as_ref(),
+            clip.new_record_timecode.clone(),
+            clip.new_record_out_timecode.clone(),
+        ),
+    };
+
+    ChangeListEntry {
+        event: 0,
+        track: track.to_string(),
+        kind: kind.clone(),
+        status: clip.status.clone(),
+        change: describe(clip),
+        clip_name: clip.name.clone(),
+        media_ref: clip.media_ref.clone(),
+        reel: clip.reel.clone(),
+        record_in,
+        record_out,
+        old_duration,
+        new_duration,
+        duration_delta: new_duration.unwrap_or(0) - old_duration.unwrap_or(0),
+        position: record_range.map(|r| r.exact_start()).unwrap_or(ExactTime::ZERO),
+    }
+}
+
+fn describe(clip: &ClipDiff) -> String {
+    match clip.status {
+        DiffStatus::Added => return "Added".to_string(),
+        DiffStatus::Removed => return "Removed".to_string(),
+        _ => {}
+    }
+    let parts: Vec<String> = clip.changes.iter().map(|c| match c {
+        ClipChange::TrimmedHead { frames } if *frames > 0 => format!("Head trimmed {frames} fr"),
+        ClipChange::TrimmedHead { frames } => format!("Head extended {} fr", -frames),
+        ClipChange::TrimmedTail { frames } if *frames > 0 => format!("Tail extended {frames} fr"),
+        ClipChange::TrimmedTail { frames } => format!("Tail trimmed {} fr", -frames),
+        ClipChange::Slipped { frames } => format!("Slipped {frames:+} fr"),
+        ClipChange::Moved { frames } => format!("Moved {frames:+} fr"),
+        ClipChange::Reordered => "Reordered".to_string(),
+        ClipChange::Replaced { old_name, .. } => format!("Replaced \"{old_name}\""),
+        ClipChange::MovedTrack { from_track, .. } => format!("Moved from track {}", from_track + 1),
+    }).collect();
+    if parts.is_empty() { "Modified".to_string() } else { parts.join("; ") }
+}
+
+pub fn render(list: &ChangeList, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Csv => render_csv(list),
+        ReportFormat::Markdown => render_markdown(list),
+        ReportFormat::Html => render_html(list),
+    }
+}
+
+const COLUMNS: [&str; 12] = [
+    "Event", "Track", "Type", "Change", "Clip", "Media", "Reel",
+    "Record In", "Record Out", "Old Duration", "New Duration", "Duration Delta",
+];
+
+fn row(list: &ChangeList, e: &ChangeListEntry) -> [String; 12] {
+    let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+    let dur = |v: Option<i64>| v.map(|f| f.to_string()).unwrap_or_default();
+    [
+        e.event.to_string(),
+        e.track.clone(),
+        format!("{:?}", e.kind),
+        e.change.clone(),
+        e.clip_name.clone(),
+        opt(&e.media_ref),
+        opt(&e.reel),
+        opt(&e.record_in),
+        opt(&e.record_out),
+        dur(e.old_duration),
+        dur(e.new_duration),
+        delta_label(list, e.duration_delta),
+    ]
+}
+
+fn delta_label(list: &ChangeList, frames: i64) -> String {
+    match list.frame_rate {
+        Some(rate) if frames != 0 => {
+            let sign = if frames > 0 { "+" } else { "-" };
+            format!("{frames:+} ({sign}{})", time::format_timecode(frames.abs(), rate, list.drop_frame))
+        }
+        _ => frames.to_string(),
+    }
+}
+
+fn render_csv(list: &ChangeList) -> String {
+    let mut out = String::new();
+    out.push_str(&COLUMNS.join(","));
+    out.push('\n');
+    for e in &list.entries {
+        let cells: Vec<String> = row(list, e).iter().map(|c| csv_escape(c)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_markdown(list: &ChangeList) -> String {
+    let mut out = format!("# Change list: {}\n\n", list.title);
+    out.push_str(&format!("**From:** {}  \n**To:** {}  \n", list.from_label, list.to_label));
+    out.push_str(&format!("**Tracks:** {:?}  \n**Changes:** {}\n\n", list.filter, list.entries.len()));
+
+    out.push_str(&format!("| {} |\n", COLUMNS.join(" | ")));
+    out.push_str(&format!("|{}\n", "---|".repeat(COLUMNS.len())));
+    for e in &list.entries {
+        let cells: Vec<String> = row(list, e).iter().map(|c| c.replace('|', "\\|")).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+
+    if !list.affected_reels.is_empty() {
+        out.push_str("\n## Affected reels\n\n");
+        for reel in &list.affected_reels {
+            out.push_str(&format!("- {reel}\n"));
+        }
+    }
+    if !list.affected_media.is_empty() {
+        out.push_str("\n## Affected media\n\n");
+        for media in &list.affected_media {
+            out.push_str(&format!("- {media}\n"));
+        }
+    }
+    out
+}
+
+fn render_html(list: &ChangeList) -> String {
+    let mut rows = String::new();
+    for e in &list.entries {
+        let class = match e.status {
+            DiffStatus::Added => "added",
+            DiffStatus::Removed => "removed",
+            _ => "modified",
+        };
+        rows.push_str(&format!("<tr class=\"{class}\">"));
+        for cell in row(list, e) {
+            rows.push_str(&format!("<td>{}</td>", html_escape(&cell)));
+        }
+        rows.push_str("</tr>\n");
+    }
+
+    let header: String = COLUMNS.iter().map(|c| format!("<th>{c}</th>")).collect();
+    let list_items = |items: &[String]| -> String {
+        items.iter().map(|i| format!("<li>{}</li>", html_escape(i))).collect()
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Change list: {title}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem; color: #1d1d1f; }}
+h1 {{ font-size: 1.4rem; margin-bottom: 0.25rem; }}
+.meta {{ color: #555; margin-bottom: 1.5rem; }}
+table {{ border-collapse: collapse; width: 100%; font-size: 0.85rem; }}
+th, td {{ border: 1px solid #ddd; padding: 4px 8px; text-align: left; vertical-align: top; }}
+th {{ background: #f2f2f2; }}
+td:nth-child(8), td:nth-child(9) {{ font-family: Menlo, Consolas, monospace; white-space: nowrap; }}
+tr.added td:nth-child(4) {{ color: #1a7f37; }}
+tr.removed td:nth-child(4) {{ color: #cf222e; }}
+tr.modified td:nth-child(4) {{ color: #9a6700; }}
+</style>
+</head>
+<body>
+<h1>Change list: {title}</h1>
+<div class="meta">From <strong>{from}</strong> to <strong>{to}</strong> &middot; Tracks: {filter:?} &middot; {count} changes</div>
+<table>
+<thead><tr>{header}</tr></thead>
+<tbody>
+{rows}</tbody>
+</table>
+<h2>Affected reels</h2>
+<ul>{reels}</ul>
+<h2>Affected media</h2>
+<ul>{media}</ul>
+</body>
+</html>
+"#,
+        title = html_escape(&list.title),
+        from = html_escape(&list.from_label),
+        to = html_escape(&list.to_label),
+        filter = list.filter,
+        count = list.entries.len(),
+        header = header,
+        rows = rows,
+        reels = list_items(&list.affected_reels),
+        media = list_items(&list.affected_media),
+    )
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeline::{diff, Clip, RationalTime, TimeRange, Timeline, Track};
+
+    fn clip(name: &str, media: &str, record: f64, duration: f64) -> Clip {
+        Clip {
+            name: name.to_string(),
+            media_ref: Some(media.to_string()),
+            source_range: Some(TimeRange {
+                start: RationalTime::new(0.0, 24.0),
+                duration: RationalTime::new(duration, 24.0),
+            }),
+            trimmed_range: None,
+            record_start: Some(RationalTime::new(record, 24.0)),
+            reel: Some("R1".to_string()),
+        }
+    }
+
+    fn timeline(video: Vec<Clip>, audio: Vec<Clip>) -> Timeline {
+        Timeline {
+            name: "Reel 1".to_string(),
+            tracks: vec![
+                Track { name: "V1".to_string(), kind: TrackKind::Video, clips: video },
+                Track { name: "A1".to_string(), kind: TrackKind::Audio, clips: audio },
+            ],
+            duration: None,
+            // 01:00:00:00
+            global_start_time: Some(RationalTime::new(86400.0, 24.0)),
+            drop_frame: false,
+        }
+    }
+
+    fn change_list(filter: TrackFilter) -> ChangeList {
+        let old = timeline(vec![clip("A", "a.mov", 0.0, 48.0), clip("Shot, \"B\"", "b.mov", 48.0, 24.0)], vec![]);
+        let new = timeline(vec![clip("A", "a.mov", 0.0, 36.0)], vec![clip("Music | <Stem> & co", "m.wav", 96.0, 24.0)]);
+        build_change_list(&diff::diff_timelines(&old, &new), "v1", "v2", filter)
+    }
+
+    #[test]
+    fn entries_carry_record_in_and_out() {
+        let list = change_list(TrackFilter::All);
+        let summary: Vec<_> = list.entries.iter()
+            .map(|e| (e.event, e.change.as_str(), e.record_in.as_deref(), e.record_out.as_deref(), e.duration_delta))
+            .collect();
+        assert_eq!(summary, vec![
+            (1, "Tail trimmed 12 fr", Some("01:00:00:00"), Some("01:00:01:12"), -12),
+            (2, "Removed", Some("01:00:02:00"), Some("01:00:03:00"), -24),
+            (3, "Added", Some("01:00:04:00"), Some("01:00:05:00"), 24),
+        ]);
+        assert_eq!(list.affected_media, vec!["a.mov", "b.mov", "m.wav"]);
+        assert_eq!(list.affected_reels, vec!["R1"]);
+    }
+
+    #[test]
+    fn track_filter_limits_entries() {
+        let audio = change_list(TrackFilter::Audio);
+        assert_eq!(audio.entries.len(), 1);
+        assert_eq!(audio.entries[0].event, 1);
+        assert_eq!(audio.entries[0].track, "A1");
+
+        let video = change_list(TrackFilter::Video);
+        assert!(video.entries.iter().all(|e| e.kind == TrackKind::Video));
+        assert_eq!(video.entries.len(), 2);
+    }
+
+    #[test]
+    fn csv_quotes_cells_with_commas_and_quotes() {
+        let csv = render(&change_list(TrackFilter::All), ReportFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], COLUMNS.join(","));
+        assert_eq!(
+            lines[2],
+            "2,V1,Video,Removed,\"Shot, \"\"B\"\"\",b.mov,R1,01:00:02:00,01:00:03:00,24,,-24 (-00:00:01:00)",
+        );
+    }
+
+    #[test]
+    fn markdown_and_html_escape_cells() {
+        let list = change_list(TrackFilter::All);
+
+        let markdown = render(&list, ReportFormat::Markdown);
+        assert!(markdown.contains("| Music \\| <Stem> & co |"), "{markdown}");
+        assert!(markdown.contains("## Affected reels\n\n- R1\n"));
+
+        let html = render(&list, ReportFormat::Html);
+        assert!(html.contains("<td>Music | &lt;Stem&gt; &amp; co</td>"), "{html}");
+        assert!(html.contains("<td>Shot, &quot;B&quot;</td>"));
+        assert!(html.contains("<tr class=\"removed\">"));
+    }
+}
@@ -0,0 +1,332 @@
+//! SubRip (`.srt`) and Advanced SubStation (`.ass`) subtitles, and a
+//! cue-level diff between two versions of a subtitle file.
+//!
+//! Cue times are held as `ExactTime` and reported as timecode at the
+//! project's frame rate, so a cue shift reads in the same frames as a clip
+//! move in the timeline diff.
+
+use serde::Serialize;
+use super::diff::{DiffStatus, DiffSummary};
+use super::time::{self, ExactTime, FrameRate};
+
+#[derive(Debug, Clone)]
+pub struct SubtitleCue {
+    pub start: ExactTime,
+    pub end: ExactTime,
+    pub text: String,
+    /// ASS style name; SubRip has none.
+    pub style: Option<String>,
+}
+
+/// What happened to a cue present in both versions. Frame deltas are at the
+/// diff's frame rate; positive means later.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum CueChange {
+    TextEdited { old_text: String },
+    /// In and out both moved by the same amount.
+    Shifted { frames: i64 },
+    /// The cue was retimed and its length changed.
+    Retimed { start_frames: i64, end_frames: i64 },
+    StyleChanged { old_style: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CueDiff {
+    pub status: DiffStatus,
+    pub text: String,
+    pub style: Option<String>,
+    /// 1-based cue numbers in each version, in time order.
+    pub old_number: Option<usize>,
+    pub new_number: Option<usize>,
+    pub old_start_timecode: Option<String>,
+    pub old_end_timecode: Option<String>,
+    pub new_start_timecode: Option<String>,
+    pub new_end_timecode: Option<String>,
+    pub changes: Vec<CueChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubtitleDiff {
+    pub frame_rate: FrameRate,
+    pub drop_frame: bool,
+    pub cues: Vec<CueDiff>,
+    pub summary: DiffSummary,
+}
+
+pub fn is_subtitle_extension(ext: &str) -> bool {
+    matches!(ext.to_lowercase().as_str(), "srt" | "ass")
+}
+
+/// Parse subtitle content whose format is given by its original file
+/// extension. Cues come back sorted by start time.
+pub fn parse_subtitles_str(content: &str, ext: &str) -> Result<Vec<SubtitleCue>, String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut cues = match ext.to_lowercase().as_str() {
+        "srt" => parse_srt(content)?,
+        "ass" => parse_ass(content)?,
+        other => return Err(format!("Unsupported subtitle format: .{other}")),
+    };
+    cues.sort_by_key(|c| (c.start, c.end));
+    Ok(cues)
+}
+
+/// SubRip: blank-line separated blocks of an optional counter, a
+/// `00:00:01,000 --> 00:00:02,500` timing line and the text.
+pub fn parse_srt(content: &str) -> Result<Vec<SubtitleCue>, String> {
+    let mut cues = Vec::new();
+    let mut lines = content.lines().map(|l| l.trim_end_matches('\r')).peekable();
+
+    while lines.peek().is_some() {
+        let block: Vec<&str> = lines.by_ref()
+            .skip_while(|l| l.trim().is_empty())
+            .take_while(|l| !l.trim().is_empty())
+            .collect();
+        let Some(timing_at) = block.iter().position(|l| l.contains("-->")) else {
+            continue;
+        };
+        let timing = block[timing_at];
+        let (start, rest) = timing.split_once("-->").unwrap_or((timing, ""));
+        // Anything after the end time is SubRip positioning, e.g. `X1:...`.
+        let end = rest.split_whitespace().next().unwrap_or("");
+        cues.push(SubtitleCue {
+            start: parse_srt_time(start)?,
+            end: parse_srt_time(end)?,
+            text: block[timing_at + 1..].join("\n"),
+            style: None,
+        });
+    }
+    Ok(cues)
+}
+
+/// Advanced SubStation: `Dialogue:` lines of the `[Events]` section, laid
+/// out by its `Format:` line. Override blocks such as `{\i1}` are dropped
+/// from the text and `\N` becomes a line break.
+pub fn parse_ass(content: &str) -> Result<Vec<SubtitleCue>, String> {
+    let mut cues = Vec::new();
+    let mut in_events = false;
+    let mut format: Vec<String> = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[events]");
+            continue;
+        }
+        if !in_events {
+            continue;
+        }
+        if let Some(fields) = line.strip_prefix("Format:") {
+            format = fields.split(',').map(|f| f.trim().to_lowercase()).collect();
+            continue;
+        }
+        let Some(fields) = line.strip_prefix("Dialogue:") else { continue };
+        if format.is_empty() {
+            return Err("ASS [Events] section has no Format line".to_string());
+        }
+
+        // Text is always last and may itself contain commas.
+        let values: Vec<&str> = fields.splitn(format.len(), ',').map(str::trim).collect();
+        let field = |name: &str| format.iter().position(|f| f == name).and_then(|i| values.get(i).copied());
+        let (Some(start), Some(end)) = (field("start"), field("end")) else {
+            return Err(format!("Malformed ASS dialogue line: {line}"));
+        };
+        cues.push(SubtitleCue {
+            start: parse_ass_time(start)?,
+            end: parse_ass_time(end)?,
+            text: ass_plain_text(field("text").unwrap_or("")),
+            style: field("style").filter(|s| !s.is_empty()).map(|s| s.to_string()),
+        });
+    }
+    Ok(cues)
+}
+
+/// `HH:MM:SS,mmm`; a `.` before the milliseconds is accepted too.
+fn parse_srt_time(value: &str) -> Result<ExactTime, String> {
+    let value = value.trim();
+    let (hms, millis) = value.split_once([',', '.']).unwrap_or((value, "0"));
+    let seconds = parse_hms(hms).ok_or_else(|| format!("Invalid SubRip time: {value}"))?;
+    let millis: i64 = millis.parse().map_err(|_| format!("Invalid SubRip time: {value}"))?;
+    Ok(ExactTime::new(seconds * 1000 + millis, 1000))
+}
+
+/// `H:MM:SS.cc`, in centiseconds.
+fn parse_ass_time(value: &str) -> Result<ExactTime, String> {
+    let (hms, centis) = value.split_once('.').unwrap_or((value, "0"));
+    let seconds = parse_hms(hms).ok_or_else(|| format!("Invalid ASS time: {value}"))?;
+    let centis: i64 = centis.parse().map_err(|_| format!("Invalid ASS time: {value}"))?;
+    Ok(ExactTime::new(seconds * 100 + centis, 100))
+}
+
+fn parse_hms(value: &str) -> Option<i64> {
+    let parts: Vec<i64> = value.split(':').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+    let [hh, mm, ss] = parts[..] else { return None };
+    Some((hh * 60 + mm) * 60 + ss)
+}
+
+fn ass_plain_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0;
+    for c in text.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+    out.replace("\\N", "\n").replace("\\n", "\n").replace("\\h", "\u{a0}")
+}
+
+/// Diff two cue lists. Cues with the same text are paired in order first,
+/// preferring the closest in time where a line repeats. Between those
+/// anchors, cues that overlap once the preceding anchor's shift is applied
+/// are taken as the same cue with edited text; the rest are added or
+/// removed.
+pub fn diff_subtitles(old: &[SubtitleCue], new: &[SubtitleCue], rate: FrameRate, drop_frame: bool) -> SubtitleDiff {
+    let anchors = align_by_text(old, new);
+    let mut cues = Vec::new();
+    let mut summary = DiffSummary { added: 0, removed: 0, modified: 0, unchanged: 0 };
+    let tc = |t: ExactTime| time::format_timecode(t.to_frames(rate), rate, drop_frame);
+
+    let push_pair = |oi: usize, ni: usize, cues: &mut Vec<CueDiff>, summary: &mut DiffSummary| {
+        let (a, b) = (&old[oi], &new[ni]);
+        let changes = cue_changes(a, b, rate);
+        let status = if changes.is_empty() {
+            summary.unchanged += 1;
+            DiffStatus::Unchanged
+        } else {
+            summary.modified += 1;
+            DiffStatus::Modified
+        };
+        cues.push(CueDiff {
+            status,
+            text: b.text.clone(),
+            style: b.style.clone(),
+            old_number: Some(oi + 1),
+            new_number: Some(ni + 1),
+            old_start_timecode: Some(tc(a.start)),
+            old_end_timecode: Some(tc(a.end)),
+            new_start_timecode: Some(tc(b.start)),
+            new_end_timecode: Some(tc(b.end)),
+            changes,
+        });
+    };
+
+    let push_added = |from: usize, to: usize, cues: &mut Vec<CueDiff>, summary: &mut DiffSummary| {
+        for (i, cue) in new.iter().enumerate().take(to).skip(from) {
+            cues.push(unpaired(cue, i, DiffStatus::Added, &tc));
+            summary.added += 1;
+        }
+    };
+
+    let (mut oi, mut ni) = (0, 0);
+    let mut offset = ExactTime::ZERO;
+    for &(ao, an) in anchors.iter().chain(std::iter::once(&(old.len(), new.len()))) {
+        // Pair edited cues inside the hunk in order, by shifted overlap.
+        let mut nj = ni;
+        for (o, cue) in old.iter().enumerate().take(ao).skip(oi) {
+            let (start, end) = (cue.start + offset, cue.end + offset);
+            match (nj..an).find(|&n| new[n].start < end && start < new[n].end) {
+                Some(n) => {
+                    push_added(nj, n, &mut cues, &mut summary);
+                    push_pair(o, n, &mut cues, &mut summary);
+                    nj = n + 1;
+                }
+                None => {
+                    cues.push(unpaired(cue, o, DiffStatus::Removed, &tc));
+                    summary.removed += 1;
+                }
+            }
+        }
+        push_added(nj, an, &mut cues, &mut summary);
+
+        if ao < old.len() {
+            push_pair(ao, an, &mut cues, &mut summary);
+            offset = new[an].start - old[ao].start;
+        }
+        (oi, ni) = (ao + 1, an + 1);
+    }
+
+    SubtitleDiff { frame_rate: rate, drop_frame, cues, summary }
+}
+
+fn unpaired(cue: &SubtitleCue, index: usize, status: DiffStatus, tc: &impl Fn(ExactTime) -> String) -> CueDiff {
+    let removed = status == DiffStatus::Removed;
+    let (number, start, end) = (Some(index + 1), Some(tc(cue.start)), Some(tc(cue.end)));
+    CueDiff {
+        text: cue.text.clone(),
+        style: cue.style.clone(),
+        old_number: if removed { number } else { None },
+        new_number: if removed { None } else { number },
+        old_start_timecode: if removed { start.clone() } else { None },
+        old_end_timecode: if removed { end.clone() } else { None },
+        new_start_timecode: if removed { None } else { start },
+        new_end_timecode: if removed { None } else { end },
+        changes: Vec::new(),
+        status,
+    }
+}
+
+fn cue_changes(old: &SubtitleCue, new: &SubtitleCue, rate: FrameRate) -> Vec<CueChange> {
+    let mut changes = Vec::new();
+    if normalized_text(&old.text) != normalized_text(&new.text) {
+        changes.push(CueChange::TextEdited { old_text: old.text.clone() });
+    }
+    let start_frames = (new.start - old.start).to_frames(rate);
+    let end_frames = (new.end - old.end).to_frames(rate);
+    if start_frames == end_frames {
+        if start_frames != 0 {
+            changes.push(CueChange::Shifted { frames: start_frames });
+        }
+    } else {
+        changes.push(CueChange::Retimed { start_frames, end_frames });
+    }
+    if old.style != new.style {
+        changes.push(CueChange::StyleChanged { old_style: old.style.clone() });
+    }
+    changes
+}
+
+/// Weighted LCS over cue text, as `align_in_order` does for clips: the
+/// number of matches is maximised first, and among equal alignments the
+/// pairs closest in time win.
+fn align_by_text(old: &[SubtitleCue], new: &[SubtitleCue]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let old_text: Vec<String> = old.iter().map(|c| normalized_text(&c.text)).collect();
+    let new_text: Vec<String> = new.iter().map(|c| normalized_text(&c.text)).collect();
+    let match_weight = (n.min(m) + 1) as f64;
+    let score = |i: usize, j: usize| match_weight + 1.0 / (1.0 + (new[j].start - old[i].start).abs().seconds());
+
+    let mut dp = vec![vec![0.0f64; m + 1]; n + 1];
+    for i in 1..=n {
+        for j in 1..=m {
+            let mut best = dp[i - 1][j].max(dp[i][j - 1]);
+            if old_text[i - 1] == new_text[j - 1] {
+                best = best.max(dp[i - 1][j - 1] + score(i - 1, j - 1));
+            }
+            dp[i][j] = best;
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 && j > 0 {
+        if old_text[i - 1] == new_text[j - 1] && dp[i][j] == dp[i - 1][j - 1] + score(i - 1, j - 1) {
+            pairs.push((i - 1, j - 1));
+            i -= 1;
+            j -= 1;
+        } else if dp[i - 1][j] >= dp[i][j - 1] {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+/// Text as compared between versions: line breaks and runs of spaces do not
+/// count as edits.
+fn normalized_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
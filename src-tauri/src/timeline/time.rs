@@ -0,0 +1,305 @@
+//! Exact time arithmetic and SMPTE timecode.
+//!
+//! `RationalTime` keeps OTIO's floating-point `value`/`rate` pair for
+//! serialization. Comparisons and arithmetic go through `ExactTime`, which
+//! holds seconds as a reduced fraction so 23.976 and 29.97 material lines up
+//! frame for frame.
+
+use std::cmp::Ordering;
+use std::ops::{Add, Neg, Sub};
+use serde::{Deserialize, Serialize};
+use super::{RationalTime, TimeRange};
+
+/// Frames per second as a fraction, e.g. 24000/1001 for 23.976.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FrameRate {
+    pub num: i64,
+    pub den: i64,
+}
+
+impl FrameRate {
+    pub fn new(num: i64, den: i64) -> Self {
+        let g = gcd(num, den).max(1);
+        Self { num: num / g, den: den / g }
+    }
+
+    /// Snap a floating-point rate to the rate it stands for: 23.976 becomes
+    /// 24000/1001, 25.0 becomes 25/1. Other rates keep three decimals.
+    pub fn from_f64(rate: f64) -> Option<Self> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return None;
+        }
+        let nominal = rate.round();
+        if (rate - nominal).abs() < 1e-6 {
+            return Some(Self::new(nominal as i64, 1));
+        }
+        let ntsc = rate.ceil();
+        if (rate - ntsc * 1000.0 / 1001.0).abs() < 0.005 {
+            return Some(Self::new(ntsc as i64 * 1000, 1001));
+        }
+        Some(Self::new((rate * 1000.0).round() as i64, 1000))
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// The frame count per timecode second, e.g. 30 for 29.97.
+    pub fn timecode_fps(&self) -> i64 {
+        (self.num + self.den - 1) / self.den
+    }
+
+    /// Drop-frame timecode only exists for the NTSC multiples of 30.
+    pub fn supports_drop_frame(&self) -> bool {
+        self.den == 1001 && self.timecode_fps() % 30 == 0
+    }
+}
+
+/// A point in (or length of) time, in seconds, held as a reduced fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExactTime {
+    num: i64,
+    den: i64,
+}
+
+impl ExactTime {
+    pub const ZERO: ExactTime = ExactTime { num: 0, den: 1 };
+
+    pub fn new(num: i64, den: i64) -> Self {
+        Self::reduce(num as i128, den as i128)
+    }
+
+    fn reduce(num: i128, den: i128) -> Self {
+        if den == 0 {
+            return Self::ZERO;
+        }
+        let sign = if den < 0 { -1 } else { 1 };
+        let g = gcd128(num, den).max(1);
+        Self {
+            num: (sign * num / g) as i64,
+            den: (sign * den / g) as i64,
+        }
+    }
+
+    pub fn from_frames(frames: i64, rate: FrameRate) -> Self {
+        Self::reduce(frames as i128 * rate.den as i128, rate.num as i128)
+    }
+
+    /// Fractional frame values keep millisecond-of-a-frame precision.
+    /// A time without a usable rate is treated as zero.
+    pub fn from_rational_time(time: &RationalTime) -> Self {
+        let Some(rate) = FrameRate::from_f64(time.rate) else {
+            return Self::ZERO;
+        };
+        if time.value.fract() == 0.0 {
+            return Self::from_frames(time.value as i64, rate);
+        }
+        let milli_frames = (time.value * 1000.0).round() as i128;
+        Self::reduce(milli_frames * rate.den as i128, rate.num as i128 * 1000)
+    }
+
+    /// Nearest whole frame at `rate`, rounding halves away from zero.
+    pub fn to_frames(&self, rate: FrameRate) -> i64 {
+        let num = self.num as i128 * rate.num as i128;
+        let den = self.den as i128 * rate.den as i128;
+        let rounded = if num >= 0 { (2 * num + den) / (2 * den) } else { -((-2 * num + den) / (2 * den)) };
+        rounded as i64
+    }
+
+    pub fn rescaled_to(&self, rate: FrameRate) -> RationalTime {
+        let frames = self.num as f64 * rate.num as f64 / (self.den as f64 * rate.den as f64);
+        RationalTime::new(frames, rate.as_f64())
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    pub fn abs(&self) -> Self {
+        Self { num: self.num.abs(), den: self.den }
+    }
+}
+
+impl From<&RationalTime> for ExactTime {
+    fn from(time: &RationalTime) -> Self {
+        Self::from_rational_time(time)
+    }
+}
+
+impl Add for ExactTime {
+    type Output = ExactTime;
+    fn add(self, rhs: ExactTime) -> ExactTime {
+        Self::reduce(
+            self.num as i128 * rhs.den as i128 + rhs.num as i128 * self.den as i128,
+            self.den as i128 * rhs.den as i128,
+        )
+    }
+}
+
+impl Sub for ExactTime {
+    type Output = ExactTime;
+    fn sub(self, rhs: ExactTime) -> ExactTime {
+        self + (-rhs)
+    }
+}
+
+impl Neg for ExactTime {
+    type Output = ExactTime;
+    fn neg(self) -> ExactTime {
+        Self { num: -self.num, den: self.den }
+    }
+}
+
+impl Ord for ExactTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+
+impl PartialOrd for ExactTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TimeRange {
+    pub fn exact_start(&self) -> ExactTime {
+        ExactTime::from(&self.start)
+    }
+
+    pub fn exact_duration(&self) -> ExactTime {
+        ExactTime::from(&self.duration)
+    }
+
+    pub fn exact_end(&self) -> ExactTime {
+        self.exact_start() + self.exact_duration()
+    }
+}
+
+/// Format a frame count as SMPTE timecode. Drop-frame timecode uses `;`
+/// before the frames field and is ignored for rates that have no drop-frame
+/// form. Negative counts get a leading `-`.
+pub fn format_timecode(frames: i64, rate: FrameRate, drop_frame: bool) -> String {
+    let sign = if frames < 0 { "-" } else { "" };
+    let mut frames = frames.abs();
+    let fps = rate.timecode_fps().max(1);
+    let drop = drop_frame && rate.supports_drop_frame();
+
+    if drop {
+        let dropped = fps / 15;
+        let per_minute = fps * 60 - dropped;
+        let per_ten_minutes = per_minute * 10 + dropped;
+        let tens = frames / per_ten_minutes;
+        let rem = frames % per_ten_minutes;
+        frames += dropped * 9 * tens;
+        if rem > dropped {
+            frames += dropped * ((rem - dropped) / per_minute);
+        }
+    }
+
+    let ff = frames % fps;
+    let ss = (frames / fps) % 60;
+    let mm = (frames / (fps * 60)) % 60;
+    let hh = frames / (fps * 3600);
+    let sep = if drop { ';' } else { ':' };
+    format!("{sign}{hh:02}:{mm:02}:{ss:02}{sep}{ff:02}")
+}
+
+/// Parse `HH:MM:SS:FF` into a frame count. A `;` or `,` before the frames
+/// field marks drop-frame timecode.
+pub fn parse_timecode(tc: &str, rate: FrameRate) -> Result<i64, String> {
+    let drop = tc.contains([';', ',']);
+    parse_timecode_with(tc, rate, drop)
+}
+
+pub fn parse_timecode_with(tc: &str, rate: FrameRate, drop_frame: bool) -> Result<i64, String> {
+    let trimmed = tc.trim();
+    let (negative, body) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    let parts: Vec<i64> = body.split([':', ';', ',', '.'])
+        .map(|p| p.parse::<i64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid timecode: {tc}"))?;
+    let [hh, mm, ss, ff] = parts[..] else {
+        return Err(format!("Invalid timecode: {tc}"));
+    };
+
+    let fps = rate.timecode_fps().max(1);
+    if mm >= 60 || ss >= 60 || ff >= fps {
+        return Err(format!("Timecode out of range for {} fps: {tc}", rate.as_f64()));
+    }
+
+    let total_minutes = hh * 60 + mm;
+    let mut frames = (total_minutes * 60 + ss) * fps + ff;
+    if drop_frame && rate.supports_drop_frame() {
+        frames -= (fps / 15) * (total_minutes - total_minutes / 10);
+    }
+    Ok(if negative { -frames } else { frames })
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    gcd128(a as i128, b as i128) as i64
+}
+
+fn gcd128(mut a: i128, mut b: i128) -> i128 {
+    a = a.abs();
+    b = b.abs();
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(frames: i64, rate: FrameRate, expected: &str) {
+        let tc = format_timecode(frames, rate, true);
+        assert_eq!(tc, expected, "frame {frames}");
+        assert_eq!(parse_timecode(&tc, rate), Ok(frames), "{tc}");
+    }
+
+    #[test]
+    fn drop_frame_29_97_round_trips_across_minutes() {
+        let rate = FrameRate::new(30000, 1001);
+        assert_round_trip(1799, rate, "00:00:59;29");
+        assert_round_trip(1800, rate, "00:01:00;02");
+        assert_round_trip(3597, rate, "00:01:59;29");
+        assert_round_trip(3598, rate, "00:02:00;02");
+        assert_round_trip(17981, rate, "00:09:59;29");
+        assert_round_trip(17982, rate, "00:10:00;00");
+        assert_round_trip(19781, rate, "00:10:59;29");
+        assert_round_trip(19782, rate, "00:11:00;02");
+        for frames in 17_900..20_000 {
+            let tc = format_timecode(frames, rate, true);
+            assert_eq!(parse_timecode(&tc, rate), Ok(frames), "{tc}");
+        }
+    }
+
+    #[test]
+    fn drop_frame_59_94_round_trips_across_minutes() {
+        let rate = FrameRate::new(60000, 1001);
+        assert_round_trip(3599, rate, "00:00:59;59");
+        assert_round_trip(3600, rate, "00:01:00;04");
+        assert_round_trip(35963, rate, "00:09:59;59");
+        assert_round_trip(35964, rate, "00:10:00;00");
+        assert_round_trip(39563, rate, "00:10:59;59");
+        assert_round_trip(39564, rate, "00:11:00;04");
+        for frames in 35_800..40_000 {
+            let tc = format_timecode(frames, rate, true);
+            assert_eq!(parse_timecode(&tc, rate), Ok(frames), "{tc}");
+        }
+    }
+
+    #[test]
+    fn from_f64_snaps_ntsc_rates() {
+        assert_eq!(FrameRate::from_f64(23.976), Some(FrameRate::new(24000, 1001)));
+        assert_eq!(FrameRate::from_f64(24000.0 / 1001.0), Some(FrameRate::new(24000, 1001)));
+        assert_eq!(FrameRate::from_f64(23.98), Some(FrameRate::new(24000, 1001)));
+        assert_eq!(FrameRate::from_f64(24.0), Some(FrameRate::new(24, 1)));
+        assert_eq!(FrameRate::from_f64(0.0), None);
+    }
+}
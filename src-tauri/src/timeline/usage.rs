@@ -0,0 +1,126 @@
+//! Which media files the timelines of a version actually use, and which are
+//! orphaned and can be archived.
+
+use std::collections::BTreeMap;
+use serde::Serialize;
+use super::Timeline;
+use super::time::ExactTime;
+
+/// A media file known to the project, from the version, the folder or both.
+#[derive(Debug, Clone)]
+pub struct MediaFile {
+    pub path: String,
+    pub file_type: String,
+    pub size: i64,
+    pub in_version: bool,
+    pub on_disk: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaUsage {
+    pub path: String,
+    pub file_type: String,
+    pub size: i64,
+    pub in_version: bool,
+    pub on_disk: bool,
+    /// Number of clips that reference the file, across all timelines.
+    pub use_count: usize,
+    /// Summed duration of those clips, in seconds.
+    pub used_duration: f64,
+    pub timelines: Vec<String>,
+}
+
+/// A media reference that matches no file in the version or the folder.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnresolvedMedia {
+    pub media_ref: String,
+    pub use_count: usize,
+    pub timelines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaUsageReport {
+    pub used: Vec<MediaUsage>,
+    pub unused: Vec<MediaUsage>,
+    pub unresolved: Vec<UnresolvedMedia>,
+    pub used_bytes: i64,
+    pub unused_bytes: i64,
+    /// Summed duration of every clip with resolved media, in seconds.
+    pub total_used_duration: f64,
+}
+
+/// Cross-reference the clips of `timelines` (each tagged with the path of
+/// the file it came from) against `files`. `resolve` maps a timeline path
+/// and a clip's media reference to an index into `files`.
+pub fn media_usage(
+    timelines: &[(String, Timeline)],
+    files: &[MediaFile],
+    resolve: impl Fn(&str, &str) -> Option<usize>,
+) -> MediaUsageReport {
+    let mut counts = vec![0usize; files.len()];
+    let mut durations = vec![ExactTime::ZERO; files.len()];
+    let mut used_in: Vec<Vec<String>> = vec![Vec::new(); files.len()];
+    let mut unresolved: BTreeMap<String, (usize, Vec<String>)> = BTreeMap::new();
+    let mut total = ExactTime::ZERO;
+
+    for (path, timeline) in timelines {
+        let label = format!("{} ({path})", timeline.name);
+        for clip in timeline.tracks.iter().flat_map(|t| &t.clips) {
+            let Some(media_ref) = &clip.media_ref else { continue };
+            let duration = clip.source_range.as_ref()
+                .map(|r| r.exact_duration())
+                .unwrap_or(ExactTime::ZERO);
+
+            match resolve(path, media_ref) {
+                Some(i) => {
+                    counts[i] += 1;
+                    durations[i] = durations[i] + duration;
+                    total = total + duration;
+                    if !used_in[i].contains(&label) {
+                        used_in[i].push(label.clone());
+                    }
+                }
+                None => {
+                    let entry = unresolved.entry(media_ref.clone()).or_default();
+                    entry.0 += 1;
+                    if !entry.1.contains(&label) {
+                        entry.1.push(label.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut used = Vec::new();
+    let mut unused = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        let usage = MediaUsage {
+            path: file.path.clone(),
+            file_type: file.file_type.clone(),
+            size: file.size,
+            in_version: file.in_version,
+            on_disk: file.on_disk,
+            use_count: counts[i],
+            used_duration: durations[i].seconds(),
+            timelines: std::mem::take(&mut used_in[i]),
+        };
+        if usage.use_count > 0 {
+            used.push(usage);
+        } else {
+            unused.push(usage);
+        }
+    }
+    used.sort_by(|a, b| b.use_count.cmp(&a.use_count).then_with(|| a.path.cmp(&b.path)));
+    unused.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+    MediaUsageReport {
+        used_bytes: used.iter().map(|u| u.size).sum(),
+        unused_bytes: unused.iter().map(|u| u.size).sum(),
+        total_used_duration: total.seconds(),
+        used,
+        unused,
+        unresolved: unresolved.into_iter()
+            .map(|(media_ref, (use_count, timelines))| UnresolvedMedia { media_ref, use_count, timelines })
+            .collect(),
+    }
+}
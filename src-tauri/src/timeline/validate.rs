@@ -0,0 +1,296 @@
+//! Pre-delivery checks over a timeline: overlapping clips, short gaps,
+//! flash frames, frame-rate mismatches and media that cannot be found.
+
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use super::{diff, RationalTime, Timeline};
+use super::time::{ExactTime, FrameRate};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LintKind {
+    Overlap,
+    Gap,
+    FlashFrame,
+    MixedFrameRate,
+    /// The timeline has no frame rate, so frame-based checks were skipped.
+    NoFrameRate,
+    /// The media file was not committed alongside the timeline.
+    MediaNotInVersion,
+    /// The media file is not on disk.
+    MediaOffline,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    pub severity: Severity,
+    pub kind: LintKind,
+    pub track: Option<String>,
+    pub clip: Option<String>,
+    pub record_start: Option<RationalTime>,
+    pub timecode: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintOptions {
+    /// Clips shorter than this many frames are flash frames.
+    pub flash_frame_threshold: i64,
+    /// Gaps up to this many frames are reported; longer gaps are taken as
+    /// deliberate.
+    pub max_gap_frames: i64,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self { flash_frame_threshold: 3, max_gap_frames: 12 }
+    }
+}
+
+/// Structural checks that need nothing but the timeline itself.
+pub fn validate_timeline(timeline: &Timeline, options: &LintOptions) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let rate = timeline.frame_rate();
+
+    if rate.is_none() && timeline.tracks.iter().any(|t| !t.clips.is_empty()) {
+        issues.push(LintIssue {
+            severity: Severity::Warning,
+            kind: LintKind::NoFrameRate,
+            track: None,
+            clip: None,
+            record_start: None,
+            timecode: None,
+            message: "Timeline has no usable frame rate".to_string(),
+        });
+    }
+
+    for track in &timeline.tracks {
+        let mut placed: Vec<(usize, ExactTime, ExactTime)> = track.record_ranges().iter()
+            .enumerate()
+            .map(|(i, r)| (i, r.exact_start(), r.exact_end()))
+            .collect();
+        placed.sort_by_key(|&(_, start, _)| start);
+
+        let issue = |severity, kind, ci: usize, position: ExactTime, message: String| {
+            let record_start = rate.map(|r| position.rescaled_to(r));
+            LintIssue {
+                severity,
+                kind,
+                track: Some(track.name.clone()),
+                clip: Some(track.clips[ci].name.clone()),
+                timecode: record_start.as_ref().and_then(|p| diff::record_timecode(p, timeline)),
+                record_start,
+                message,
+            }
+        };
+
+        // Compare each clip with the furthest-reaching clip before it, so a
+        // long clip that spans several later ones is checked against all.
+        let mut reach: Option<(usize, ExactTime)> = None;
+        for &(ci, start, end) in &placed {
+            let Some((pi, prev_end)) = reach else {
+                reach = Some((ci, end));
+                continue;
+            };
+            if end > prev_end {
+                reach = Some((ci, end));
+            }
+            if start < prev_end {
+                let frames = rate.map(|r| (prev_end - start).to_frames(r)).unwrap_or(0);
+                issues.push(issue(
+                    Severity::Error,
+                    LintKind::Overlap,
+                    ci,
+                    start,
+                    format!("\"{}\" overlaps \"{}\" by {frames} frames", track.clips[ci].name, track.clips[pi].name),
+                ));
+            } else if let Some(r) = rate {
+                let frames = (start - prev_end).to_frames(r);
+                if frames > 0 && frames <= options.max_gap_frames {
+                    issues.push(issue(
+                        Severity::Warning,
+                        LintKind::Gap,
+                        ci,
+                        prev_end,
+                        format!("{frames}-frame gap before \"{}\"", track.clips[ci].name),
+                    ));
+                }
+            }
+        }
+
+        for &(ci, start, end) in &placed {
+            let clip = &track.clips[ci];
+            if let Some(r) = rate {
+                let frames = (end - start).to_frames(r);
+                if frames < options.flash_frame_threshold {
+                    issues.push(issue(
+                        Severity::Warning,
+                        LintKind::FlashFrame,
+                        ci,
+                        start,
+                        format!("\"{}\" is only {frames} frames long", clip.name),
+                    ));
+                }
+            }
+
+            let Some(timeline_rate) = rate else { continue };
+            let record_rate = clip.record_start.as_ref().and_then(|t| FrameRate::from_f64(t.rate));
+            let source_rate = clip.source_range.as_ref().and_then(|r| FrameRate::from_f64(r.duration.rate));
+            if let Some(placed_at) = record_rate.filter(|r| *r != timeline_rate) {
+                issues.push(issue(
+                    Severity::Error,
+                    LintKind::MixedFrameRate,
+                    ci,
+                    start,
+                    format!("\"{}\" is placed at {} fps on a {} fps timeline",
+                        clip.name, fps_label(placed_at), fps_label(timeline_rate)),
+                ));
+            } else if let Some(source) = source_rate.filter(|r| *r != timeline_rate) {
+                issues.push(issue(
+                    Severity::Warning,
+                    LintKind::MixedFrameRate,
+                    ci,
+                    start,
+                    format!("\"{}\" is {} fps media on a {} fps timeline",
+                        clip.name, fps_label(source), fps_label(timeline_rate)),
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Flag clips whose media is missing from the version (`in_version`) or
+/// from disk (`on_disk`). Both lookups receive the clip's `media_ref`.
+/// Clips without a media reference, such as titles and generators, are
+/// skipped.
+pub fn check_media(
+    timeline: &Timeline,
+    in_version: impl Fn(&str) -> bool,
+    on_disk: impl Fn(&str) -> bool,
+) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    for track in &timeline.tracks {
+        for (clip, record) in track.clips.iter().zip(track.record_ranges()) {
+            let Some(media_ref) = &clip.media_ref else { continue };
+            let (severity, kind, message) = match (in_version(media_ref), on_disk(media_ref)) {
+                (true, true) => continue,
+                (false, false) => (
+                    Severity::Error,
+                    LintKind::MediaOffline,
+                    format!("Media for \"{}\" is neither in this version nor on disk: {media_ref}", clip.name),
+                ),
+                (false, true) => (
+                    Severity::Warning,
+                    LintKind::MediaNotInVersion,
+                    format!("Media for \"{}\" is not part of this version: {media_ref}", clip.name),
+                ),
+                (true, false) => (
+                    Severity::Warning,
+                    LintKind::MediaOffline,
+                    format!("Media for \"{}\" is offline but can be restored from this version: {media_ref}", clip.name),
+                ),
+            };
+            issues.push(LintIssue {
+                severity,
+                kind,
+                track: Some(track.name.clone()),
+                clip: Some(clip.name.clone()),
+                timecode: diff::record_timecode(&record.start, timeline),
+                record_start: Some(record.start),
+                message,
+            });
+        }
+    }
+    issues
+}
+
+/// The filesystem path a media reference points at: `file://` URLs are
+/// unwrapped and percent-decoded, anything else is taken as a path.
+pub fn media_path(media_ref: &str) -> PathBuf {
+    let Some(rest) = media_ref.strip_prefix("file://") else {
+        return PathBuf::from(media_ref);
+    };
+    // file://localhost/path and file:///path both name /path.
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    PathBuf::from(percent_decode(rest))
+}
+
+/// Index of the project file (relative to `project_root`) that a clip's
+/// media reference names. Relative references resolve against the timeline's
+/// folder. Absolute ones outside the project still match by trailing path,
+/// which covers media linked from another machine's mount point; the longest
+/// such match wins.
+pub fn match_media_file(media_ref: &str, files: &[PathBuf], project_root: &Path, timeline_dir: &Path) -> Option<usize> {
+    let media = media_path(media_ref);
+    if media.is_absolute() {
+        if let Ok(rel) = media.strip_prefix(project_root) {
+            return files.iter().position(|f| f == rel);
+        }
+        files.iter()
+            .enumerate()
+            .filter(|(_, f)| media.ends_with(f))
+            .max_by_key(|(_, f)| f.components().count())
+            .map(|(i, _)| i)
+    } else {
+        let rel = normalize(&timeline_dir.join(&media));
+        let media = normalize(&media);
+        files.iter().position(|f| *f == rel).or_else(|| files.iter().position(|f| *f == media))
+    }
+}
+
+/// Whether a clip's media reference points at a file that exists.
+pub fn media_on_disk(media_ref: &str, project_root: &Path, timeline_dir: &Path) -> bool {
+    let media = media_path(media_ref);
+    if media.is_absolute() {
+        media.exists()
+    } else {
+        project_root.join(timeline_dir).join(&media).exists() || project_root.join(&media).exists()
+    }
+}
+
+/// Resolve `.` and `..` lexically; `..` past the start is dropped.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn fps_label(rate: FrameRate) -> String {
+    let fps = rate.as_f64();
+    if rate.den == 1 { format!("{fps}") } else { format!("{fps:.3}") }
+}
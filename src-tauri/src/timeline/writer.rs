@@ -0,0 +1,191 @@
+use std::path::Path;
+use serde_json::{json, Value};
+use super::{Timeline, Track, TrackKind, Clip, TimeRange, RationalTime};
+
+/// Serialize a Timeline as OTIO JSON. The output reads back through
+/// `parser::parse_otio_json` into an equivalent Timeline, and gaps between
+/// clips are written as `Gap` items so record positions survive the trip.
+pub fn to_otio_json(timeline: &Timeline) -> Result<String, String> {
+    serde_json::to_string_pretty(&timeline_value(timeline))
+        .map_err(|e| format!("Failed to serialize OTIO: {e}"))
+}
+
+pub fn write_otio_file(timeline: &Timeline, path: &Path) -> Result<(), String> {
+    let json = to_otio_json(timeline)?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write file: {e}"))
+}
+
+pub fn timeline_value(timeline: &Timeline) -> Value {
+    json!({
+        "OTIO_SCHEMA": "Timeline.1",
+        "metadata": if timeline.drop_frame {
+            json!({ "turnaround": { "drop_frame": true } })
+        } else {
+            json!({})
+        },
+        "name": timeline.name,
+        "global_start_time": timeline.global_start_time.as_ref().map(rational_time_value),
+        "tracks": {
+            "OTIO_SCHEMA": "Stack.1",
+            "metadata": {},
+            "name": "tracks",
+            "source_range": null,
+            "effects": [],
+            "markers": [],
+            "children": timeline.tracks.iter().map(track_value).collect::<Vec<_>>(),
+        },
+    })
+}
+
+fn track_value(track: &Track) -> Value {
+    let mut children = Vec::new();
+    let mut position: Option<RationalTime> = None;
+
+    for clip in &track.clips {
+        if let Some(record) = &clip.record_start {
+            let pos = position.clone().unwrap_or_else(|| RationalTime::new(0.0, record.rate));
+            let gap = record.value - pos.rescaled_to(record.rate).value;
+            if gap > 0.0 {
+                children.push(gap_value(RationalTime::new(gap, record.rate)));
+            }
+            position = Some(record.clone());
+        }
+
+        if let (Some(pos), Some(range)) = (&position, &clip.source_range) {
+            let d = range.duration.rescaled_to(pos.rate);
+            position = Some(RationalTime::new(pos.value + d.value, pos.rate));
+        }
+
+        children.push(clip_value(clip));
+    }
+
+    json!({
+        "OTIO_SCHEMA": "Track.1",
+        "metadata": {},
+        "name": track.name,
+        "kind": match track.kind {
+            TrackKind::Video => "Video",
+            TrackKind::Audio => "Audio",
+        },
+        "source_range": null,
+        "effects": [],
+        "markers": [],
+        "children": children,
+    })
+}
+
+fn clip_value(clip: &Clip) -> Value {
+    let media_reference = match &clip.media_ref {
+        Some(url) => json!({
+            "OTIO_SCHEMA": "ExternalReference.1",
+            "metadata": {},
+            "name": "",
+            "available_range": null,
+            "target_url": url,
+        }),
+        None => json!({
+            "OTIO_SCHEMA": "MissingReference.1",
+            "metadata": {},
+            "name": "",
+            "available_range": null,
+        }),
+    };
+
+    // OTIO derives `trimmed_range` rather than storing it, so keep ours in
+    // metadata where other tools will carry it through untouched. Reels go
+    // where OTIO's own CMX 3600 adapter puts them.
+    let mut metadata = json!({});
+    if let Some(range) = &clip.trimmed_range {
+        metadata["turnaround"] = json!({ "trimmed_range": time_range_value(range) });
+    }
+    if let Some(reel) = &clip.reel {
+        metadata["cmx_3600"] = json!({ "reel": reel });
+    }
+
+    json!({
+        "OTIO_SCHEMA": "Clip.1",
+        "metadata": metadata,
+        "name": clip.name,
+        "source_range": clip.source_range.as_ref().map(time_range_value),
+        "effects": [],
+        "markers": [],
+        "media_reference": media_reference,
+    })
+}
+
+fn gap_value(duration: RationalTime) -> Value {
+    json!({
+        "OTIO_SCHEMA": "Gap.1",
+        "metadata": {},
+        "name": "",
+        "source_range": time_range_value(&TimeRange {
+            start: RationalTime::new(0.0, duration.rate),
+            duration,
+        }),
+        "effects": [],
+        "markers": [],
+    })
+}
+
+fn time_range_value(range: &TimeRange) -> Value {
+    json!({
+        "OTIO_SCHEMA": "TimeRange.1",
+        "start_time": rational_time_value(&range.start),
+        "duration": rational_time_value(&range.duration),
+    })
+}
+
+fn rational_time_value(time: &RationalTime) -> Value {
+    json!({
+        "OTIO_SCHEMA": "RationalTime.1",
+        "value": time.value,
+        "rate": time.rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeline::parser;
+
+    const RATE: f64 = 30000.0 / 1001.0;
+
+    fn range(start: f64, duration: f64) -> TimeRange {
+        TimeRange { start: RationalTime::new(start, RATE), duration: RationalTime::new(duration, RATE) }
+    }
+
+    fn clip(name: &str, record: f64, source: TimeRange) -> Clip {
+        Clip {
+            name: name.to_string(),
+            media_ref: Some(format!("file:///media/{name}.mov")),
+            source_range: Some(source),
+            trimmed_range: None,
+            record_start: Some(RationalTime::new(record, RATE)),
+            reel: None,
+        }
+    }
+
+    #[test]
+    fn written_timeline_parses_back_unchanged() {
+        let trimmed = Clip {
+            trimmed_range: Some(range(90.0, 36.0)),
+            reel: Some("A001C003".to_string()),
+            ..clip("B", 60.0, range(100.0, 24.0))
+        };
+        let timeline = Timeline {
+            name: "Reel 1".to_string(),
+            tracks: vec![
+                // B sits after a 12-frame gap.
+                Track { name: "V1".to_string(), kind: TrackKind::Video, clips: vec![clip("A", 0.0, range(0.0, 48.0)), trimmed] },
+                // C starts 12 frames in, after a leading gap.
+                Track { name: "A1".to_string(), kind: TrackKind::Audio, clips: vec![clip("C", 12.0, range(0.0, 30.0))] },
+            ],
+            duration: None,
+            global_start_time: Some(RationalTime::new(107892.0, RATE)),
+            drop_frame: true,
+        };
+
+        let parsed = parser::parse_otio_json(&to_otio_json(&timeline).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&timeline).unwrap());
+    }
+}
@@ -1,49 +1,29 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use crate::db::schema::{self, Commit, FileSnapshot};
+use crate::vcs::ignore::IgnoreRules;
 use crate::vcs::object_store::ObjectStore;
+use crate::vcs::rename::{self, Rename};
+use crate::vcs::tracking::TrackingConfig;
+use crate::watcher::editors;
 use rusqlite::Connection;
 use uuid::Uuid;
 use chrono::Utc;
 
-const PROJECT_EXTENSIONS: &[&str] = &[
-    "prproj", "drp", "db", "fcpxml", "otio", "xml", "edl", "aaf", "sesx", "als", "flp", "ptx",
-];
-
-const MEDIA_EXTENSIONS: &[&str] = &[
-    // Video
-    "mp4", "mov", "avi", "mkv", "mxf", "webm", "wmv", "flv", "m4v", "mpg", "mpeg", "ts", "r3d", "braw", "ari",
-    // Audio
-    "wav", "mp3", "aac", "flac", "ogg", "m4a", "aiff", "aif", "wma",
-    // Images
-    "png", "jpg", "jpeg", "tif", "tiff", "exr", "dpx", "bmp", "gif", "webp", "psd", "psb", "svg",
-    // Subtitles / data
-    "srt", "ass", "lut", "cube",
-];
-
-const FULL_COPY_SIZE_LIMIT: u64 = 50 * 1024 * 1024; // 50 MB
-
-fn is_project_file(ext: &str) -> bool {
-    PROJECT_EXTENSIONS.contains(&ext.to_lowercase().as_str())
-}
-
-fn is_tracked_extension(ext: &str) -> bool {
-    let lower = ext.to_lowercase();
-    PROJECT_EXTENSIONS.contains(&lower.as_str()) || MEDIA_EXTENSIONS.contains(&lower.as_str())
-}
-
 pub fn create_commit(
     conn: &Connection,
     project_id: &str,
     project_root: &Path,
     message: &str,
     is_milestone: bool,
+    is_automatic: bool,
     object_store: &ObjectStore,
 ) -> Result<Commit, super::VcsError> {
     let branch = schema::get_active_branch(conn, project_id)?
         .ok_or(super::VcsError::NoActiveBranch)?;
 
-    let changed_files = scan_tracked_files(project_root)?;
+    let tracking = TrackingConfig::load(conn)?;
+    let changed_files = scan_tracked_files(project_root, &tracking)?;
     if changed_files.is_empty() {
         return Err(super::VcsError::NoChanges);
     }
@@ -59,10 +39,12 @@ pub fn create_commit(
         message: message.to_string(),
         is_milestone,
         created_at: now,
+        is_automatic,
     };
 
     schema::insert_commit(conn, &commit)?;
 
+    let mut snapshots = Vec::with_capacity(changed_files.len());
     for file_path in &changed_files {
         let abs_path = project_root.join(file_path);
         let metadata = std::fs::metadata(&abs_path)?;
@@ -74,26 +56,41 @@ pub fn create_commit(
             .unwrap_or("unknown")
             .to_string();
 
-        let should_full_copy = is_project_file(&ext) || (file_size as u64) <= FULL_COPY_SIZE_LIMIT;
+        let should_full_copy = tracking.should_full_copy(&ext, file_size as u64);
 
-        let content_hash = if should_full_copy {
+        let (content_hash, partial_hash) = if should_full_copy {
             let (hash, _) = object_store.store_file(&abs_path, conn)?;
-            hash
+            (hash, None)
         } else {
-            crate::hasher::hash_file(&abs_path)?
+            (crate::hasher::hash_file(&abs_path)?, Some(crate::hasher::hash_file_partial(&abs_path)?))
         };
 
-        let file_type = classify_file_type(&ext);
+        let (file_type, producer) = tracking.classify_file(&abs_path);
 
-        let snapshot = FileSnapshot {
+        snapshots.push(FileSnapshot {
             id: Uuid::new_v4().to_string(),
             commit_id: commit_id.clone(),
             file_path: file_path.clone(),
             content_hash,
             file_size,
             file_type,
-        };
-        schema::insert_file_snapshot(conn, &snapshot)?;
+            producer,
+            partial_hash,
+            renamed_from: None,
+        });
+    }
+
+    let parent_snapshots = match &branch.head_commit_id {
+        Some(parent_id) => schema::get_snapshots_for_commit(conn, parent_id)?,
+        None => Vec::new(),
+    };
+    for rename in renames_since(&parent_snapshots, &snapshots) {
+        if let Some(snapshot) = snapshots.iter_mut().find(|s| s.file_path == rename.to) {
+            snapshot.renamed_from = Some(rename.from);
+        }
+    }
+    for snapshot in &snapshots {
+        schema::insert_file_snapshot(conn, snapshot)?;
     }
 
     schema::update_branch_head(conn, &branch.id, &commit_id)?;
@@ -101,68 +98,99 @@ pub fn create_commit(
     Ok(commit)
 }
 
-fn classify_file_type(ext: &str) -> String {
-    let lower = ext.to_lowercase();
-    match lower.as_str() {
-        "mp4" | "mov" | "avi" | "mkv" | "mxf" | "webm" | "wmv" | "flv" | "m4v" | "mpg" | "mpeg" | "ts" | "r3d" | "braw" | "ari"
-            => "video".to_string(),
-        "wav" | "mp3" | "aac" | "flac" | "ogg" | "m4a" | "aiff" | "aif" | "wma"
-            => "audio".to_string(),
-        "png" | "jpg" | "jpeg" | "tif" | "tiff" | "exr" | "dpx" | "bmp" | "gif" | "webp" | "psd" | "psb" | "svg"
-            => "image".to_string(),
-        "prproj" | "drp" | "fcpxml" | "otio" | "xml" | "edl" | "aaf" | "sesx" | "als" | "flp" | "ptx"
-            => "project".to_string(),
-        "srt" | "ass" => "subtitle".to_string(),
-        "lut" | "cube" => "lut".to_string(),
-        _ => lower,
-    }
+/// Files of `parent` that reappear under another path in `current` with the
+/// same content.
+fn renames_since(parent: &[FileSnapshot], current: &[FileSnapshot]) -> Vec<Rename> {
+    let parent_hashes: HashMap<&str, &str> = parent.iter()
+        .map(|s| (s.file_path.as_str(), s.content_hash.as_str()))
+        .collect();
+    let current_hashes: HashMap<&str, &str> = current.iter()
+        .map(|s| (s.file_path.as_str(), s.content_hash.as_str()))
+        .collect();
+    let removed: Vec<(String, i64)> = parent.iter()
+        .filter(|s| !current_hashes.contains_key(s.file_path.as_str()))
+        .map(|s| (s.file_path.clone(), s.file_size))
+        .collect();
+    let added: Vec<(String, i64)> = current.iter()
+        .filter(|s| !parent_hashes.contains_key(s.file_path.as_str()))
+        .map(|s| (s.file_path.clone(), s.file_size))
+        .collect();
+    rename::match_renames(&removed, &added, |from, to| parent_hashes.get(from) == current_hashes.get(to))
 }
 
-fn scan_tracked_files(root: &Path) -> Result<Vec<String>, std::io::Error> {
+/// Tracked files under `root`, relative to it, leaving out hidden files,
+/// editor clutter and anything matched by `.turnaroundignore` files.
+pub fn scan_tracked_files(root: &Path, tracking: &TrackingConfig) -> Result<Vec<String>, std::io::Error> {
     let mut tracked = Vec::new();
-    scan_dir_recursive(root, root, &mut tracked)?;
+    let mut rules = IgnoreRules::for_root(root);
+    scan_dir_recursive(root, root, tracking, &mut rules, &mut tracked)?;
     Ok(tracked)
 }
 
-fn scan_dir_recursive(root: &Path, dir: &Path, results: &mut Vec<String>) -> Result<(), std::io::Error> {
+fn scan_dir_recursive(
+    root: &Path,
+    dir: &Path,
+    tracking: &TrackingConfig,
+    rules: &mut IgnoreRules,
+    results: &mut Vec<String>,
+) -> Result<(), std::io::Error> {
     if !dir.is_dir() {
         return Ok(());
     }
+    let dir_rel = dir.strip_prefix(root).unwrap_or(dir).to_string_lossy().replace('\\', "/");
+    rules.add_dir(dir, &dir_rel);
 
     for entry in std::fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
 
-        if path.file_name().map(|n| n.to_string_lossy().starts_with('.')).unwrap_or(false) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
             continue;
         }
+        let Ok(rel) = path.strip_prefix(root) else { continue };
+        let rel_str = rel.to_string_lossy().replace('\\', "/");
 
         if path.is_dir() {
-            scan_dir_recursive(root, &path, results)?;
-        } else if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            if is_tracked_extension(ext) {
-                if let Ok(rel) = path.strip_prefix(root) {
-                    results.push(rel.to_string_lossy().to_string());
-                }
+            if !editors::is_ignored_dir(&name) && !rules.is_ignored(&rel_str, true) {
+                scan_dir_recursive(root, &path, tracking, rules, results)?;
             }
+        } else if tracking.is_tracked(&path) && !editors::is_ignored(rel) && !rules.is_ignored(&rel_str, false) {
+            results.push(rel.to_string_lossy().to_string());
         }
     }
     Ok(())
 }
 
+/// Uncommitted changes: paths added, modified or removed since the head
+/// commit, and files that only moved.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ChangedFiles {
+    /// Changed paths, leaving out both sides of `renames`.
+    pub changed: Vec<String>,
+    pub renames: Vec<Rename>,
+}
+
+impl ChangedFiles {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.renames.is_empty()
+    }
+}
+
 /// Fast check: compares file size and modification time against the head commit.
-/// Does NOT hash files — designed to run on a timer without blocking.
+/// Only hashes new files the size of a removed one, to tell whether they moved,
+/// and reference-only media just partially — designed to run on a timer without blocking.
 pub fn get_changed_files(
     conn: &Connection,
     project_id: &str,
     project_root: &Path,
-) -> Result<Vec<String>, super::VcsError> {
+) -> Result<ChangedFiles, super::VcsError> {
     let branch = schema::get_active_branch(conn, project_id)?
         .ok_or(super::VcsError::NoActiveBranch)?;
 
-    let current = scan_tracked_files(project_root)?;
+    let current = scan_tracked_files(project_root, &TrackingConfig::load(conn)?)?;
     let head_id = match &branch.head_commit_id {
-        None => return Ok(current),
+        None => return Ok(ChangedFiles { changed: current, renames: Vec::new() }),
         Some(id) => id.clone(),
     };
 
@@ -173,13 +201,14 @@ pub fn get_changed_files(
         .unwrap_or(0);
 
     let snapshots = schema::get_snapshots_for_commit(conn, &head_id)?;
-    let last_state: HashMap<String, i64> = snapshots
+    let last_state: HashMap<String, FileSnapshot> = snapshots
         .into_iter()
-        .map(|s| (s.file_path, s.file_size))
+        .map(|s| (s.file_path.clone(), s))
         .collect();
 
     let current_set: HashSet<_> = current.iter().cloned().collect();
     let mut changed: Vec<String> = Vec::new();
+    let mut added: Vec<(String, i64)> = Vec::new();
 
     for path in &current {
         let abs = project_root.join(path);
@@ -191,10 +220,11 @@ pub fn get_changed_files(
         match last_state.get(path) {
             None => {
                 changed.push(path.clone());
+                added.push((path.clone(), meta.len() as i64));
             }
-            Some(&last_size) => {
+            Some(last) => {
                 let current_size = meta.len() as i64;
-                if current_size != last_size {
+                if current_size != last.file_size {
                     changed.push(path.clone());
                     continue;
                 }
@@ -212,13 +242,28 @@ pub fn get_changed_files(
         }
     }
 
-    for path in last_state.keys() {
+    let mut removed: Vec<(String, i64)> = Vec::new();
+    for (path, last) in &last_state {
         if !current_set.contains(path) {
             changed.push(path.clone());
+            removed.push((path.clone(), last.file_size));
         }
     }
 
-    Ok(changed)
+    let mut hashes: HashMap<(String, bool), Option<String>> = HashMap::new();
+    let renames = rename::match_renames(&removed, &added, |from, to| {
+        let last = &last_state[from];
+        let partial = last.partial_hash.is_some();
+        let hash = hashes.entry((to.to_string(), partial)).or_insert_with(|| {
+            let abs = project_root.join(to);
+            let hash = if partial { crate::hasher::hash_file_partial(&abs) } else { crate::hasher::hash_file(&abs) };
+            hash.ok()
+        });
+        hash.as_deref() == Some(last.partial_hash.as_deref().unwrap_or(&last.content_hash))
+    });
+    changed.retain(|path| !renames.iter().any(|r| &r.from == path || &r.to == path));
+
+    Ok(ChangedFiles { changed, renames })
 }
 
 pub fn delete_commit(
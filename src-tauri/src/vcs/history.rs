@@ -1,5 +1,12 @@
-use crate::db::schema::{self, Commit};
+use crate::db::schema::{self, Commit, FileSnapshot};
 use rusqlite::Connection;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct FileRevision {
+    pub commit: Commit,
+    pub snapshot: FileSnapshot,
+}
 
 pub fn get_branch_history(conn: &Connection, branch_id: &str, limit: u32) -> Result<Vec<Commit>, super::VcsError> {
     Ok(schema::get_history(conn, branch_id, limit)?)
@@ -23,3 +30,35 @@ pub fn walk_history(conn: &Connection, start_commit_id: &str, max_depth: usize)
 
     Ok(result)
 }
+
+/// The commits, newest first and at most `max_depth` back from
+/// `start_commit_id`, in which the file at `file_path` was added, changed or
+/// moved. Follows the file back across renames to where it first appeared.
+pub fn file_history(
+    conn: &Connection,
+    start_commit_id: &str,
+    file_path: &str,
+    max_depth: usize,
+) -> Result<Vec<FileRevision>, super::VcsError> {
+    let mut revisions: Vec<FileRevision> = Vec::new();
+    let mut path = file_path.to_string();
+
+    for commit in walk_history(conn, start_commit_id, max_depth)? {
+        let Some(snapshot) = schema::get_snapshot(conn, &commit.id, &path)? else {
+            break;
+        };
+        if let Some(from) = &snapshot.renamed_from {
+            path = from.clone();
+        }
+        // Going back in time, so the previous entry is the next version; it
+        // only stays if this one differs from it.
+        if let Some(newer) = revisions.last() {
+            if newer.snapshot.renamed_from.is_none() && newer.snapshot.content_hash == snapshot.content_hash {
+                revisions.pop();
+            }
+        }
+        revisions.push(FileRevision { commit, snapshot });
+    }
+
+    Ok(revisions)
+}
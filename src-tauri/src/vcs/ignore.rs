@@ -0,0 +1,215 @@
+//! `.turnaroundignore` files: gitignore syntax, one per folder if wanted,
+//! each applying to its own folder and everything below it.
+//!
+//! Supported: `#` comments, `!` to re-include, a trailing `/` for folders
+//! only, a `/` elsewhere to anchor the pattern to the ignore file's folder,
+//! and `*`, `?`, `[...]` and `**` wildcards. Matching ignores case, since
+//! projects mostly live on case-insensitive volumes. As in git, a file
+//! cannot be re-included if a folder above it is ignored.
+
+use std::path::Path;
+
+pub const IGNORE_FILE: &str = ".turnaroundignore";
+
+/// Written into new projects.
+pub const DEFAULT_IGNORE: &str = "\
+# Files and folders Turnaround should not version, in .gitignore syntax.
+# Add a .turnaroundignore to any subfolder for rules that only apply there.
+
+Cache/
+CacheClip/
+Render Cache/
+Proxies/
+Optimized Media/
+node_modules/
+target/
+__pycache__/
+";
+
+struct Rule {
+    /// Folder of the ignore file, relative to the project root; empty for
+    /// the root itself.
+    base: String,
+    /// Lowercased pattern split on `/`.
+    segments: Vec<String>,
+    negate: bool,
+    dir_only: bool,
+}
+
+#[derive(Default)]
+pub struct IgnoreRules {
+    /// Shallower files first, so deeper rules win by coming later.
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Collect every ignore file under `root`, skipping folders that are
+    /// already ignored.
+    pub fn load(root: &Path) -> Self {
+        let mut rules = Self::for_root(root);
+        rules.load_dir(root, "");
+        rules
+    }
+
+    /// The rules to start from before reading any ignore file: the
+    /// defaults when `root` has no ignore file of its own, so projects
+    /// created before ignore files existed still skip caches and build
+    /// folders.
+    pub fn for_root(root: &Path) -> Self {
+        let mut rules = Self::default();
+        if !root.join(IGNORE_FILE).is_file() {
+            rules.add_rules("", DEFAULT_IGNORE);
+        }
+        rules
+    }
+
+    fn load_dir(&mut self, dir: &Path, rel: &str) {
+        self.add_dir(dir, rel);
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || !entry.path().is_dir() {
+                continue;
+            }
+            let child = join(rel, &name);
+            if !self.is_ignored(&child, true) {
+                self.load_dir(&entry.path(), &child);
+            }
+        }
+    }
+
+    /// Add the rules of `dir`'s own ignore file, if it has one. `rel` is
+    /// `dir` relative to the project root. For callers walking the tree
+    /// themselves, top-down.
+    pub fn add_dir(&mut self, dir: &Path, rel: &str) {
+        if let Ok(text) = std::fs::read_to_string(dir.join(IGNORE_FILE)) {
+            self.add_rules(rel, &text);
+        }
+    }
+
+    pub fn add_rules(&mut self, base: &str, text: &str) {
+        for line in text.lines() {
+            let mut line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let negate = line.starts_with('!');
+            // `!` negates; `\#` and `\!` are literal.
+            if negate || line.starts_with("\\#") || line.starts_with("\\!") {
+                line = &line[1..];
+            }
+            let dir_only = line.ends_with('/');
+            let line = line.trim_end_matches('/');
+            if line.is_empty() {
+                continue;
+            }
+            let anchored = line.contains('/');
+            let mut segments: Vec<String> = line.trim_start_matches('/')
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(str::to_lowercase)
+                .collect();
+            if !anchored {
+                segments.insert(0, "**".to_string());
+            }
+            self.rules.push(Rule { base: base.to_string(), segments, negate, dir_only });
+        }
+    }
+
+    /// Whether `rel` (relative to the project root, `/`-separated), or any
+    /// folder above it, is ignored.
+    pub fn is_ignored(&self, rel: &str, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let rel = rel.replace('\\', "/").to_lowercase();
+        let parts: Vec<&str> = rel.split('/').filter(|s| !s.is_empty()).collect();
+        (1..parts.len()).any(|n| self.matches(&parts[..n], true))
+            || self.matches(&parts, is_dir)
+    }
+
+    /// The verdict of the last rule matching exactly this path.
+    fn matches(&self, parts: &[&str], is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let base: Vec<String> = rule.base.split('/')
+                .filter(|s| !s.is_empty())
+                .map(str::to_lowercase)
+                .collect();
+            if parts.len() < base.len() || parts.iter().zip(&base).any(|(p, b)| p != b) {
+                continue;
+            }
+            if match_segments(&rule.segments, &parts[base.len()..]) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
+
+fn join(rel: &str, name: &str) -> String {
+    if rel.is_empty() { name.to_string() } else { format!("{rel}/{name}") }
+}
+
+fn match_segments(pattern: &[String], parts: &[&str]) -> bool {
+    match pattern.first() {
+        None => parts.is_empty(),
+        Some(seg) if seg == "**" => {
+            (0..=parts.len()).any(|skip| match_segments(&pattern[1..], &parts[skip..]))
+        }
+        Some(seg) => {
+            !parts.is_empty() && glob_match(seg, parts[0]) && match_segments(&pattern[1..], &parts[1..])
+        }
+    }
+}
+
+/// Match one path component against `*`, `?` and `[...]` wildcards.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    glob_from(&pattern, &name)
+}
+
+fn glob_from(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| glob_from(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && glob_from(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some(close) = pattern.iter().skip(2).position(|&c| c == ']').map(|i| i + 2) else {
+                return !name.is_empty() && name[0] == '[' && glob_from(&pattern[1..], &name[1..]);
+            };
+            !name.is_empty()
+                && class_matches(&pattern[1..close], name[0])
+                && glob_from(&pattern[close + 1..], &name[1..])
+        }
+        Some('\\') if pattern.len() > 1 => {
+            !name.is_empty() && name[0] == pattern[1] && glob_from(&pattern[2..], &name[1..])
+        }
+        Some(&c) => !name.is_empty() && name[0] == c && glob_from(&pattern[1..], &name[1..]),
+    }
+}
+
+/// `class` is the inside of `[...]`: characters and `a-z` ranges, negated
+/// by a leading `!` or `^`.
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!') | Some('^') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= class[i] <= c && c <= class[i + 2];
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
@@ -2,6 +2,10 @@ pub mod object_store;
 pub mod commit;
 pub mod branch;
 pub mod history;
+pub mod ignore;
+pub mod rename;
+pub mod sniff;
+pub mod tracking;
 
 use thiserror::Error;
 
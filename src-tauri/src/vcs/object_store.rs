@@ -6,12 +6,14 @@ use rusqlite::Connection;
 
 pub struct ObjectStore {
     base_path: PathBuf,
+    derived_base: PathBuf,
 }
 
 impl ObjectStore {
     pub fn new(editgit_dir: &Path) -> Self {
         let base_path = editgit_dir.join("objects");
-        Self { base_path }
+        let derived_base = editgit_dir.join("derived");
+        Self { base_path, derived_base }
     }
 
     pub fn init(&self) -> std::io::Result<()> {
@@ -53,6 +55,25 @@ impl ObjectStore {
         self.object_path(hash)
     }
 
+    /// Where to cache a file derived from an object, such as a thumbnail:
+    /// beside the object, named `<object>.<suffix>`. Derived files are
+    /// removed along with the object.
+    pub fn derived_path(&self, hash: &str, suffix: &str) -> PathBuf {
+        let mut name = self.object_path(hash).into_os_string();
+        name.push(".");
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+
+    /// Where to cache a file derived from two objects together, such as a
+    /// diff: `derived/<old>-<new>/<name>`, with the folder created. Removed
+    /// when either object is.
+    pub fn pair_derived_path(&self, old_hash: &str, new_hash: &str, name: &str) -> std::io::Result<PathBuf> {
+        let dir = self.derived_base.join(format!("{old_hash}-{new_hash}"));
+        fs::create_dir_all(&dir)?;
+        Ok(dir.join(name))
+    }
+
     pub fn remove_ref(&self, hash: &str, conn: &Connection) -> Result<(), super::VcsError> {
         let remaining = schema::decrement_object_ref(conn, hash)?;
         if remaining <= 0 {
@@ -60,8 +81,37 @@ impl ObjectStore {
             if path.exists() {
                 fs::remove_file(&path)?;
             }
+            self.remove_derived(hash)?;
             schema::delete_object(conn, hash)?;
         }
         Ok(())
     }
+
+    fn remove_derived(&self, hash: &str) -> std::io::Result<()> {
+        let path = self.object_path(hash);
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return Ok(());
+        };
+        let prefix = format!("{}.", name.to_string_lossy());
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(());
+        };
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        let Ok(pairs) = fs::read_dir(&self.derived_base) else {
+            return Ok(());
+        };
+        for pair in pairs {
+            let pair = pair?;
+            if pair.file_name().to_string_lossy().split('-').any(|h| h == hash) {
+                fs::remove_dir_all(pair.path())?;
+            }
+        }
+        Ok(())
+    }
 }
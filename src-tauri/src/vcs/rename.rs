@@ -0,0 +1,50 @@
+//! Rename and move detection: a path that disappeared and one that appeared
+//! with the same content are one file that moved.
+
+use std::collections::HashSet;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Pair added paths with removed ones of the same size for which
+/// `same_content(from, to)` holds, preferring a removed path with the same
+/// file name, then the first in path order. Each path is used at most once.
+pub fn match_renames(
+    removed: &[(String, i64)],
+    added: &[(String, i64)],
+    mut same_content: impl FnMut(&str, &str) -> bool,
+) -> Vec<Rename> {
+    let mut removed: Vec<&(String, i64)> = removed.iter().collect();
+    removed.sort();
+    let mut added: Vec<&(String, i64)> = added.iter().collect();
+    added.sort();
+
+    let mut used = HashSet::new();
+    let mut renames = Vec::new();
+    for (to, size) in added {
+        let candidates: Vec<&String> = removed.iter()
+            .filter(|(from, from_size)| from_size == size && !used.contains(from))
+            .map(|(from, _)| from)
+            .collect();
+        if candidates.is_empty() {
+            continue;
+        }
+        let name = file_name(to);
+        let (same_name, others): (Vec<&String>, Vec<&String>) =
+            candidates.into_iter().partition(|from| file_name(from) == name);
+        if let Some(from) = same_name.into_iter().chain(others).find(|from| same_content(from, to)) {
+            used.insert(from.clone());
+            renames.push(Rename { from: from.clone(), to: to.clone() });
+        }
+    }
+    renames
+}
+
+fn file_name(path: &str) -> Option<&std::ffi::OsStr> {
+    Path::new(path).file_name()
+}
@@ -0,0 +1,189 @@
+//! Content sniffing: recognise common media containers and NLE project
+//! formats from their first bytes, so a file's type doesn't rest on its
+//! extension alone.
+
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read for binary signatures and small text headers.
+const HEAD_LEN: usize = 64 * 1024;
+/// Text formats put producer hints anywhere near the top; read further.
+const TEXT_SCAN_LEN: usize = 1024 * 1024;
+
+const MXF_HEADER_KEY: &[u8] = &[0x06, 0x0E, 0x2B, 0x34, 0x02, 0x05, 0x01, 0x01, 0x0D, 0x01, 0x02, 0x01, 0x01];
+
+/// Box types a QuickTime file may start with when it has no `ftyp`.
+const QUICKTIME_LEADING_ATOMS: &[&[u8]] = &[b"moov", b"mdat", b"wide", b"free", b"skip", b"pnot"];
+
+/// Strings that give away which editor wrote an XML or OTIO file, most
+/// specific first.
+const PRODUCER_HINTS: &[(&str, &str)] = &[
+    ("pproTicks", "Premiere Pro"),
+    ("Adobe Premiere", "Premiere Pro"),
+    ("DaVinci Resolve", "DaVinci Resolve"),
+    ("Resolve_OTIO", "DaVinci Resolve"),
+    ("Avid Media Composer", "Avid Media Composer"),
+    ("Final Cut Pro", "Final Cut Pro"),
+    ("kdenlive", "Kdenlive"),
+    ("shotcut", "Shotcut"),
+];
+
+/// Every type sniffing can report.
+pub const SNIFFED_TYPES: &[&str] = &["project", "video", "audio", "image", "database"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sniffed {
+    /// One of `SNIFFED_TYPES`.
+    pub file_type: &'static str,
+    /// The editor that wrote a project file, when it can be told.
+    pub producer: Option<&'static str>,
+}
+
+impl Sniffed {
+    fn new(file_type: &'static str) -> Self {
+        Self { file_type, producer: None }
+    }
+
+    fn project(producer: Option<&'static str>) -> Self {
+        Self { file_type: "project", producer }
+    }
+}
+
+/// Sniff the file at `path`; `None` if it can't be read or isn't recognised.
+pub fn sniff_file(path: &Path) -> Option<Sniffed> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut head = Vec::with_capacity(HEAD_LEN);
+    (&mut file).take(HEAD_LEN as u64).read_to_end(&mut head).ok()?;
+    // Only text gets the longer read; binary signatures sit at the start.
+    if head.len() == HEAD_LEN && looks_like_text(&head) {
+        file.take((TEXT_SCAN_LEN - HEAD_LEN) as u64).read_to_end(&mut head).ok()?;
+    }
+    sniff(&head)
+}
+
+/// Recognise the format from the first bytes of a file.
+pub fn sniff(head: &[u8]) -> Option<Sniffed> {
+    if head.starts_with(b"SQLite format 3\0") {
+        return Some(Sniffed::new("database"));
+    }
+    if head.starts_with(&[0x1F, 0x8B]) {
+        return sniff_gzip(head);
+    }
+    if let Some(sniffed) = sniff_binary(head) {
+        return Some(sniffed);
+    }
+    let text = String::from_utf8_lossy(strip_bom(head));
+    let text = text.trim_start();
+    if text.starts_with('<') {
+        return sniff_xml(text);
+    }
+    if text.starts_with('{') && text.contains("\"OTIO_SCHEMA\"") {
+        return Some(Sniffed::project(producer_hint(text)));
+    }
+    None
+}
+
+fn sniff_binary(head: &[u8]) -> Option<Sniffed> {
+    let at = |range: std::ops::Range<usize>| head.get(range).unwrap_or_default();
+
+    if at(4..8) == b"ftyp" {
+        return Some(match at(8..12) {
+            b"M4A " | b"M4B " | b"M4P " => Sniffed::new("audio"),
+            b"heic" | b"heix" | b"mif1" | b"msf1" | b"avif" | b"crx " => Sniffed::new("image"),
+            _ => Sniffed::new("video"),
+        });
+    }
+    if QUICKTIME_LEADING_ATOMS.contains(&at(4..8)) {
+        return Some(Sniffed::new("video"));
+    }
+    if head.starts_with(MXF_HEADER_KEY) {
+        return Some(Sniffed::new("video"));
+    }
+    if matches!(at(0..4), b"RIFF" | b"RF64" | b"BW64") {
+        return match at(8..12) {
+            b"WAVE" => Some(Sniffed::new("audio")),
+            b"AVI " => Some(Sniffed::new("video")),
+            b"WEBP" => Some(Sniffed::new("image")),
+            _ => None,
+        };
+    }
+    if at(0..4) == b"FORM" && matches!(at(8..12), b"AIFF" | b"AIFC") {
+        return Some(Sniffed::new("audio"));
+    }
+    if head.starts_with(b"fLaC") || head.starts_with(b"ID3") {
+        return Some(Sniffed::new("audio"));
+    }
+    if head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some(Sniffed::new("video"));
+    }
+    if head.starts_with(b"\x89PNG\r\n\x1a\n")
+        || head.starts_with(&[0xFF, 0xD8, 0xFF])
+        || head.starts_with(b"II*\0")
+        || head.starts_with(b"MM\0*")
+    {
+        return Some(Sniffed::new("image"));
+    }
+    None
+}
+
+/// Premiere saves `.prproj` as gzip'd XML; look inside.
+fn sniff_gzip(head: &[u8]) -> Option<Sniffed> {
+    let mut inflated = Vec::new();
+    // A truncated stream errors at its end, after what we need.
+    let _ = flate2::read::GzDecoder::new(head)
+        .take(HEAD_LEN as u64)
+        .read_to_end(&mut inflated);
+    let text = String::from_utf8_lossy(strip_bom(&inflated));
+    let text = text.trim_start();
+    if text.starts_with('<') && xml_root(text) == Some("PremiereData") {
+        Some(Sniffed::project(Some("Premiere Pro")))
+    } else {
+        None
+    }
+}
+
+/// Only NLE formats; other XML, SVG included, is left to its extension.
+fn sniff_xml(text: &str) -> Option<Sniffed> {
+    match xml_root(text)? {
+        "fcpxml" => Some(Sniffed::project(producer_hint(text).or(Some("Final Cut Pro")))),
+        "xmeml" | "mlt" => Some(Sniffed::project(producer_hint(text))),
+        "PremiereData" => Some(Sniffed::project(Some("Premiere Pro"))),
+        _ => None,
+    }
+}
+
+/// Name of the root element, skipping the declaration, comments,
+/// processing instructions and DOCTYPE.
+fn xml_root(text: &str) -> Option<&str> {
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = &after[after.find("-->")? + 3..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = &rest[rest.find('>')? + 1..];
+        } else if let Some(after) = rest.strip_prefix('<') {
+            let end = after
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .unwrap_or(after.len());
+            return Some(&after[..end]).filter(|name| !name.is_empty());
+        } else {
+            return None;
+        }
+    }
+}
+
+fn producer_hint(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    PRODUCER_HINTS.iter()
+        .find(|(hint, _)| lower.contains(&hint.to_lowercase()))
+        .map(|(_, producer)| *producer)
+}
+
+fn strip_bom(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes)
+}
+
+fn looks_like_text(head: &[u8]) -> bool {
+    matches!(strip_bom(head).iter().find(|b| !b.is_ascii_whitespace()), Some(b'<') | Some(b'{'))
+}
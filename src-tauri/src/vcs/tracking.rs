@@ -0,0 +1,155 @@
+//! Which files a project tracks, what type each is, and whether commits
+//! store a full copy or only a reference (hash and size).
+//!
+//! Stored per project in the `config` table; projects without their own
+//! configuration use the default preset.
+
+use std::path::Path;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use crate::db::{schema, DbError};
+use super::sniff;
+
+const CONFIG_KEY: &str = "tracking";
+
+/// Extensions shared by unrelated formats, whose content decides the type.
+const AMBIGUOUS_EXTENSIONS: &[&str] = &["xml", "db"];
+
+const DEFAULT_FULL_COPY_SIZE_LIMIT: u64 = 50 * 1024 * 1024; // 50 MB
+
+/// `(file type, storage, extensions)` of the default preset.
+const DEFAULT_PRESET: &[(&str, StoragePolicy, &[&str])] = &[
+    ("project", StoragePolicy::FullCopy, &[
+        "prproj", "drp", "fcpxml", "otio", "xml", "edl", "aaf", "sesx", "als", "flp", "ptx",
+    ]),
+    ("database", StoragePolicy::FullCopy, &["db"]),
+    ("video", StoragePolicy::SizeLimit, &[
+        "mp4", "mov", "avi", "mkv", "mxf", "webm", "wmv", "flv", "m4v", "mpg", "mpeg", "ts", "r3d", "braw", "ari",
+    ]),
+    ("audio", StoragePolicy::SizeLimit, &[
+        "wav", "mp3", "aac", "flac", "ogg", "m4a", "aiff", "aif", "wma",
+    ]),
+    ("image", StoragePolicy::SizeLimit, &[
+        "png", "jpg", "jpeg", "tif", "tiff", "exr", "dpx", "bmp", "gif", "webp", "psd", "psb", "svg",
+    ]),
+    ("subtitle", StoragePolicy::SizeLimit, &["srt", "ass"]),
+    ("lut", StoragePolicy::SizeLimit, &["lut", "cube"]),
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StoragePolicy {
+    /// Always copy into the object store.
+    FullCopy,
+    /// Copy files up to the project's size limit, reference larger ones.
+    SizeLimit,
+    /// Only record the hash and size.
+    ReferenceOnly,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileTypeRule {
+    /// Lowercase, without the dot.
+    pub extension: String,
+    /// `project`, `video`, `audio`, `image`, ... or any other label.
+    pub file_type: String,
+    pub storage: StoragePolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackingConfig {
+    pub file_types: Vec<FileTypeRule>,
+    /// Largest file `SizeLimit` types copy in full, in bytes.
+    pub full_copy_size_limit: u64,
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        let file_types = DEFAULT_PRESET.iter()
+            .flat_map(|(file_type, storage, extensions)| {
+                extensions.iter().map(move |ext| FileTypeRule {
+                    extension: ext.to_string(),
+                    file_type: file_type.to_string(),
+                    storage: *storage,
+                })
+            })
+            .collect();
+        Self { file_types, full_copy_size_limit: DEFAULT_FULL_COPY_SIZE_LIMIT }
+    }
+}
+
+impl TrackingConfig {
+    pub fn load(conn: &Connection) -> Result<Self, DbError> {
+        Ok(schema::get_config(conn, CONFIG_KEY)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    /// Save with extensions normalised and de-duplicated, the first rule for
+    /// an extension winning.
+    pub fn save(&self, conn: &Connection) -> Result<(), DbError> {
+        let mut normalized = self.clone();
+        let mut seen = std::collections::HashSet::new();
+        normalized.file_types.retain_mut(|rule| {
+            rule.extension = rule.extension.trim().trim_start_matches('.').to_lowercase();
+            !rule.extension.is_empty() && seen.insert(rule.extension.clone())
+        });
+        let json = serde_json::to_string(&normalized).expect("tracking config serializes");
+        schema::set_config(conn, CONFIG_KEY, &json)
+    }
+
+    pub fn rule(&self, ext: &str) -> Option<&FileTypeRule> {
+        self.file_types.iter().find(|r| r.extension.eq_ignore_ascii_case(ext))
+    }
+
+    pub fn is_tracked(&self, path: &Path) -> bool {
+        extension(path).is_some_and(|ext| self.rule(ext).is_some())
+    }
+
+    /// The configured type, or the lowercase extension for untracked ones.
+    pub fn classify(&self, ext: &str) -> String {
+        self.rule(ext)
+            .map(|r| r.file_type.clone())
+            .unwrap_or_else(|| ext.to_lowercase())
+    }
+
+    /// Type and producing editor of the file at `path`. The configured type
+    /// stands unless the extension has no rule, says little about the
+    /// content (`xml`, `db`), or claims a built-in type the content
+    /// contradicts, such as an image named `.mov`; then the sniffed type is
+    /// used. Custom labels are always kept.
+    pub fn classify_file(&self, path: &Path) -> (String, Option<String>) {
+        let ext = extension(path).unwrap_or("unknown");
+        let sniffed = sniff::sniff_file(path);
+        let producer = sniffed.as_ref().and_then(|s| s.producer).map(str::to_string);
+        let file_type = match (self.rule(ext), sniffed) {
+            (None, Some(sniffed)) => sniffed.file_type.to_string(),
+            (Some(rule), Some(sniffed))
+                if AMBIGUOUS_EXTENSIONS.iter().any(|a| a.eq_ignore_ascii_case(ext))
+                    || (sniff::SNIFFED_TYPES.contains(&rule.file_type.as_str())
+                        && rule.file_type != sniffed.file_type) =>
+            {
+                sniffed.file_type.to_string()
+            }
+            _ => self.classify(ext),
+        };
+        (file_type, producer)
+    }
+
+    pub fn is_project_path(&self, path: &Path) -> bool {
+        extension(path).is_some_and(|ext| self.classify(ext) == "project")
+    }
+
+    pub fn should_full_copy(&self, ext: &str, size: u64) -> bool {
+        match self.rule(ext).map(|r| r.storage) {
+            Some(StoragePolicy::FullCopy) => true,
+            Some(StoragePolicy::ReferenceOnly) => false,
+            Some(StoragePolicy::SizeLimit) | None => size <= self.full_copy_size_limit,
+        }
+    }
+}
+
+fn extension(path: &Path) -> Option<&str> {
+    path.extension().and_then(|e| e.to_str())
+}
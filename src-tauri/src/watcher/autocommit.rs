@@ -0,0 +1,225 @@
+//! Auto-commit policies: commit on the user's behalf once the project has
+//! settled, driven by the project watcher's batched change events.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, EventId, Listener, Manager};
+use crate::AppState;
+use crate::db::{schema, DbError};
+use crate::vcs::{self, object_store::ObjectStore, tracking::TrackingConfig};
+use super::FileChangeEvent;
+
+const CONFIG_KEY: &str = "auto_commit";
+
+/// How often the runner re-checks its policies between change events.
+const TICK: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum AutoCommitPolicy {
+    /// Commit once no project file (the NLE document itself) has changed
+    /// for `seconds`.
+    AfterIdle { seconds: u64 },
+    /// Commit at most every `minutes` while there are uncommitted changes.
+    Periodic { minutes: u64 },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoCommitConfig {
+    pub enabled: bool,
+    /// A commit is made as soon as any policy is due.
+    pub policies: Vec<AutoCommitPolicy>,
+}
+
+pub fn load_config(conn: &Connection) -> Result<AutoCommitConfig, DbError> {
+    Ok(schema::get_config(conn, CONFIG_KEY)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+pub fn save_config(conn: &Connection, config: &AutoCommitConfig) -> Result<(), DbError> {
+    let json = serde_json::to_string(config).expect("auto-commit config serializes");
+    schema::set_config(conn, CONFIG_KEY, &json)
+}
+
+/// Changes seen since the last commit and when they happened.
+#[derive(Default)]
+struct Pending {
+    /// Latest change kind per project-relative path.
+    changes: BTreeMap<String, String>,
+    last_project_change: Option<Instant>,
+}
+
+impl Pending {
+    fn is_due(&self, policies: &[AutoCommitPolicy], last_commit: Instant, now: Instant) -> bool {
+        if self.changes.is_empty() {
+            return false;
+        }
+        policies.iter().any(|policy| match policy {
+            AutoCommitPolicy::AfterIdle { seconds } => self.last_project_change
+                .map(|t| now.duration_since(t) >= Duration::from_secs(*seconds))
+                .unwrap_or(false),
+            AutoCommitPolicy::Periodic { minutes } => {
+                now.duration_since(last_commit) >= Duration::from_secs(minutes * 60)
+            }
+        })
+    }
+}
+
+/// Keeps the runner alive; dropping it stops auto-commits.
+pub struct AutoCommitHandle {
+    app: AppHandle,
+    listener: EventId,
+    config: Arc<Mutex<AutoCommitConfig>>,
+}
+
+impl AutoCommitHandle {
+    /// Apply a new configuration to the running watcher.
+    pub fn set_config(&self, config: AutoCommitConfig) {
+        *self.config.lock() = config;
+    }
+}
+
+impl Drop for AutoCommitHandle {
+    fn drop(&mut self) {
+        // Unlistening drops the event handler and with it the channel
+        // sender, which ends the runner thread.
+        self.app.unlisten(self.listener);
+    }
+}
+
+pub fn start(
+    app: AppHandle,
+    project_root: PathBuf,
+    config: AutoCommitConfig,
+    tracking: TrackingConfig,
+) -> AutoCommitHandle {
+    let (tx, rx) = mpsc::channel::<Vec<FileChangeEvent>>();
+    let listener = app.listen("editgit://files-changed", move |event| {
+        if let Ok(changes) = serde_json::from_str(event.payload()) {
+            let _ = tx.send(changes);
+        }
+    });
+    let config = Arc::new(Mutex::new(config));
+
+    let thread_app = app.clone();
+    let thread_config = config.clone();
+    std::thread::spawn(move || {
+        let mut pending = Pending::default();
+        let mut last_commit = Instant::now();
+        loop {
+            match rx.recv_timeout(TICK) {
+                Ok(changes) => {
+                    let now = Instant::now();
+                    for change in changes {
+                        let Ok(rel) = Path::new(&change.path).strip_prefix(&project_root) else { continue };
+                        let rel = rel.to_string_lossy().replace('\\', "/");
+                        if rel.starts_with(".turnaround/") {
+                            continue;
+                        }
+                        if tracking.is_project_path(Path::new(&rel)) {
+                            pending.last_project_change = Some(now);
+                        }
+                        pending.changes.insert(rel, change.kind);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            let config = thread_config.lock().clone();
+            let now = Instant::now();
+            if !config.enabled || !pending.is_due(&config.policies, last_commit, now) {
+                continue;
+            }
+
+            // Whether or not the commit goes through, start the next window
+            // from here so a failing commit is not retried every tick.
+            last_commit = now;
+            match commit_now(&thread_app, &project_root, &pending.changes) {
+                Ok(Some(commit)) => {
+                    log::info!("Auto-committed {} change(s): {}", pending.changes.len(), commit.id);
+                    let _ = thread_app.emit("editgit://auto-committed", &commit);
+                    pending = Pending::default();
+                }
+                Ok(None) => pending = Pending::default(),
+                Err(e) => {
+                    // Keep the changes for the retry; restarting the idle
+                    // clock spaces retries out like the periodic window.
+                    log::warn!("Auto-commit failed: {e}");
+                    pending.last_project_change = pending.last_project_change.map(|_| now);
+                }
+            }
+        }
+    });
+
+    AutoCommitHandle { app, listener, config }
+}
+
+/// Commit the working tree as an automatic commit. `None` when the project
+/// is no longer active or nothing differs from HEAD.
+fn commit_now(
+    app: &AppHandle,
+    project_root: &Path,
+    changes: &BTreeMap<String, String>,
+) -> Result<Option<schema::Commit>, String> {
+    let state = app.state::<AppState>();
+    let Some(project_path) = state.active_project_path.lock().clone() else {
+        return Ok(None);
+    };
+    if !Path::new(&project_path).starts_with(project_root) {
+        return Ok(None);
+    }
+
+    let db = state.db.lock();
+    let project = schema::get_project_by_path(&db.conn, &project_path)
+        .map_err(|e| e.to_string())?
+        .ok_or("Project not found in database")?;
+    let dirty = vcs::commit::get_changed_files(&db.conn, &project.id, Path::new(&project_path))
+        .map_err(|e| e.to_string())?;
+    if dirty.is_empty() {
+        return Ok(None);
+    }
+
+    let obj_store = ObjectStore::new(&Path::new(&project_path).join(".turnaround"));
+    let commit = vcs::commit::create_commit(
+        &db.conn,
+        &project.id,
+        Path::new(&project_path),
+        &commit_message(changes),
+        false,
+        true,
+        &obj_store,
+    )
+    .map_err(|e| e.to_string())?;
+    drop(db);
+
+    if let Err(e) = crate::backup::backup_project(&project.name, &project_path) {
+        log::warn!("Background backup failed: {e}");
+        let _ = app.emit("editgit://backup-failed", &e);
+    }
+    Ok(Some(commit))
+}
+
+/// "Auto-commit: Edit.prproj, Cut v2.xml" with the per-file changes below.
+fn commit_message(changes: &BTreeMap<String, String>) -> String {
+    const LISTED: usize = 3;
+    let names: Vec<&str> = changes.keys()
+        .map(|p| p.rsplit('/').next().unwrap_or(p))
+        .take(LISTED)
+        .collect();
+    let mut summary = format!("Auto-commit: {}", names.join(", "));
+    if changes.len() > LISTED {
+        summary.push_str(&format!(" and {} more", changes.len() - LISTED));
+    }
+
+    let details: Vec<String> = changes.iter().map(|(path, kind)| format!("{kind}: {path}")).collect();
+    format!("{summary}\n\n{}", details.join("\n"))
+}
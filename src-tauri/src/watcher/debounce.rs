@@ -0,0 +1,105 @@
+//! Per-path debouncing for the project watcher.
+//!
+//! Raw events only mark a path as dirty. Once a path has been quiet for a
+//! while, the debouncer compares whether it existed before the burst with
+//! whether it exists now, so a create-modify-rename sequence from an atomic
+//! save reports one `modified`, and a temp file created and deleted inside
+//! the window reports nothing.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use super::FileChangeEvent;
+
+struct Pending {
+    first_seen: Instant,
+    last_seen: Instant,
+}
+
+pub struct Debouncer {
+    quiet: Duration,
+    max_wait: Duration,
+    /// Tracked paths believed to exist, as of the last flush.
+    known: HashSet<PathBuf>,
+    pending: HashMap<PathBuf, Pending>,
+    /// Paths that are only reported once quiet, however long that takes.
+    quiet_only: Box<dyn Fn(&Path) -> bool + Send>,
+}
+
+impl Debouncer {
+    /// A path is reported once it has seen no events for `quiet`, or after
+    /// `max_wait` of continuous activity.
+    pub fn new(quiet: Duration, max_wait: Duration, known: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            quiet,
+            max_wait,
+            known: known.into_iter().collect(),
+            pending: HashMap::new(),
+            quiet_only: Box::new(|_| false),
+        }
+    }
+
+    /// Exempt paths from `max_wait`, so a render or export that keeps
+    /// growing is not reported half-written.
+    pub fn with_quiet_only(mut self, quiet_only: impl Fn(&Path) -> bool + Send + 'static) -> Self {
+        self.quiet_only = Box::new(quiet_only);
+        self
+    }
+
+    pub fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.pending
+            .entry(path)
+            .and_modify(|p| p.last_seen = now)
+            .or_insert(Pending { first_seen: now, last_seen: now });
+    }
+
+    /// How many paths are waiting to be reported.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// When the next pending path becomes due, if any.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.iter().map(|(path, p)| self.due_at(path, p)).min()
+    }
+
+    /// Net changes for every path that is due, sorted by path.
+    pub fn flush_ready(&mut self, now: Instant, exists: impl Fn(&Path) -> bool) -> Vec<FileChangeEvent> {
+        let due: Vec<PathBuf> = self.pending.iter()
+            .filter(|(path, p)| self.due_at(path, p) <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        let mut changes = Vec::new();
+        for path in due {
+            self.pending.remove(&path);
+            let existed = self.known.contains(&path);
+            let kind = match (existed, exists(&path)) {
+                (false, false) => continue,
+                (false, true) => "created",
+                (true, true) => "modified",
+                (true, false) => "removed",
+            };
+            if kind == "removed" {
+                self.known.remove(&path);
+            } else {
+                self.known.insert(path.clone());
+            }
+            changes.push(FileChangeEvent {
+                path: path.to_string_lossy().to_string(),
+                kind: kind.to_string(),
+            });
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+
+    fn due_at(&self, path: &Path, pending: &Pending) -> Instant {
+        let quiet = pending.last_seen + self.quiet;
+        if (self.quiet_only)(path) {
+            quiet
+        } else {
+            quiet.min(pending.first_seen + self.max_wait)
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! What editors leave in a project folder besides the project itself: lock
+//! files that say a project is open, temp files from saves and renders,
+//! caches, and autosave folders.
+//!
+//! Rules are matched against paths relative to the project root, and only
+//! folder names specific enough not to collide with a user's own folders
+//! are listed.
+
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::vcs::ignore::glob_match;
+
+pub struct EditorProfile {
+    pub name: &'static str,
+    project_extensions: &'static [&'static str],
+    /// Lock files beside a project, `{stem}` being the project's file stem.
+    lock_patterns: &'static [&'static str],
+    /// File names written during saves and renders.
+    temp_patterns: &'static [&'static str],
+    /// Folders of caches and previews the editor can regenerate.
+    cache_dirs: &'static [&'static str],
+    /// Folders the editor writes timed copies of the project into.
+    autosave_dirs: &'static [&'static str],
+}
+
+pub const PROFILES: &[EditorProfile] = &[
+    EditorProfile {
+        name: "Premiere Pro",
+        project_extensions: &["prproj"],
+        lock_patterns: &["{stem}.prlock"],
+        temp_patterns: &["*.prproj.tmp", "*.prproj~*"],
+        cache_dirs: &[
+            "Adobe Premiere Pro Video Previews",
+            "Adobe Premiere Pro Audio Previews",
+            "Media Cache",
+            "Media Cache Files",
+        ],
+        autosave_dirs: &["Adobe Premiere Pro Auto-Save"],
+    },
+    EditorProfile {
+        name: "DaVinci Resolve",
+        project_extensions: &["drp", "db"],
+        lock_patterns: &[],
+        temp_patterns: &["*.db-journal", "*.db-wal", "*.db-shm", "*.drp.tmp"],
+        cache_dirs: &["CacheClip", "ProxyMedia", ".gallery"],
+        autosave_dirs: &[],
+    },
+    EditorProfile {
+        name: "Pro Tools",
+        project_extensions: &["ptx"],
+        lock_patterns: &[],
+        temp_patterns: &[],
+        cache_dirs: &["WaveCache.wfm"],
+        autosave_dirs: &["Session File Backups"],
+    },
+];
+
+/// Temp names any application may leave behind, including our own
+/// in-progress Resolve snapshots.
+const COMMON_TEMP_PATTERNS: &[&str] = &[
+    "~$*", ".~lock.*#", "*.tmp", "*.temp", "*.part", "*.partial", "*.crdownload", "*.download",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenProject {
+    /// Relative to the project root.
+    pub project_path: String,
+    pub editor: String,
+    pub lock_path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutosaveFile {
+    /// Relative to the project root.
+    pub path: String,
+    pub editor: String,
+    pub size_bytes: u64,
+    /// RFC 3339.
+    pub modified_at: Option<String>,
+}
+
+/// Whether `rel` is a temp or lock file, or lives in a cache or autosave
+/// folder, and so is not part of the project's own history.
+pub fn is_ignored(rel: &Path) -> bool {
+    let Some(name) = rel.file_name().map(|n| n.to_string_lossy()) else {
+        return false;
+    };
+    if is_temp_name(&name) || is_lock_file(rel) {
+        return true;
+    }
+    rel.parent()
+        .map(|dirs| dirs.components().any(|c| {
+            let dir = c.as_os_str().to_string_lossy();
+            is_cache_dir(&dir) || autosave_editor_for_dir(&dir).is_some()
+        }))
+        .unwrap_or(false)
+}
+
+/// Whether a folder called `name` should not be descended into at all.
+pub fn is_ignored_dir(name: &str) -> bool {
+    is_cache_dir(name) || autosave_editor_for_dir(name).is_some()
+}
+
+pub fn is_lock_file(rel: &Path) -> bool {
+    let Some(name) = rel.file_name().map(|n| n.to_string_lossy()) else {
+        return false;
+    };
+    PROFILES.iter()
+        .flat_map(|p| p.lock_patterns)
+        .any(|pattern| name_matches(&pattern.replace("{stem}", "*"), &name))
+}
+
+/// The editor whose autosave folder holds `rel`, if any.
+pub fn autosave_editor(rel: &Path) -> Option<&'static str> {
+    rel.parent()?
+        .components()
+        .find_map(|c| autosave_editor_for_dir(&c.as_os_str().to_string_lossy()))
+}
+
+/// Lock files that would mark `project_file` as open, with the editor
+/// that writes them.
+pub fn lock_files_for(project_file: &Path) -> Vec<(&'static str, PathBuf)> {
+    let (Some(stem), Some(ext)) = (project_file.file_stem(), project_file.extension()) else {
+        return Vec::new();
+    };
+    let (stem, ext) = (stem.to_string_lossy(), ext.to_string_lossy().to_lowercase());
+    PROFILES.iter()
+        .filter(|p| p.project_extensions.contains(&ext.as_str()))
+        .flat_map(|p| p.lock_patterns.iter().map(move |pattern| (p.name, pattern)))
+        .map(|(editor, pattern)| (editor, project_file.with_file_name(pattern.replace("{stem}", &stem))))
+        .collect()
+}
+
+/// The project a lock file belongs to, whether or not the lock still exists.
+pub fn project_for_lock(root: &Path, lock: &Path) -> Option<OpenProject> {
+    let name = lock.file_name()?.to_string_lossy().to_string();
+    for profile in PROFILES {
+        for pattern in profile.lock_patterns {
+            let Some((prefix, suffix)) = pattern.split_once("{stem}") else { continue };
+            let lower = name.to_lowercase();
+            if !lower.starts_with(&prefix.to_lowercase()) || !lower.ends_with(&suffix.to_lowercase()) {
+                continue;
+            }
+            let Some(stem) = name.get(prefix.len()..name.len() - suffix.len()) else { continue };
+            let candidates: Vec<PathBuf> = profile.project_extensions.iter()
+                .map(|ext| lock.with_file_name(format!("{stem}.{ext}")))
+                .collect();
+            let project = candidates.iter().find(|p| p.is_file()).unwrap_or(&candidates[0]);
+            let rel = |p: &Path| p.strip_prefix(root).unwrap_or(p).to_string_lossy().to_string();
+            return Some(OpenProject {
+                project_path: rel(project),
+                editor: profile.name.to_string(),
+                lock_path: rel(lock),
+            });
+        }
+    }
+    None
+}
+
+/// Projects among `tracked` (relative paths) whose editor holds a lock.
+pub fn open_projects(root: &Path, tracked: &[String]) -> Vec<OpenProject> {
+    tracked.iter()
+        .flat_map(|rel| {
+            lock_files_for(&root.join(rel))
+                .into_iter()
+                .filter(|(_, lock)| lock.is_file())
+                .map(move |(editor, lock)| OpenProject {
+                    project_path: rel.clone(),
+                    editor: editor.to_string(),
+                    lock_path: lock.strip_prefix(root).unwrap_or(&lock).to_string_lossy().to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Every file in the project's autosave folders, newest first.
+pub fn list_autosaves(root: &Path) -> Vec<AutosaveFile> {
+    let mut found = Vec::new();
+    collect_autosaves(root, root, &mut found);
+    found.sort_by(|a, b| b.modified_at.cmp(&a.modified_at).then_with(|| a.path.cmp(&b.path)));
+    found
+}
+
+fn collect_autosaves(root: &Path, dir: &Path, out: &mut Vec<AutosaveFile>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || !path.is_dir() || is_cache_dir(&name) {
+            continue;
+        }
+        match autosave_editor_for_dir(&name) {
+            Some(editor) => collect_autosave_files(root, &path, editor, out),
+            None => collect_autosaves(root, &path, out),
+        }
+    }
+}
+
+fn collect_autosave_files(root: &Path, dir: &Path, editor: &str, out: &mut Vec<AutosaveFile>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_autosave_files(root, &path, editor, out);
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || is_temp_name(&name) {
+            continue;
+        }
+        let meta = entry.metadata().ok();
+        out.push(AutosaveFile {
+            path: path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/"),
+            editor: editor.to_string(),
+            size_bytes: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+            modified_at: meta
+                .and_then(|m| m.modified().ok())
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+        });
+    }
+}
+
+fn is_temp_name(name: &str) -> bool {
+    COMMON_TEMP_PATTERNS.iter()
+        .chain(PROFILES.iter().flat_map(|p| p.temp_patterns))
+        .any(|pattern| name_matches(pattern, name))
+}
+
+fn is_cache_dir(name: &str) -> bool {
+    PROFILES.iter().flat_map(|p| p.cache_dirs).any(|d| d.eq_ignore_ascii_case(name))
+}
+
+fn autosave_editor_for_dir(name: &str) -> Option<&'static str> {
+    PROFILES.iter()
+        .find(|p| p.autosave_dirs.iter().any(|d| d.eq_ignore_ascii_case(name)))
+        .map(|p| p.name)
+}
+
+/// Profile patterns ignore case, as the macOS and Windows volumes editors
+/// work on do.
+fn name_matches(pattern: &str, name: &str) -> bool {
+    glob_match(&pattern.to_lowercase(), &name.to_lowercase())
+}
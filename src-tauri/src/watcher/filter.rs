@@ -1,21 +1,37 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use super::editors;
+use crate::vcs::ignore::IgnoreRules;
+use crate::vcs::tracking::TrackingConfig;
 
-const PROJECT_EXTENSIONS: &[&str] = &[
-    "prproj", "drp", "db", "fcpxml", "otio", "xml", "edl", "aaf", "sesx", "als", "flp", "ptx",
-];
+/// Decides which paths under a project root the watcher reports, the same
+/// way the commit scanner does.
+pub struct ProjectFilter {
+    root: PathBuf,
+    tracking: TrackingConfig,
+    rules: IgnoreRules,
+}
+
+impl ProjectFilter {
+    pub fn new(root: &Path, tracking: TrackingConfig) -> Self {
+        Self { root: root.to_path_buf(), tracking, rules: IgnoreRules::load(root) }
+    }
 
-const MEDIA_EXTENSIONS: &[&str] = &[
-    "mp4", "mov", "avi", "mkv", "mxf", "webm", "wmv", "flv", "m4v", "mpg", "mpeg", "ts", "r3d", "braw", "ari",
-    "wav", "mp3", "aac", "flac", "ogg", "m4a", "aiff", "aif", "wma",
-    "png", "jpg", "jpeg", "tif", "tiff", "exr", "dpx", "bmp", "gif", "webp", "psd", "psb", "svg",
-    "srt", "ass", "lut", "cube",
-];
+    /// Pick up edited `.turnaroundignore` files.
+    pub fn reload_ignore_rules(&mut self) {
+        self.rules = IgnoreRules::load(&self.root);
+    }
+
+    /// Has a tracked extension, wherever it is.
+    pub fn is_tracked_file(&self, path: &Path) -> bool {
+        self.tracking.is_tracked(path)
+    }
 
-pub fn is_tracked_file(path: &Path) -> bool {
-    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        let lower = ext.to_lowercase();
-        PROJECT_EXTENSIONS.contains(&lower.as_str()) || MEDIA_EXTENSIONS.contains(&lower.as_str())
-    } else {
-        false
+    /// A tracked file that is neither an editor's temp, lock, cache or
+    /// autosave file nor excluded by ignore rules.
+    pub fn is_project_file(&self, path: &Path) -> bool {
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        self.is_tracked_file(path)
+            && !editors::is_ignored(rel)
+            && !self.rules.is_ignored(&rel.to_string_lossy(), false)
     }
 }
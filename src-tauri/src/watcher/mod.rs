@@ -1,33 +1,80 @@
+pub mod autocommit;
+pub mod debounce;
+pub mod editors;
 pub mod filter;
+pub mod poll;
 pub mod resolve;
+pub mod snapshot;
+pub mod status;
 
 use notify::{RecommendedWatcher, RecursiveMode, Watcher, Config};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use crate::vcs::ignore::IGNORE_FILE;
+use crate::vcs::tracking::TrackingConfig;
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileChangeEvent {
     pub path: String,
     pub kind: String,
 }
 
+/// How long a path must go without events before its change is reported.
+const PROJECT_QUIET_PERIOD: Duration = Duration::from_millis(750);
+/// Report a path that keeps changing at least this often.
+const PROJECT_MAX_WAIT: Duration = Duration::from_secs(10);
+/// How long the Resolve DB must go unwritten before it is snapshotted.
+const RESOLVE_QUIET_PERIOD: Duration = Duration::from_secs(1);
+
+/// Where project changes come from; dropping it stops them.
+enum ProjectSource {
+    Native { _watcher: RecommendedWatcher },
+    Polling { _poller: poll::Poller },
+}
+
 pub struct WatcherHandle {
-    _project_watcher: RecommendedWatcher,
+    _project_watcher: ProjectSource,
     _resolve_watcher: Option<RecommendedWatcher>,
+    auto_commit: autocommit::AutoCommitHandle,
+    status: status::StatusReporter,
+}
+
+impl WatcherHandle {
+    pub fn status(&self) -> status::WatcherStatus {
+        self.status.get()
+    }
+
+    pub fn set_auto_commit(&self, config: autocommit::AutoCommitConfig) {
+        self.auto_commit.set_config(config);
+    }
 }
 
 /// Start watching the project folder for tracked-file changes.
-/// If `resolve_db` is provided, also poll that file and copy it into
-/// `project_dir/project.drp` whenever it changes.
+/// If `resolve_db` is provided, also watch that database and snapshot it into
+/// `project_dir/ResolveProject.db` whenever it changes. Batched changes also
+/// feed the auto-commit runner, which acts according to `auto_commit`.
 pub fn start_watching(
     app_handle: AppHandle,
     project_dir: PathBuf,
     resolve_db: Option<PathBuf>,
+    watch: status::WatchConfig,
+    tracking: TrackingConfig,
+    auto_commit: autocommit::AutoCommitConfig,
 ) -> Result<WatcherHandle, String> {
-    let project_watcher = start_project_watcher(app_handle.clone(), &project_dir)?;
+    let status = status::StatusReporter::new(app_handle.clone(), status::WatcherStatus::stopped());
+    let project_watcher = start_project_watcher(
+        app_handle.clone(),
+        &project_dir,
+        &watch,
+        tracking.clone(),
+        status.clone(),
+    )?;
+    let auto_commit = autocommit::start(app_handle.clone(), project_dir.clone(), auto_commit, tracking);
     let resolve_watcher = match resolve_db {
         Some(db_path) => {
             Some(start_resolve_watcher(app_handle, db_path, project_dir)?)
@@ -38,17 +85,175 @@ pub fn start_watching(
     Ok(WatcherHandle {
         _project_watcher: project_watcher,
         _resolve_watcher: resolve_watcher,
+        auto_commit,
+        status,
     })
 }
 
-fn start_project_watcher(app: AppHandle, watch_path: &PathBuf) -> Result<RecommendedWatcher, String> {
-    let (tx, rx) = mpsc::channel();
+fn start_project_watcher(
+    app: AppHandle,
+    watch_path: &Path,
+    config: &status::WatchConfig,
+    tracking: TrackingConfig,
+    status: status::StatusReporter,
+) -> Result<ProjectSource, String> {
+    let network = poll::is_network_filesystem(watch_path);
+    let polling = match config.mode {
+        status::WatchMode::Auto => network,
+        status::WatchMode::Native => false,
+        status::WatchMode::Polling => true,
+    };
+
+    let index = poll::StatIndex::scan(watch_path, &tracking, config.autosave_history).unwrap_or_default();
+    let root = watch_path.to_path_buf();
+    let rel = move |path: &Path| path.strip_prefix(&root).unwrap_or(path).to_path_buf();
+    let mut open_locks: HashSet<PathBuf> = index.paths()
+        .filter(|p| editors::is_lock_file(&rel(p)))
+        .cloned()
+        .collect();
+
+    // A poll sees a file once per interval, so only call it settled after
+    // it has looked the same across a full one.
+    let interval = Duration::from_secs(config.poll_interval_secs.max(1));
+    let quiet = if polling { PROJECT_QUIET_PERIOD.max(interval * 3 / 2) } else { PROJECT_QUIET_PERIOD };
+    let mut debouncer = debounce::Debouncer::new(
+        quiet,
+        PROJECT_MAX_WAIT.max(quiet),
+        index.paths().cloned().collect::<Vec<_>>(),
+    )
+    .with_quiet_only({
+        // Media that keeps growing is a render or export still being written.
+        let tracking = tracking.clone();
+        move |path| !tracking.is_project_path(path)
+    });
+
+    // Both sources send batches of changed paths, or an error to report.
+    let (tx, rx) = mpsc::channel::<Result<Vec<PathBuf>, String>>();
+    let source = if polling {
+        log::info!("Polling {} every {}s", watch_path.display(), interval.as_secs());
+        ProjectSource::Polling {
+            _poller: poll::start(
+                watch_path.to_path_buf(),
+                interval,
+                tracking.clone(),
+                config.autosave_history,
+                index,
+                tx,
+            ),
+        }
+    } else {
+        ProjectSource::Native { _watcher: start_native_watcher(watch_path, tx)? }
+    };
+
+    status.update(|s| {
+        s.mode = if polling { status::WatchMode::Polling } else { status::WatchMode::Native };
+        s.state = status::WatcherState::Running;
+        s.network_filesystem = network;
+    });
+
+    let root = watch_path.to_path_buf();
+    let autosave_history = config.autosave_history;
+    let mut filter = filter::ProjectFilter::new(&root, tracking);
+    std::thread::spawn(move || loop {
+        let received = match debouncer.next_deadline() {
+            Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(Ok(paths)) => {
+                // Polls don't see ignore files change, but already walk the
+                // whole tree each time.
+                if polling || paths.iter().any(|p| p.file_name() == Some(OsStr::new(IGNORE_FILE))) {
+                    filter.reload_ignore_rules();
+                }
+                let now = Instant::now();
+                for path in paths {
+                    let rel_path = rel(&path);
+                    if editors::is_lock_file(&rel_path) {
+                        report_lock(&app, &root, &path, &mut open_locks);
+                    } else if filter.is_project_file(&path)
+                        || (autosave_history
+                            && filter.is_tracked_file(&path)
+                            && editors::autosave_editor(&rel_path).is_some())
+                    {
+                        debouncer.touch(path, now);
+                    }
+                }
+                status.update(|s| {
+                    s.state = status::WatcherState::Running;
+                    s.error = None;
+                });
+            }
+            Ok(Err(e)) => {
+                log::warn!("Project watcher error: {e}");
+                status.update(|s| {
+                    s.state = status::WatcherState::Errored;
+                    s.error = Some(e);
+                });
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        let (autosaves, changes): (Vec<_>, Vec<_>) = debouncer
+            .flush_ready(Instant::now(), |p| p.exists())
+            .into_iter()
+            .partition(|c| editors::autosave_editor(&rel(Path::new(&c.path))).is_some());
+        status.update(|s| {
+            s.backlog = debouncer.pending_len();
+            if !changes.is_empty() || !autosaves.is_empty() {
+                s.last_event_at = Some(chrono::Utc::now().to_rfc3339());
+            }
+        });
+        if !autosaves.is_empty() {
+            let _ = app.emit("editgit://autosave-changed", &autosaves);
+        }
+        if changes.is_empty() {
+            continue;
+        }
+        for change in &changes {
+            let _ = app.emit("editgit://file-changed", change);
+        }
+        let _ = app.emit("editgit://files-changed", &changes);
+    });
+
+    Ok(source)
+}
+
+/// Emit `editgit://project-opened` or `-closed` when a lock file appears or
+/// goes away.
+fn report_lock(app: &AppHandle, root: &Path, lock: &Path, open_locks: &mut HashSet<PathBuf>) {
+    let open = lock.is_file();
+    let changed = if open { open_locks.insert(lock.to_path_buf()) } else { open_locks.remove(lock) };
+    if !changed {
+        return;
+    }
+    let Some(project) = editors::project_for_lock(root, lock) else { return };
+    let event = if open { "editgit://project-opened" } else { "editgit://project-closed" };
+    let _ = app.emit(event, &project);
+}
 
+fn start_native_watcher(
+    watch_path: &Path,
+    tx: mpsc::Sender<Result<Vec<PathBuf>, String>>,
+) -> Result<RecommendedWatcher, String> {
     let mut watcher = RecommendedWatcher::new(
         move |res: Result<notify::Event, notify::Error>| {
-            if let Ok(event) = res {
-                let _ = tx.send(event);
-            }
+            let message = match res {
+                // Reads and permission changes leave content alone.
+                Ok(event) => {
+                    let relevant = matches!(
+                        event.kind,
+                        notify::EventKind::Create(_) | notify::EventKind::Remove(_) | notify::EventKind::Modify(_)
+                    ) && !matches!(event.kind, notify::EventKind::Modify(notify::event::ModifyKind::Metadata(_)));
+                    if !relevant {
+                        return;
+                    }
+                    Ok(event.paths)
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(message);
         },
         Config::default(),
     )
@@ -58,47 +263,6 @@ fn start_project_watcher(app: AppHandle, watch_path: &PathBuf) -> Result<Recomme
         .watch(watch_path, RecursiveMode::Recursive)
         .map_err(|e| format!("Failed to watch project path: {e}"))?;
 
-    let app_clone = app.clone();
-    std::thread::spawn(move || {
-        let mut last_emit = std::time::Instant::now();
-        let debounce = Duration::from_millis(300);
-
-        while let Ok(event) = rx.recv() {
-            let dominated_paths: Vec<_> = event
-                .paths
-                .iter()
-                .filter(|p| filter::is_tracked_file(p))
-                .collect();
-
-            if dominated_paths.is_empty() {
-                continue;
-            }
-
-            let now = std::time::Instant::now();
-            if now.duration_since(last_emit) < debounce {
-                continue;
-            }
-            last_emit = now;
-
-            for path in &dominated_paths {
-                let kind = match event.kind {
-                    notify::EventKind::Create(_) => "created",
-                    notify::EventKind::Modify(_) => "modified",
-                    notify::EventKind::Remove(_) => "removed",
-                    _ => continue,
-                };
-
-                let payload = FileChangeEvent {
-                    path: path.to_string_lossy().to_string(),
-                    kind: kind.to_string(),
-                };
-
-                let _ = app_clone.emit("editgit://file-changed", &payload);
-            }
-
-        }
-    });
-
     Ok(watcher)
 }
 
@@ -135,38 +299,42 @@ fn start_resolve_watcher(
         .to_string_lossy()
         .to_string();
     std::thread::spawn(move || {
-        let mut last_emit = std::time::Instant::now();
-        let debounce = Duration::from_secs(1);
-
-        while let Ok(event) = rx.recv() {
-            let is_related = event.paths.iter().any(|p| {
+        let is_related = |event: &notify::Event| {
+            event.paths.iter().any(|p| {
                 let name = p.file_name().unwrap_or_default().to_string_lossy();
                 name == db_filename
                     || name.starts_with(&format!("{db_filename}-"))
                     || p == &db_path
-            });
-            if !is_related {
-                continue;
-            }
+            })
+        };
 
-            let now = std::time::Instant::now();
-            if now.duration_since(last_emit) < debounce {
+        while let Ok(event) = rx.recv() {
+            if !is_related(&event) {
                 continue;
             }
-            last_emit = now;
+            // Resolve writes in bursts; snapshot once it has gone quiet so
+            // the copy reflects the end of the save, not its first page.
+            loop {
+                match rx.recv_timeout(RESOLVE_QUIET_PERIOD) {
+                    Ok(_) => continue,
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
 
             let dest = project_dir.join("ResolveProject.db");
-            if let Err(e) = std::fs::copy(&db_path, &dest) {
-                log::warn!("Failed to copy Resolve DB to project folder: {e}");
+            if let Err(e) = snapshot::snapshot_database(&db_path, &dest) {
+                log::warn!("Failed to snapshot Resolve DB into project folder: {e}");
                 continue;
             }
 
-            log::info!("Resolve DB change detected, copied to {}", dest.display());
+            log::info!("Resolve DB change detected, snapshot written to {}", dest.display());
             let payload = FileChangeEvent {
                 path: dest.to_string_lossy().to_string(),
                 kind: "modified".to_string(),
             };
             let _ = app_clone.emit("editgit://file-changed", &payload);
+            let _ = app_clone.emit("editgit://files-changed", [&payload]);
         }
     });
 
@@ -0,0 +1,156 @@
+//! Polling for project folders where native file events never arrive,
+//! typically SMB and NFS shares.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+use super::editors;
+use crate::vcs::tracking::TrackingConfig;
+
+/// Filesystem types whose changes native watchers do not see reliably.
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "webdav", "davfs", "ncpfs", "afs",
+    "9p", "ceph", "glusterfs", "lustre", "fuse.sshfs", "fuse.rclone",
+];
+
+/// Size and modification time of every tracked file under a root.
+#[derive(Default)]
+pub struct StatIndex {
+    entries: HashMap<PathBuf, (u64, Option<SystemTime>)>,
+}
+
+impl StatIndex {
+    /// Index the tracked files under `root`, the lock files of any that are
+    /// open, and, with `include_autosaves`, editor autosaves.
+    pub fn scan(root: &Path, tracking: &TrackingConfig, include_autosaves: bool) -> Result<Self, String> {
+        // An unmounted share reads as an empty folder; don't report that as
+        // every file being removed.
+        if !root.is_dir() {
+            return Err(format!("Project folder is unreachable: {}", root.display()));
+        }
+        let files = crate::vcs::commit::scan_tracked_files(root, tracking)
+            .map_err(|e| format!("Failed to scan project folder: {e}"))?;
+        let locks = files.iter()
+            .flat_map(|rel| editors::lock_files_for(&root.join(rel)))
+            .map(|(_, lock)| lock);
+        let autosaves = if include_autosaves { editors::list_autosaves(root) } else { Vec::new() };
+        let autosaves = autosaves.into_iter().map(|a| root.join(a.path));
+        let entries = files.iter()
+            .map(|rel| root.join(rel))
+            .chain(locks)
+            .chain(autosaves)
+            .filter_map(|path| {
+                let meta = std::fs::metadata(&path).ok()?;
+                Some((path, (meta.len(), meta.modified().ok())))
+            })
+            .collect();
+        Ok(Self { entries })
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.entries.keys()
+    }
+
+    /// Paths added, removed or restatted differently in `newer`.
+    pub fn changed_paths(&self, newer: &StatIndex) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = newer.entries.iter()
+            .filter(|(path, stat)| self.entries.get(*path) != Some(stat))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(self.entries.keys().filter(|p| !newer.entries.contains_key(*p)).cloned());
+        changed
+    }
+}
+
+/// Keeps the polling thread alive; dropping it stops polling.
+pub struct Poller {
+    _stop: mpsc::Sender<()>,
+}
+
+/// Rescan `root` every `interval`, sending the paths that changed since the
+/// previous scan, or the error when a scan fails.
+pub fn start(
+    root: PathBuf,
+    interval: Duration,
+    tracking: TrackingConfig,
+    include_autosaves: bool,
+    initial: StatIndex,
+    tx: mpsc::Sender<Result<Vec<PathBuf>, String>>,
+) -> Poller {
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    std::thread::spawn(move || {
+        let mut index = initial;
+        while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+            let message = match StatIndex::scan(&root, &tracking, include_autosaves) {
+                Ok(newer) => {
+                    let changed = index.changed_paths(&newer);
+                    index = newer;
+                    Ok(changed)
+                }
+                Err(e) => Err(e),
+            };
+            if tx.send(message).is_err() {
+                break;
+            }
+        }
+    });
+    Poller { _stop: stop_tx }
+}
+
+/// Whether `path` lives on a network filesystem, going by the mount table.
+pub fn is_network_filesystem(path: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        let s = path.to_string_lossy();
+        if s.starts_with(r"\\?\UNC\") || (s.starts_with(r"\\") && !s.starts_with(r"\\?\")) {
+            return true;
+        }
+    }
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    mount_table()
+        .into_iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| NETWORK_FS_TYPES.contains(&fs_type.as_str()))
+        .unwrap_or(false)
+}
+
+/// `(mount point, filesystem type)` for every mounted filesystem.
+#[cfg(target_os = "linux")]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else {
+        return Vec::new();
+    };
+    mounts.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// Parsed from `mount`, whose lines read `//host/share on /Volumes/share (smbfs, ...)`.
+#[cfg(target_os = "macos")]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    let Ok(output) = std::process::Command::new("/sbin/mount").output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split(',').next()?.trim();
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    Vec::new()
+}
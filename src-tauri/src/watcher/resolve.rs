@@ -1,57 +1,140 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::Serialize;
 
-const RESOLVE_LIBRARY_REL: &str =
-    "Library/Application Support/Blackmagic Design/DaVinci Resolve/Resolve Project Library/Resolve Projects";
+/// Per-user project libraries, relative to the home directory.
+#[cfg(target_os = "macos")]
+const RESOLVE_LIBRARY_RELS: &[&str] = &[
+    "Library/Application Support/Blackmagic Design/DaVinci Resolve/Resolve Project Library",
+];
+#[cfg(target_os = "linux")]
+const RESOLVE_LIBRARY_RELS: &[&str] = &[
+    ".local/share/DaVinciResolve/Resolve Project Library",
+];
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+const RESOLVE_LIBRARY_RELS: &[&str] = &[];
+
+/// System-wide disk databases from older Resolve versions.
+#[cfg(target_os = "macos")]
+const RESOLVE_SYSTEM_LIBRARIES: &[&str] = &[
+    "/Library/Application Support/Blackmagic Design/DaVinci Resolve/Resolve Disk Database",
+];
+#[cfg(target_os = "linux")]
+const RESOLVE_SYSTEM_LIBRARIES: &[&str] = &[
+    "/opt/resolve/Resolve Disk Database",
+];
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+const RESOLVE_SYSTEM_LIBRARIES: &[&str] = &[];
+
+/// Project folders can nest; stop descending past this depth.
+const MAX_FOLDER_DEPTH: usize = 16;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ResolveProject {
     pub name: String,
     pub db_path: String,
+    /// The library or disk database the project was found in.
+    pub library: String,
+    /// The Resolve user that owns the project, e.g. `guest`.
+    pub user: String,
+    /// Project Manager folders above the project, `/`-separated; empty at
+    /// the top level.
+    pub folder: String,
+    /// When `Project.db` was last written, RFC 3339.
+    pub modified_at: Option<String>,
+    pub size_bytes: u64,
 }
 
-/// Locate Resolve's Project Library root for the current user.
-fn resolve_projects_root() -> Option<PathBuf> {
-    dirs::home_dir().map(|h| h.join(RESOLVE_LIBRARY_REL))
+/// Library roots to scan: the platform defaults followed by `extra_roots`,
+/// without duplicates.
+fn resolve_library_roots(extra_roots: &[String]) -> Vec<PathBuf> {
+    let home = dirs::home_dir();
+    let defaults = RESOLVE_LIBRARY_RELS.iter()
+        .filter_map(|rel| home.as_ref().map(|h| h.join(rel)))
+        .chain(RESOLVE_SYSTEM_LIBRARIES.iter().map(PathBuf::from));
+
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for root in defaults.chain(extra_roots.iter().map(PathBuf::from)) {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
 }
 
-/// Scan the Resolve library and return all discovered projects with their
-/// display name (the folder name) and the full path to `Project.db`.
-pub fn list_resolve_projects() -> Vec<ResolveProject> {
-    let root = match resolve_projects_root() {
-        Some(r) if r.is_dir() => r,
-        _ => return Vec::new(),
-    };
+/// The `Resolve Projects` folder of a library. Accepts the library folder
+/// itself or its `Resolve Projects` child, which is what users tend to pick.
+fn projects_dir(root: &Path) -> Option<PathBuf> {
+    let nested = root.join("Resolve Projects");
+    if nested.join("Users").is_dir() {
+        Some(nested)
+    } else if root.join("Users").is_dir() {
+        Some(root.to_path_buf())
+    } else {
+        None
+    }
+}
 
-    let users_dir = root.join("Users");
+/// Scan every Resolve library for projects, including the platform defaults
+/// and `extra_roots`, sorted by folder and name.
+pub fn list_resolve_projects(extra_roots: &[String]) -> Vec<ResolveProject> {
     let mut projects = Vec::new();
+    let mut seen = std::collections::HashSet::new();
 
-    let user_entries = match std::fs::read_dir(&users_dir) {
-        Ok(e) => e,
-        Err(_) => return projects,
-    };
-
-    for user in user_entries.flatten() {
-        let projects_dir = user.path().join("Projects");
-        if !projects_dir.is_dir() {
-            continue;
-        }
-        let proj_entries = match std::fs::read_dir(&projects_dir) {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        for proj in proj_entries.flatten() {
-            let db = proj.path().join("Project.db");
-            if db.is_file() {
-                let name = proj.file_name().to_string_lossy().to_string();
+    for root in resolve_library_roots(extra_roots) {
+        let Some(dir) = projects_dir(&root) else { continue };
+        let Ok(users) = std::fs::read_dir(dir.join("Users")) else { continue };
+        for user in users.flatten() {
+            let projects_dir = user.path().join("Projects");
+            if !projects_dir.is_dir() {
+                continue;
+            }
+            let user_name = user.file_name().to_string_lossy().to_string();
+            let mut found = Vec::new();
+            collect_projects(&projects_dir, "", 0, &mut found);
+            for (folder, project_dir) in found {
+                let db = project_dir.join("Project.db");
+                // The same library can be reachable through two configured roots.
+                if !seen.insert(db.clone()) {
+                    continue;
+                }
+                let meta = std::fs::metadata(&db).ok();
                 projects.push(ResolveProject {
-                    name,
+                    name: project_dir.file_name().unwrap_or_default().to_string_lossy().to_string(),
                     db_path: db.to_string_lossy().to_string(),
+                    library: root.to_string_lossy().to_string(),
+                    user: user_name.clone(),
+                    folder,
+                    modified_at: meta.as_ref()
+                        .and_then(|m| m.modified().ok())
+                        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+                    size_bytes: meta.map(|m| m.len()).unwrap_or(0),
                 });
             }
         }
     }
 
-    projects.sort_by(|a, b| a.name.cmp(&b.name));
+    projects.sort_by(|a, b| a.folder.cmp(&b.folder).then_with(|| a.name.cmp(&b.name)));
     projects
 }
+
+/// A directory holding `Project.db` is a project; any other directory is a
+/// Project Manager folder that may hold more projects.
+fn collect_projects(dir: &Path, folder: &str, depth: usize, out: &mut Vec<(String, PathBuf)>) {
+    if depth > MAX_FOLDER_DEPTH {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.join("Project.db").is_file() {
+            out.push((folder.to_string(), path));
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let child = if folder.is_empty() { name } else { format!("{folder}/{name}") };
+        collect_projects(&path, &child, depth + 1, out);
+    }
+}
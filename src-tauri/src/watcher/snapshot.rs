@@ -0,0 +1,104 @@
+//! Consistent copies of a SQLite database that another process is writing.
+//!
+//! A plain file copy of a live database can catch it between pages, and
+//! misses anything still sitting in its `-wal` file. The online backup API
+//! reads through SQLite's own locking instead, so the copy is always a
+//! committed state.
+
+use std::path::Path;
+use std::time::Duration;
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{Connection, ErrorCode, OpenFlags};
+
+const PAGES_PER_STEP: i32 = 256;
+/// Consecutive busy attempts before giving up on this snapshot.
+const BUSY_RETRIES: u32 = 50;
+const BUSY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Back up `source` into `dest`, replacing it only once the copy passes
+/// `PRAGMA integrity_check`.
+pub fn snapshot_database(source: &Path, dest: &Path) -> Result<(), String> {
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = Path::new(&partial);
+    let _ = std::fs::remove_file(partial);
+
+    let result = backup_to(source, partial).and_then(|_| verify(partial));
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(partial);
+        return Err(e);
+    }
+    std::fs::rename(partial, dest).map_err(|e| format!("Failed to replace {}: {e}", dest.display()))
+}
+
+fn backup_to(source: &Path, dest: &Path) -> Result<(), String> {
+    let src = retry_busy(|| {
+        let conn = Connection::open_with_flags(
+            source,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        // Touch the schema so a database being recovered reports busy here
+        // rather than halfway through the copy.
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))?;
+        Ok(conn)
+    })
+    .map_err(|e| format!("Failed to open {}: {e}", source.display()))?;
+
+    let mut dst = Connection::open(dest)
+        .map_err(|e| format!("Failed to create {}: {e}", dest.display()))?;
+    {
+        let backup = Backup::new(&src, &mut dst).map_err(|e| format!("Failed to start backup: {e}"))?;
+        let mut busy = 0;
+        loop {
+            match backup.step(PAGES_PER_STEP).map_err(|e| format!("Backup failed: {e}"))? {
+                StepResult::Done => break,
+                StepResult::More => busy = 0,
+                StepResult::Busy | StepResult::Locked => {
+                    busy += 1;
+                    if busy > BUSY_RETRIES {
+                        return Err("Backup gave up: source database stayed busy".to_string());
+                    }
+                    std::thread::sleep(BUSY_BACKOFF);
+                }
+                // StepResult is non-exhaustive.
+                _ => std::thread::sleep(BUSY_BACKOFF),
+            }
+        }
+    }
+
+    // The copy inherits WAL mode from the source; keep it a single file.
+    dst.pragma_update(None, "journal_mode", "DELETE")
+        .map_err(|e| format!("Failed to set journal mode on snapshot: {e}"))?;
+    dst.close().map_err(|(_, e)| format!("Failed to close snapshot: {e}"))
+}
+
+fn verify(path: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open snapshot: {e}"))?;
+    let mut stmt = conn.prepare("PRAGMA integrity_check")
+        .map_err(|e| format!("Integrity check failed: {e}"))?;
+    let problems: Vec<String> = stmt.query_map([], |row| row.get(0))
+        .and_then(|rows| rows.collect())
+        .map_err(|e| format!("Integrity check failed: {e}"))?;
+    if problems.len() == 1 && problems[0] == "ok" {
+        Ok(())
+    } else {
+        Err(format!("Snapshot failed integrity check: {}", problems.join("; ")))
+    }
+}
+
+fn retry_busy<T>(mut attempt: impl FnMut() -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+    let mut tries = 0;
+    loop {
+        match attempt() {
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if matches!(e.code, ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+                    && tries < BUSY_RETRIES =>
+            {
+                tries += 1;
+                std::thread::sleep(BUSY_BACKOFF);
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Watch mode configuration and the health the watcher reports back.
+
+use std::sync::Arc;
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use crate::db::{schema, DbError};
+
+const CONFIG_KEY: &str = "watch";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchMode {
+    /// Native events, falling back to polling on network filesystems.
+    #[default]
+    Auto,
+    Native,
+    Polling,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    pub mode: WatchMode,
+    pub poll_interval_secs: u64,
+    /// Report editor autosaves on `editgit://autosave-changed`. They are
+    /// never part of commits either way.
+    pub autosave_history: bool,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self { mode: WatchMode::Auto, poll_interval_secs: 5, autosave_history: false }
+    }
+}
+
+pub fn load_config(conn: &Connection) -> Result<WatchConfig, DbError> {
+    Ok(schema::get_config(conn, CONFIG_KEY)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+pub fn save_config(conn: &Connection, config: &WatchConfig) -> Result<(), DbError> {
+    let json = serde_json::to_string(config).expect("watch config serializes");
+    schema::set_config(conn, CONFIG_KEY, &json)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WatcherState {
+    Running,
+    Errored,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WatcherStatus {
+    /// The mode actually in use, never `auto`.
+    pub mode: WatchMode,
+    pub state: WatcherState,
+    pub network_filesystem: bool,
+    pub error: Option<String>,
+    /// Paths seen changing but not yet reported.
+    pub backlog: usize,
+    /// When changes were last reported, RFC 3339.
+    pub last_event_at: Option<String>,
+}
+
+impl WatcherStatus {
+    pub fn stopped() -> Self {
+        Self {
+            mode: WatchMode::Auto,
+            state: WatcherState::Stopped,
+            network_filesystem: false,
+            error: None,
+            backlog: 0,
+            last_event_at: None,
+        }
+    }
+}
+
+/// Shared status that emits `editgit://watcher-status` whenever it changes.
+#[derive(Clone)]
+pub struct StatusReporter {
+    app: AppHandle,
+    status: Arc<Mutex<WatcherStatus>>,
+}
+
+impl StatusReporter {
+    pub fn new(app: AppHandle, status: WatcherStatus) -> Self {
+        Self { app, status: Arc::new(Mutex::new(status)) }
+    }
+
+    pub fn get(&self) -> WatcherStatus {
+        self.status.lock().clone()
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut WatcherStatus)) {
+        let mut status = self.status.lock();
+        let before = status.clone();
+        f(&mut status);
+        if *status != before {
+            let _ = self.app.emit("editgit://watcher-status", &*status);
+        }
+    }
+}
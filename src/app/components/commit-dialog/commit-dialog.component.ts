@@ -2,6 +2,7 @@ import { Component, input, output, signal } from '@angular/core';
 import { FormsModule } from '@angular/forms';
 import { VcsService } from '../../services/vcs.service';
 import { extractError } from '../../models/error.model';
+import { Rename } from '../../models/commit.model';
 
 @Component({
   selector: 'app-commit-dialog',
@@ -13,6 +14,7 @@ import { extractError } from '../../models/error.model';
 export class CommitDialogComponent {
   isMilestone = input(false);
   changedFiles = input<string[]>([]);
+  renames = input<Rename[]>([]);
   committed = output<void>();
   closed = output<void>();
 
@@ -167,6 +167,7 @@ export class GhostTimelineComponent {
       case 'audio': return 'A';
       case 'image': return 'I';
       case 'project': return 'P';
+      case 'database': return 'D';
       default: return 'F';
     }
   }
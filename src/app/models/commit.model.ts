@@ -6,6 +6,7 @@ export interface Commit {
   message: string;
   is_milestone: boolean;
   created_at: string;
+  is_automatic: boolean;
 }
 
 export interface FileSnapshot {
@@ -15,9 +16,46 @@ export interface FileSnapshot {
   content_hash: string;
   file_size: number;
   file_type: string;
+  /** Editor that wrote a project file, when its content says. */
+  producer: string | null;
+  partial_hash: string | null;
+  /** Path of this file in the parent version, if it moved since. */
+  renamed_from: string | null;
+}
+
+export interface Rename {
+  from: string;
+  to: string;
+}
+
+/** Uncommitted changes since the head version. */
+export interface ChangedFiles {
+  /** Added, modified and removed paths, leaving out both sides of renames. */
+  changed: string[];
+  renames: Rename[];
+}
+
+export interface FileRevision {
+  commit: Commit;
+  snapshot: FileSnapshot;
 }
 
 export interface CommitDetail {
   commit: Commit;
   files: FileSnapshot[];
 }
+
+export type StoragePolicy = 'full_copy' | 'size_limit' | 'reference_only';
+
+export interface FileTypeRule {
+  /** Lowercase, without the dot. */
+  extension: string;
+  file_type: string;
+  storage: StoragePolicy;
+}
+
+export interface TrackingConfig {
+  file_types: FileTypeRule[];
+  /** Largest file, in bytes, that 'size_limit' types copy in full. */
+  full_copy_size_limit: number;
+}
@@ -0,0 +1,48 @@
+export type LutKind = 'OneD' | 'ThreeD';
+
+export type Rgb = [number, number, number];
+
+export interface LutInfo {
+  title: string | null;
+  kind: LutKind;
+  size: number;
+  domain_min: Rgb;
+  domain_max: Rgb;
+}
+
+export interface ChartSample {
+  patch: string;
+  input: Rgb;
+  old_output: Rgb;
+  new_output: Rgb;
+  delta_e: number;
+}
+
+export interface LutComparison {
+  old: LutInfo;
+  new: LutInfo;
+  kind_changed: boolean;
+  size_changed: boolean;
+  domain_changed: boolean;
+  max_delta: Rgb;
+  mean_delta: Rgb;
+  max_delta_input: Rgb;
+  samples_compared: number;
+  chart: ChartSample[];
+}
+
+export interface ImageDiffReport {
+  old_width: number;
+  old_height: number;
+  new_width: number;
+  new_height: number;
+  dimensions_changed: boolean;
+  psnr: number | null;
+  ssim: number;
+  mean_abs_error: number;
+  changed_fraction: number;
+  changed_bounds: [number, number, number, number] | null;
+  heatmap_path: string;
+  old_thumbnail_path: string;
+  new_thumbnail_path: string;
+}
@@ -3,6 +3,11 @@ export interface RationalTime {
   rate: number;
 }
 
+export interface FrameRate {
+  num: number;
+  den: number;
+}
+
 export interface TimeRange {
   start: RationalTime;
   duration: RationalTime;
@@ -15,6 +20,8 @@ export interface Clip {
   media_ref: string | null;
   source_range: TimeRange | null;
   trimmed_range: TimeRange | null;
+  record_start: RationalTime | null;
+  reel: string | null;
 }
 
 export interface Track {
@@ -27,23 +34,49 @@ export interface Timeline {
   name: string;
   tracks: Track[];
   duration: RationalTime | null;
+  global_start_time: RationalTime | null;
+  drop_frame: boolean;
 }
 
 export type DiffStatus = 'Added' | 'Removed' | 'Modified' | 'Unchanged';
 
+export type ClipChange =
+  | { kind: 'TrimmedHead'; frames: number }
+  | { kind: 'TrimmedTail'; frames: number }
+  | { kind: 'Slipped'; frames: number }
+  | { kind: 'Moved'; frames: number }
+  | { kind: 'Reordered' }
+  | { kind: 'Replaced'; old_name: string; old_media_ref: string | null }
+  | { kind: 'MovedTrack'; from_track: number; to_track: number };
+
 export interface ClipDiff {
   name: string;
   status: DiffStatus;
   media_ref: string | null;
+  reel: string | null;
   old_range: TimeRange | null;
   new_range: TimeRange | null;
+  old_record_range: TimeRange | null;
+  new_record_range: TimeRange | null;
+  old_record_timecode: string | null;
+  new_record_timecode: string | null;
+  old_record_out_timecode: string | null;
+  new_record_out_timecode: string | null;
+  changes: ClipChange[];
   track_index: number;
   clip_index: number;
 }
 
+export type TrackStatus = 'Added' | 'Removed' | 'Renamed' | 'Reordered' | 'Matched';
+
 export interface TrackDiff {
   name: string;
   kind: TrackKind;
+  status: TrackStatus;
+  reordered: boolean;
+  old_name: string | null;
+  old_index: number | null;
+  new_index: number | null;
   clips: ClipDiff[];
 }
 
@@ -57,6 +90,199 @@ export interface DiffSummary {
 export interface TimelineDiff {
   old_name: string;
   new_name: string;
+  frame_rate: FrameRate | null;
+  drop_frame: boolean;
   tracks: TrackDiff[];
   summary: DiffSummary;
 }
+
+export interface TimelineRef {
+  path: string;
+  name: string;
+}
+
+export interface PairedTimelineDiff {
+  path: string;
+  name: string;
+  diff: TimelineDiff;
+}
+
+export interface SkippedTimelineFile {
+  path: string;
+  error: string;
+}
+
+export interface CommitTimelineDiff {
+  timelines: PairedTimelineDiff[];
+  added: TimelineRef[];
+  removed: TimelineRef[];
+  summary: DiffSummary;
+  skipped: SkippedTimelineFile[];
+}
+
+export type ReportFormat = 'Csv' | 'Markdown' | 'Html';
+
+export type TrackFilter = 'All' | 'Video' | 'Audio';
+
+export interface ChangeListEntry {
+  event: number;
+  track: string;
+  kind: TrackKind;
+  status: DiffStatus;
+  change: string;
+  clip_name: string;
+  media_ref: string | null;
+  reel: string | null;
+  record_in: string | null;
+  record_out: string | null;
+  old_duration: number | null;
+  new_duration: number | null;
+  duration_delta: number;
+}
+
+export interface ChangeList {
+  title: string;
+  from_label: string;
+  to_label: string;
+  frame_rate: FrameRate | null;
+  drop_frame: boolean;
+  filter: TrackFilter;
+  entries: ChangeListEntry[];
+  affected_media: string[];
+  affected_reels: string[];
+}
+
+export interface ChangeListReport {
+  list: ChangeList;
+  content: string;
+  dest_path: string | null;
+}
+
+export interface TimelineExport {
+  source_path: string;
+  dest_path: string;
+  timeline_name: string;
+  track_count: number;
+  clip_count: number;
+}
+
+export type LintSeverity = 'Error' | 'Warning';
+
+export type LintKind =
+  | 'Overlap'
+  | 'Gap'
+  | 'FlashFrame'
+  | 'MixedFrameRate'
+  | 'NoFrameRate'
+  | 'MediaNotInVersion'
+  | 'MediaOffline';
+
+export interface LintIssue {
+  severity: LintSeverity;
+  kind: LintKind;
+  track: string | null;
+  clip: string | null;
+  record_start: RationalTime | null;
+  timecode: string | null;
+  message: string;
+}
+
+export interface LintOptions {
+  flash_frame_threshold?: number;
+  max_gap_frames?: number;
+}
+
+export interface TimelineValidation {
+  path: string;
+  name: string;
+  errors: number;
+  warnings: number;
+  issues: LintIssue[];
+}
+
+export interface CommitValidation {
+  commit_id: string;
+  timelines: TimelineValidation[];
+  skipped: SkippedTimelineFile[];
+}
+
+export interface MediaUsage {
+  path: string;
+  file_type: string;
+  size: number;
+  in_version: boolean;
+  on_disk: boolean;
+  use_count: number;
+  used_duration: number;
+  timelines: string[];
+}
+
+export interface UnresolvedMedia {
+  media_ref: string;
+  use_count: number;
+  timelines: string[];
+}
+
+export interface MediaUsageReport {
+  used: MediaUsage[];
+  unused: MediaUsage[];
+  unresolved: UnresolvedMedia[];
+  used_bytes: number;
+  unused_bytes: number;
+  total_used_duration: number;
+}
+
+export type LineageEvent = 'Added' | 'Modified' | 'Removed';
+
+export interface ClipLineageEntry {
+  commit_id: string;
+  message: string;
+  created_at: string;
+  is_milestone: boolean;
+  event: LineageEvent;
+  changes: ClipChange[];
+  clip_name: string;
+  record_timecode: string | null;
+  source_range: TimeRange | null;
+}
+
+export interface ClipLineage {
+  file_path: string;
+  timeline_name: string;
+  media_ref: string;
+  entries: ClipLineageEntry[];
+  truncated: boolean;
+}
+
+export type CueChange =
+  | { kind: 'TextEdited'; old_text: string }
+  | { kind: 'Shifted'; frames: number }
+  | { kind: 'Retimed'; start_frames: number; end_frames: number }
+  | { kind: 'StyleChanged'; old_style: string | null };
+
+export interface CueDiff {
+  status: DiffStatus;
+  text: string;
+  style: string | null;
+  old_number: number | null;
+  new_number: number | null;
+  old_start_timecode: string | null;
+  old_end_timecode: string | null;
+  new_start_timecode: string | null;
+  new_end_timecode: string | null;
+  changes: CueChange[];
+}
+
+export interface SubtitleFileDiff {
+  path: string;
+  status: DiffStatus;
+  frame_rate: FrameRate;
+  drop_frame: boolean;
+  cues: CueDiff[];
+  summary: DiffSummary;
+}
+
+export interface CommitSubtitleDiff {
+  files: SubtitleFileDiff[];
+  skipped: SkippedTimelineFile[];
+}
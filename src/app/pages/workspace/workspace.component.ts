@@ -13,6 +13,7 @@ import { VcsService } from '../../services/vcs.service';
 import { WatcherService, FileChangeEvent, ResolveProject } from '../../services/watcher.service';
 import { TimelineService } from '../../services/timeline.service';
 import { extractError } from '../../models/error.model';
+import { Rename } from '../../models/commit.model';
 
 @Component({
   selector: 'app-workspace',
@@ -39,6 +40,7 @@ export class WorkspaceComponent implements OnInit, OnDestroy {
   showCommitDialog = signal(false);
   commitDialogMilestone = signal(false);
   changedFiles = signal<string[]>([]);
+  renames = signal<Rename[]>([]);
   sidebarCollapsed = signal(false);
   sidebarTab = signal<'history' | 'files'>('history');
 
@@ -46,9 +48,12 @@ export class WorkspaceComponent implements OnInit, OnDestroy {
   resolveProjects = signal<ResolveProject[]>([]);
   selectedResolveDb = signal('');
   linkedResolveName = signal<string | null>(null);
+  backupError = signal<string | null>(null);
   loadingResolve = signal(false);
 
   private watcherSub?: Subscription;
+  private autoCommitSub?: Subscription;
+  private backupSub?: Subscription;
   private changeCheckInterval?: ReturnType<typeof setInterval>;
   private lastDismissedAt = 0;
   private readonly CHANGE_CHECK_MS = 30000;
@@ -89,11 +94,21 @@ export class WorkspaceComponent implements OnInit, OnDestroy {
       });
     });
 
+    this.backupSub = this.watcherService.backupFailed$.subscribe((error) => this.backupError.set(error));
+
+    this.autoCommitSub = this.watcherService.autoCommitted$.subscribe(() => {
+      this.changedFiles.set([]);
+      this.renames.set([]);
+      this.vcsService.refreshHistory();
+    });
+
     this.changeCheckInterval = setInterval(() => this.checkForChangesAndShowDialog(), this.CHANGE_CHECK_MS);
   }
 
   ngOnDestroy() {
     this.watcherSub?.unsubscribe();
+    this.autoCommitSub?.unsubscribe();
+    this.backupSub?.unsubscribe();
     if (this.changeCheckInterval) clearInterval(this.changeCheckInterval);
     this.watcherService.stopWatching();
   }
@@ -102,9 +117,10 @@ export class WorkspaceComponent implements OnInit, OnDestroy {
     if (this.showCommitDialog()) return;
     if (Date.now() - this.lastDismissedAt < this.DISMISS_COOLDOWN_MS) return;
     try {
-      const files = await this.vcsService.getChangedFiles();
-      if (files.length > 0) {
-        this.changedFiles.set(files);
+      const { changed, renames } = await this.vcsService.getChangedFiles();
+      if (changed.length > 0 || renames.length > 0) {
+        this.changedFiles.set(changed);
+        this.renames.set(renames);
       }
     } catch {
       // ignore (e.g. no project)
@@ -114,13 +130,14 @@ export class WorkspaceComponent implements OnInit, OnDestroy {
   async openCommitDialog(isMilestone = false) {
     this.commitDialogMilestone.set(isMilestone);
     try {
-      const fromBackend = await this.vcsService.getChangedFiles();
-      if (fromBackend.length > 0) {
-        this.changedFiles.update((existing) => {
-          const combined = new Set([...existing, ...fromBackend]);
-          return [...combined];
-        });
-      }
+      const { changed, renames } = await this.vcsService.getChangedFiles();
+      // The watcher reports both sides of a move as changes.
+      const moved = new Set(renames.flatMap((r) => [r.from, r.to]));
+      this.changedFiles.update((existing) => {
+        const combined = new Set([...existing, ...changed]);
+        return [...combined].filter((f) => !moved.has(f));
+      });
+      this.renames.set(renames);
     } catch (e: unknown) {
       console.warn('Could not refresh changed files:', extractError(e).message);
     }
@@ -130,6 +147,7 @@ export class WorkspaceComponent implements OnInit, OnDestroy {
   async onCommitCreated() {
     this.showCommitDialog.set(false);
     this.changedFiles.set([]);
+    this.renames.set([]);
     await this.vcsService.refreshHistory();
   }
 
@@ -179,6 +197,11 @@ export class WorkspaceComponent implements OnInit, OnDestroy {
     }
   }
 
+  resolveProjectLabel(rp: ResolveProject): string {
+    const name = rp.folder ? `${rp.folder} / ${rp.name}` : rp.name;
+    return rp.modified_at ? `${name} — ${new Date(rp.modified_at).toLocaleString()}` : name;
+  }
+
   private async loadLinkedResolveName() {
     try {
       const linked = await this.watcherService.getLinkedResolveProject();
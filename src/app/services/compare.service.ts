@@ -0,0 +1,26 @@
+import { Injectable } from '@angular/core';
+import { TauriService } from './tauri.service';
+import { ImageDiffReport, LutComparison } from '../models/compare.model';
+
+@Injectable({ providedIn: 'root' })
+export class CompareService {
+  constructor(private tauri: TauriService) {}
+
+  async compareLuts(
+    commitA: string,
+    commitB: string,
+    filePath: string,
+    includeChart = false,
+  ): Promise<LutComparison> {
+    return this.tauri.invoke<LutComparison>('compare_luts', {
+      commitA,
+      commitB,
+      filePath,
+      includeChart,
+    });
+  }
+
+  async compareImages(commitA: string, commitB: string, filePath: string): Promise<ImageDiffReport> {
+    return this.tauri.invoke<ImageDiffReport>('compare_images', { commitA, commitB, filePath });
+  }
+}
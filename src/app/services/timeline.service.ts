@@ -1,10 +1,21 @@
 import { Injectable, signal } from '@angular/core';
 import { TauriService } from './tauri.service';
-import { TimelineDiff } from '../models/timeline.model';
+import {
+  ChangeListReport,
+  ClipLineage,
+  CommitSubtitleDiff,
+  CommitTimelineDiff,
+  CommitValidation,
+  LintOptions,
+  MediaUsageReport,
+  ReportFormat,
+  TimelineExport,
+  TrackFilter,
+} from '../models/timeline.model';
 
 @Injectable({ providedIn: 'root' })
 export class TimelineService {
-  private _diff = signal<TimelineDiff | null>(null);
+  private _diff = signal<CommitTimelineDiff | null>(null);
   private _loading = signal(false);
 
   readonly diff = this._diff.asReadonly();
@@ -12,10 +23,10 @@ export class TimelineService {
 
   constructor(private tauri: TauriService) {}
 
-  async getTimelineDiff(commitA: string, commitB: string): Promise<TimelineDiff> {
+  async getTimelineDiff(commitA: string, commitB: string): Promise<CommitTimelineDiff> {
     this._loading.set(true);
     try {
-      const diff = await this.tauri.invoke<TimelineDiff>('get_timeline_diff', {
+      const diff = await this.tauri.invoke<CommitTimelineDiff>('get_timeline_diff', {
         commitA,
         commitB,
       });
@@ -26,6 +37,74 @@ export class TimelineService {
     }
   }
 
+  async generateChangeList(
+    commitA: string,
+    commitB: string,
+    format: ReportFormat,
+    trackFilter: TrackFilter = 'All',
+    destPath?: string,
+    timeline?: { filePath?: string; name?: string },
+  ): Promise<ChangeListReport> {
+    return this.tauri.invoke<ChangeListReport>('generate_change_list', {
+      commitA,
+      commitB,
+      format,
+      trackFilter,
+      filePath: timeline?.filePath ?? null,
+      timelineName: timeline?.name ?? null,
+      destPath: destPath ?? null,
+    });
+  }
+
+  async exportTimelineOtio(
+    commitId: string,
+    destPath: string,
+    timeline?: { filePath?: string; name?: string },
+  ): Promise<TimelineExport> {
+    return this.tauri.invoke<TimelineExport>('export_timeline_otio', {
+      commitId,
+      filePath: timeline?.filePath ?? null,
+      timelineName: timeline?.name ?? null,
+      destPath,
+    });
+  }
+
+  async validateTimelines(commitId: string, options?: LintOptions): Promise<CommitValidation> {
+    return this.tauri.invoke<CommitValidation>('validate_timelines', {
+      commitId,
+      options: options ?? null,
+    });
+  }
+
+  async getMediaUsage(commitId: string): Promise<MediaUsageReport> {
+    return this.tauri.invoke<MediaUsageReport>('get_media_usage', { commitId });
+  }
+
+  async getClipLineage(
+    commitId: string,
+    mediaRef: string,
+    recordTimecode: string,
+    filePath?: string,
+    timelineName?: string,
+  ): Promise<ClipLineage> {
+    return this.tauri.invoke<ClipLineage>('get_clip_lineage', {
+      commitId,
+      mediaRef,
+      recordTimecode,
+      filePath: filePath ?? null,
+      timelineName: timelineName ?? null,
+      maxDepth: null,
+    });
+  }
+
+  async getSubtitleDiff(commitA: string, commitB: string, frameRate?: number): Promise<CommitSubtitleDiff> {
+    return this.tauri.invoke<CommitSubtitleDiff>('get_subtitle_diff', {
+      commitA,
+      commitB,
+      frameRate: frameRate ?? null,
+    });
+  }
+
   clearDiff() {
     this._diff.set(null);
   }
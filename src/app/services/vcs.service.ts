@@ -1,6 +1,6 @@
 import { Injectable, signal, computed } from '@angular/core';
 import { TauriService } from './tauri.service';
-import { Commit, CommitDetail, FileSnapshot } from '../models/commit.model';
+import { ChangedFiles, Commit, CommitDetail, FileRevision, FileSnapshot, TrackingConfig } from '../models/commit.model';
 import { Branch } from '../models/project.model';
 import { extractError } from '../models/error.model';
 
@@ -93,6 +93,11 @@ export class VcsService {
     return this.tauri.invoke<CommitDetail>('get_commit_detail', { commitId });
   }
 
+  /** Versions of a file back from a commit, following it across renames. */
+  async getFileHistory(commitId: string, filePath: string, limit = 100): Promise<FileRevision[]> {
+    return this.tauri.invoke<FileRevision[]>('get_file_history', { commitId, filePath, limit });
+  }
+
   async getBranches(): Promise<Branch[]> {
     const branches = await this.tauri.invoke<Branch[]>('get_branches');
     this._branches.set(branches);
@@ -133,8 +138,20 @@ export class VcsService {
   }
 
   /** Compare disk state to head commit. Catches changes the file watcher may miss (e.g. DaVinci Resolve atomic saves). */
-  async getChangedFiles(): Promise<string[]> {
-    return this.tauri.invoke<string[]>('get_changed_files');
+  async getChangedFiles(): Promise<ChangedFiles> {
+    return this.tauri.invoke<ChangedFiles>('get_changed_files');
+  }
+
+  async getTrackingConfig(): Promise<TrackingConfig> {
+    return this.tauri.invoke<TrackingConfig>('get_tracking_config');
+  }
+
+  async getDefaultTrackingConfig(): Promise<TrackingConfig> {
+    return this.tauri.invoke<TrackingConfig>('get_default_tracking_config');
+  }
+
+  async setTrackingConfig(config: TrackingConfig): Promise<void> {
+    await this.tauri.invoke('set_tracking_config', { config });
   }
 
   async focusWindow(): Promise<void> {
@@ -2,26 +2,85 @@ import { Injectable, signal } from '@angular/core';
 import { TauriService } from './tauri.service';
 import { Subject } from 'rxjs';
 import type { UnlistenFn } from '@tauri-apps/api/event';
+import type { Commit } from '../models/commit.model';
 
 export interface FileChangeEvent {
   path: string;
   kind: string;
 }
 
+export type WatchMode = 'auto' | 'native' | 'polling';
+
+export interface WatchConfig {
+  mode: WatchMode;
+  poll_interval_secs: number;
+  /** Report editor autosaves on autosaveChanged$; they are never committed. */
+  autosave_history: boolean;
+}
+
+/** A project an editor holds a lock file for. */
+export interface OpenProject {
+  project_path: string;
+  editor: string;
+  lock_path: string;
+}
+
+export interface AutosaveFile {
+  path: string;
+  editor: string;
+  size_bytes: number;
+  modified_at: string | null;
+}
+
+export interface WatcherStatus {
+  /** The mode in use; never 'auto'. */
+  mode: WatchMode;
+  state: 'running' | 'errored' | 'stopped';
+  network_filesystem: boolean;
+  error: string | null;
+  backlog: number;
+  last_event_at: string | null;
+}
+
+export type AutoCommitPolicy =
+  | { kind: 'AfterIdle'; seconds: number }
+  | { kind: 'Periodic'; minutes: number };
+
+export interface AutoCommitConfig {
+  enabled: boolean;
+  policies: AutoCommitPolicy[];
+}
+
 export interface ResolveProject {
   name: string;
   db_path: string;
+  library: string;
+  user: string;
+  /** Project Manager folders above the project, '/'-separated. */
+  folder: string;
+  modified_at: string | null;
+  size_bytes: number;
 }
 
 @Injectable({ providedIn: 'root' })
 export class WatcherService {
   private _watching = signal(false);
-  private _unlisten: UnlistenFn | null = null;
+  private _unlisten: UnlistenFn[] = [];
   private _linkedResolve = signal<string | null>(null);
+  private _status = signal<WatcherStatus | null>(null);
 
   readonly watching = this._watching.asReadonly();
   readonly linkedResolve = this._linkedResolve.asReadonly();
+  readonly status = this._status.asReadonly();
   readonly fileChanged$ = new Subject<FileChangeEvent>();
+  /** Every change reported after one quiet period, one entry per file. */
+  readonly filesChanged$ = new Subject<FileChangeEvent[]>();
+  readonly autoCommitted$ = new Subject<Commit>();
+  readonly projectOpened$ = new Subject<OpenProject>();
+  readonly projectClosed$ = new Subject<OpenProject>();
+  readonly autosaveChanged$ = new Subject<FileChangeEvent[]>();
+  /** Why the backup after a commit failed. */
+  readonly backupFailed$ = new Subject<string>();
 
   constructor(private tauri: TauriService) {}
 
@@ -29,25 +88,78 @@ export class WatcherService {
     await this.tauri.invoke('start_watching');
     this._watching.set(true);
 
-    this._unlisten = await this.tauri.listen<FileChangeEvent>(
-      'editgit://file-changed',
-      (event) => this.fileChanged$.next(event),
-    );
+    this._unlisten = [
+      await this.tauri.listen<FileChangeEvent>(
+        'editgit://file-changed',
+        (event) => this.fileChanged$.next(event),
+      ),
+      await this.tauri.listen<FileChangeEvent[]>(
+        'editgit://files-changed',
+        (events) => this.filesChanged$.next(events),
+      ),
+      await this.tauri.listen<Commit>(
+        'editgit://auto-committed',
+        (commit) => this.autoCommitted$.next(commit),
+      ),
+      await this.tauri.listen<string>(
+        'editgit://backup-failed',
+        (error) => this.backupFailed$.next(error),
+      ),
+      await this.tauri.listen<OpenProject>(
+        'editgit://project-opened',
+        (project) => this.projectOpened$.next(project),
+      ),
+      await this.tauri.listen<OpenProject>(
+        'editgit://project-closed',
+        (project) => this.projectClosed$.next(project),
+      ),
+      await this.tauri.listen<FileChangeEvent[]>(
+        'editgit://autosave-changed',
+        (events) => this.autosaveChanged$.next(events),
+      ),
+      await this.tauri.listen<WatcherStatus>(
+        'editgit://watcher-status',
+        (status) => this._status.set(status),
+      ),
+    ];
+    await this.getStatus();
   }
 
   async stopWatching(): Promise<void> {
     await this.tauri.invoke('stop_watching');
     this._watching.set(false);
-    if (this._unlisten) {
-      this._unlisten();
-      this._unlisten = null;
-    }
+    this._unlisten.forEach((unlisten) => unlisten());
+    this._unlisten = [];
+    this._status.set(null);
+  }
+
+  async getStatus(): Promise<WatcherStatus> {
+    const status = await this.tauri.invoke<WatcherStatus>('get_watcher_status');
+    this._status.set(status);
+    return status;
+  }
+
+  async getWatchConfig(): Promise<WatchConfig> {
+    return this.tauri.invoke<WatchConfig>('get_watch_config');
+  }
+
+  /** Takes effect the next time watching starts. */
+  async setWatchConfig(config: WatchConfig): Promise<void> {
+    await this.tauri.invoke('set_watch_config', { config });
   }
 
   async listResolveProjects(): Promise<ResolveProject[]> {
     return this.tauri.invoke<ResolveProject[]>('list_resolve_projects');
   }
 
+  async getResolveLibraryRoots(): Promise<string[]> {
+    return this.tauri.invoke<string[]>('get_resolve_library_roots');
+  }
+
+  async setResolveLibraryRoots(roots: string[]): Promise<void> {
+    await this.tauri.invoke('set_resolve_library_roots', { roots });
+  }
+
   async linkResolveProject(dbPath: string): Promise<void> {
     await this.tauri.invoke('link_resolve_project', { dbPath });
     this._linkedResolve.set(dbPath);
@@ -63,4 +175,20 @@ export class WatcherService {
     this._linkedResolve.set(path);
     return path;
   }
+
+  async getOpenProjects(): Promise<OpenProject[]> {
+    return this.tauri.invoke<OpenProject[]>('get_open_projects');
+  }
+
+  async listAutosaves(): Promise<AutosaveFile[]> {
+    return this.tauri.invoke<AutosaveFile[]>('list_autosaves');
+  }
+
+  async getAutoCommitConfig(): Promise<AutoCommitConfig> {
+    return this.tauri.invoke<AutoCommitConfig>('get_auto_commit_config');
+  }
+
+  async setAutoCommitConfig(config: AutoCommitConfig): Promise<void> {
+    await this.tauri.invoke('set_auto_commit_config', { config });
+  }
 }